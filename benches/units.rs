@@ -0,0 +1,70 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Benchmarks for unit arithmetic. Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use calc::units::{Number, Unit, AMPERE, FOOT, KILOGRAM, METER, OHM, SECOND, VOLT};
+
+/// Runs `f` repeatedly and prints the average time per iteration.
+fn bench<T>(name: &str, iterations: u32, f: impl Fn() -> T) {
+    // Warm up.
+    for _ in 0..iterations / 10 {
+        black_box(f());
+    }
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{name:32} {:>12.0} ns/iter",
+        elapsed.as_secs_f64() * 1e9 / f64::from(iterations)
+    );
+}
+
+fn main() {
+    let speed = Number::new(3.0).with_unit((METER / SECOND).unwrap());
+    let resistance = Number::new(2.0).with_unit(OHM.clone());
+    let voltage = Number::new(5.0).with_unit(VOLT.clone());
+    let mixed = Number::new(7.0).with_unit(Unit::new(&[METER, KILOGRAM], &[FOOT, AMPERE]).unwrap());
+
+    bench("pow 2", 100_000, || speed.pow(&Number::new(2.0)).unwrap());
+    bench("pow 50", 10_000, || speed.pow(&Number::new(50.0)).unwrap());
+    bench("pow 500", 1_000, || speed.pow(&Number::new(500.0)).unwrap());
+    bench("root 2 of pow 100", 1_000, || {
+        speed
+            .pow(&Number::new(100.0))
+            .unwrap()
+            .root(&Number::new(2.0))
+            .unwrap()
+    });
+    bench("mul derived", 100_000, || (&voltage * &resistance).unwrap());
+    bench("div derived", 100_000, || (&voltage / &resistance).unwrap());
+    bench("mul mixed", 100_000, || (&mixed * &mixed).unwrap());
+    let volt_per_ampere = (&*VOLT / AMPERE).unwrap();
+    bench("convert derived", 100_000, || {
+        OHM.convert(black_box(1.0), &volt_per_ampere).unwrap()
+    });
+    bench("commensurable", 1_000_000, || {
+        VOLT.is_commensurable_with(black_box(&OHM))
+    });
+}
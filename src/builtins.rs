@@ -19,12 +19,24 @@
 
 use std::collections::HashMap;
 
-use crate::{commit, pop_as_f, pop_as_ff, pop_as_fu, pop_as_i, pop_as_ii, popn, popnn};
+use approx::relative_eq;
+
+use crate::{
+    commit, pop_as_f, pop_as_ff, pop_as_i, pop_as_ii, popf, popm, popn, popnn, popu, popv, popvv,
+};
 use crate::{
-    integer, stack,
+    constants, distributions, eval,
+    eval::{Block, Context},
+    integer,
+    matrix::Matrix,
+    number_theory, polynomial,
+    random::Rng,
+    settings::Settings,
+    solver, special, stack,
     stack::Stack,
     units,
     units::{Number, Unit, JOULE, METER, RADIAN, SECOND},
+    vector::Vector,
 };
 
 /// An error that occurred while executing a builtin.
@@ -42,6 +54,55 @@ pub enum Error {
     NotNonNegative,
     /// A number was expected to be whole but had a fractional part.
     NotWhole,
+    /// A number was expected to be positive but was zero or negative.
+    NotPositive,
+    /// The lower bound of a range was greater than the upper bound.
+    BoundsReversed,
+    /// A number was expected to be a probability but wasn't between 0 and 1.
+    NotAProbability,
+    /// The result of an integer operation, or a number that has to be an
+    /// integer, is too large.
+    Overflow,
+    /// A number has no multiplicative inverse modulo the modulus.
+    NotInvertible,
+    /// Two vectors were expected to have the same length but didn't.
+    LengthMismatch,
+    /// A vector was expected to have three elements but didn't.
+    NotThreeDimensional,
+    /// A vector was expected to have a direction but was zero.
+    ZeroVector,
+    /// A `]` was found without a matching `[`.
+    UnmatchedBracket,
+    /// A `[ ]` held both vectors and items that weren't vectors.
+    MixedBracket,
+    /// Two matrices, or a matrix and a vector, had shapes that don't fit the
+    /// operation.
+    ShapeMismatch,
+    /// A matrix was expected to be square but wasn't.
+    NotSquare,
+    /// A matrix was expected to be invertible but was singular.
+    Singular,
+    /// An index was past the end of a matrix.
+    IndexOutOfRange,
+    /// A polynomial was expected to be non-zero but all of its coefficients
+    /// were zero.
+    ZeroPolynomial,
+    /// An error occurred while evaluating a word in a block.
+    Block {
+        word: String,
+        error: Box<eval::Error>,
+    },
+    /// A block was expected to leave a result on the stack but left nothing.
+    NoResult,
+    /// No root of a function could be found near the initial guess.
+    NoConvergence,
+    /// No combination of the quantities on the stack has the requested
+    /// dimension.
+    NoCombination,
+    /// A number was expected to be one of the physical constants.
+    NotAConstant,
+    /// The exchange-rate file couldn't be loaded.
+    Rates(units::currency::Error),
 }
 
 /// Enables the `?` operator inside implementations of builtins.
@@ -62,53 +123,108 @@ impl From<units::Error> for Error {
 type Result = std::result::Result<(), Error>;
 
 /// A function that implements a builtin.
-pub type Builtin = fn(&mut Stack) -> Result;
+pub type Builtin = fn(&mut Context) -> Result;
 
 /// A table of builtin function names and their implementations.
 pub type Table = HashMap<&'static str, Builtin>;
 
+/// Expresses the result of an arithmetic operation in the user's unit system,
+/// if one is selected, and in a named unit, if the user has enabled automatic
+/// naming and a suitable unit is eligible.
+fn named(settings: &Settings, item: stack::Item) -> stack::Item {
+    let item = match (item, &settings.system) {
+        (stack::Item::Float(x), Some(system)) => stack::Item::Float(system.normalize(&x)),
+        (stack::Item::Vector(v), Some(system)) => {
+            stack::Item::Vector(v.map(|x| Ok(system.normalize(x))).unwrap_or(v))
+        }
+        (stack::Item::Matrix(m), Some(system)) => {
+            stack::Item::Matrix(m.map(|x| Ok(system.normalize(x))).unwrap_or(m))
+        }
+        (item, _) => item,
+    };
+    if !settings.autoname {
+        return item;
+    }
+    let candidates = settings.name_candidates();
+    match item {
+        stack::Item::Float(x) => stack::Item::Float(x.named(&candidates)),
+        stack::Item::Unit(u) => {
+            // A bare unit can only be renamed if doing so doesn't change its
+            // magnitude.
+            let n = u
+                .named(&candidates)
+                .filter(|n| u.convert(1.0, n).is_ok_and(|x| relative_eq!(x, 1.0)));
+            stack::Item::Unit(n.cloned().unwrap_or(u))
+        }
+        stack::Item::Vector(v) => {
+            stack::Item::Vector(v.map(|x| Ok(x.named(&candidates))).unwrap_or(v))
+        }
+        stack::Item::Matrix(m) => {
+            stack::Item::Matrix(m.map(|x| Ok(x.named(&candidates))).unwrap_or(m))
+        }
+        stack::Item::Block(_) | stack::Item::Integer(_) => item,
+    }
+}
+
 /// `( a b -- a+b )` Pops two items, adds them, and pushes the result.
+/// Either item may be a vector or matrix, in which case the items are added
+/// element by element.
 ///
 /// # Errors
 ///
 /// An error occurs if:
 /// - there are fewer than two items on the stack;
-/// - the items are not numbers; or,
+/// - the items are not numbers, vectors, or matrices;
+/// - the items are vectors with different lengths;
+/// - the items are matrices with different shapes; or,
 /// - the items have incommensurable units.
-#[allow(clippy::missing_panics_doc)]
-pub fn builtin_add(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, b) = popnn!(tx)?;
-    match (a, b) {
-        (stack::Item::Float(a), stack::Item::Float(b)) => tx.pushf((&a + &b)?),
-        (stack::Item::Float(a), stack::Item::Integer(b)) => tx.pushf((&a + &b.as_units_number())?),
-        (stack::Item::Integer(a), stack::Item::Float(b)) => tx.pushf((&a + &b)?),
-        (stack::Item::Integer(a), stack::Item::Integer(b)) => tx.pushi(&a + &b),
-        _ => panic!("invariant wasn't"),
-    }
+pub fn builtin_add(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let result = match tx.pop2()? {
+        (a @ (stack::Item::Vector(_) | stack::Item::Matrix(_)), b)
+        | (a, b @ (stack::Item::Vector(_) | stack::Item::Matrix(_))) => {
+            broadcast(a, b, |x, y| x + y)?
+        }
+        (stack::Item::Float(a), stack::Item::Float(b)) => stack::Item::Float((&a + &b)?),
+        (stack::Item::Float(a), stack::Item::Integer(b)) => {
+            stack::Item::Float((&a + &b.as_units_number())?)
+        }
+        (stack::Item::Integer(a), stack::Item::Float(b)) => stack::Item::Float((&a + &b)?),
+        (stack::Item::Integer(a), stack::Item::Integer(b)) => stack::Item::Integer(&a + &b),
+        _ => return Err(stack::Error::TypeMismatch.into()),
+    };
+    tx.push(named(&ctx.settings, result));
     commit!(tx)
 }
 
 /// `( a b -- a-b )` Pops two items, subtracts the upper item from the lower
-/// item, and pushes the result.
+/// item, and pushes the result. Either item may be a vector or matrix, in which
+/// case the items are subtracted element by element.
 ///
 /// # Errors
 ///
 /// An error occurs if:
 /// - there are fewer than two items on the stack;
-/// - the items are not numbers; or,
+/// - the items are not numbers, vectors, or matrices;
+/// - the items are vectors with different lengths;
+/// - the items are matrices with different shapes; or,
 /// - the items have incommensurable units.
-#[allow(clippy::missing_panics_doc)]
-pub fn builtin_sub(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, b) = popnn!(tx)?;
-    match (a, b) {
-        (stack::Item::Float(a), stack::Item::Float(b)) => tx.pushf((&a - &b)?),
-        (stack::Item::Float(a), stack::Item::Integer(b)) => tx.pushf((&a - &b.as_units_number())?),
-        (stack::Item::Integer(a), stack::Item::Float(b)) => tx.pushf((&a - &b)?),
-        (stack::Item::Integer(a), stack::Item::Integer(b)) => tx.pushi(&a - &b),
-        _ => panic!("invariant wasn't"),
-    }
+pub fn builtin_sub(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let result = match tx.pop2()? {
+        (a @ (stack::Item::Vector(_) | stack::Item::Matrix(_)), b)
+        | (a, b @ (stack::Item::Vector(_) | stack::Item::Matrix(_))) => {
+            broadcast(a, b, |x, y| x - y)?
+        }
+        (stack::Item::Float(a), stack::Item::Float(b)) => stack::Item::Float((&a - &b)?),
+        (stack::Item::Float(a), stack::Item::Integer(b)) => {
+            stack::Item::Float((&a - &b.as_units_number())?)
+        }
+        (stack::Item::Integer(a), stack::Item::Float(b)) => stack::Item::Float((&a - &b)?),
+        (stack::Item::Integer(a), stack::Item::Integer(b)) => stack::Item::Integer(&a - &b),
+        _ => return Err(stack::Error::TypeMismatch.into()),
+    };
+    tx.push(named(&ctx.settings, result));
     commit!(tx)
 }
 
@@ -118,9 +234,18 @@ pub fn builtin_sub(stack: &mut Stack) -> Result {
 /// - two numbers
 /// - two units
 /// - `a` is a number and `b` is a unit
+/// - a vector and a number, or two vectors of the same length, which are
+///   multiplied element by element
+/// - `a` is a vector and `b` is a unit
+/// - two matrices, where `a` has as many columns as `b` has rows, or a matrix
+///   `a` and a vector `b` with as many elements as `a` has columns, which are
+///   multiplied as matrices
+/// - a matrix and a number, which are multiplied element by element
+/// - `a` is a matrix and `b` is a unit
 ///
 /// Multiplying two units produces a new derived unit. Multiplying a number by
-/// a unit is equivalent to multiplying by one of that unit.
+/// a unit is equivalent to multiplying by one of that unit. Multiplying a
+/// number with a named unit, like psi, by a plain number keeps the unit.
 ///
 /// # Errors
 ///
@@ -129,20 +254,45 @@ pub fn builtin_sub(stack: &mut Stack) -> Result {
 /// - the items are not two numbers;
 /// - the items are not two units; or,
 /// - the items are not a number `a` and a unit `b`.
-/// - the operation would result in a nonsensical temperature unit.
-pub fn builtin_mul(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
+/// - the items are vectors with different lengths;
+/// - the items are matrices, or a matrix and a vector, whose shapes don't
+///   match;
+/// - the operation would result in a nonsensical temperature unit, such as
+///   scaling a temperature like tempC.
+pub fn builtin_mul(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
     let items = tx.pop2()?;
-    match items {
-        (stack::Item::Float(a), stack::Item::Float(b)) => tx.pushf((&a * &b)?),
-        (stack::Item::Float(a), stack::Item::Integer(b)) => tx.pushf((&a * &b.as_units_number())?),
-        (stack::Item::Integer(a), stack::Item::Float(b)) => tx.pushf((&a * &b)?),
-        (stack::Item::Integer(a), stack::Item::Integer(b)) => tx.pushi(&a * &b),
-        (stack::Item::Unit(a), stack::Item::Unit(b)) => tx.pushu((&a * &b)?),
-        (stack::Item::Float(a), stack::Item::Unit(b)) => tx.pushf((&a * &b)?),
-        (stack::Item::Integer(a), stack::Item::Unit(b)) => tx.pushf((&a * &b)?),
+    let result = match items {
+        (stack::Item::Vector(a), stack::Item::Unit(b)) => stack::Item::Vector(a.map(|x| x * &b)?),
+        (stack::Item::Matrix(a), stack::Item::Unit(b)) => stack::Item::Matrix(a.map(|x| x * &b)?),
+        (stack::Item::Matrix(a), stack::Item::Matrix(b)) => {
+            if a.cols != b.rows {
+                return Err(Error::ShapeMismatch);
+            }
+            stack::Item::Matrix(a.mul(&b)?)
+        }
+        (stack::Item::Matrix(a), stack::Item::Vector(b)) => {
+            if a.cols != b.len() {
+                return Err(Error::ShapeMismatch);
+            }
+            stack::Item::Vector(a.mul_vector(&b)?)
+        }
+        (a @ (stack::Item::Vector(_) | stack::Item::Matrix(_)), b)
+        | (a, b @ (stack::Item::Vector(_) | stack::Item::Matrix(_))) => {
+            broadcast(a, b, |x, y| x * y)?
+        }
+        (stack::Item::Float(a), stack::Item::Float(b)) => stack::Item::Float((&a * &b)?),
+        (stack::Item::Float(a), stack::Item::Integer(b)) => {
+            stack::Item::Float((&a * &b.as_units_number())?)
+        }
+        (stack::Item::Integer(a), stack::Item::Float(b)) => stack::Item::Float((&a * &b)?),
+        (stack::Item::Integer(a), stack::Item::Integer(b)) => stack::Item::Integer(&a * &b),
+        (stack::Item::Unit(a), stack::Item::Unit(b)) => stack::Item::Unit((&a * &b)?),
+        (stack::Item::Float(a), stack::Item::Unit(b)) => stack::Item::Float((&a * &b)?),
+        (stack::Item::Integer(a), stack::Item::Unit(b)) => stack::Item::Float((&a * &b)?),
         _ => return Err(stack::Error::TypeMismatch.into()),
     };
+    tx.push(named(&ctx.settings, result));
     commit!(tx)
 }
 
@@ -152,6 +302,11 @@ pub fn builtin_mul(stack: &mut Stack) -> Result {
 /// - two numbers
 /// - two units
 /// - `a` is a number and `b` is a unit
+/// - a vector and a number, or two vectors of the same length, which are
+///   divided element by element
+/// - `a` is a vector and `b` is a unit
+/// - a matrix and a number, which are divided element by element
+/// - `a` is a matrix and `b` is a unit
 ///
 /// Dividing two units produces a new derived unit. Dividing a number by a unit
 /// is equivalent to multiplying the number by the unit's inverse.
@@ -163,41 +318,100 @@ pub fn builtin_mul(stack: &mut Stack) -> Result {
 /// - the items are not two numbers;
 /// - the items are not two units;
 /// - the items are not a number `a` and a unit `b`;
-/// - the operation would result in a nonsensical temperature unit.
-pub fn builtin_div(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
+/// - the items are vectors with different lengths;
+/// - both items are matrices;
+/// - the operation would result in a nonsensical temperature unit, such as
+///   scaling a temperature like tempC.
+pub fn builtin_div(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
     let items = tx.pop2()?;
-    match items {
-        (stack::Item::Float(a), stack::Item::Float(b)) => tx.pushf((&a / &b)?),
-        (stack::Item::Float(a), stack::Item::Integer(b)) => tx.pushf((&a / &b.as_units_number())?),
-        (stack::Item::Integer(a), stack::Item::Float(b)) => tx.pushf((&a / &b)?),
-        (stack::Item::Integer(a), stack::Item::Integer(b)) => tx.pushf(&a / &b),
-        (stack::Item::Unit(a), stack::Item::Unit(b)) => tx.pushu((&a / &b)?),
-        (stack::Item::Float(a), stack::Item::Unit(b)) => tx.pushf((&a / &b)?),
-        (stack::Item::Integer(a), stack::Item::Unit(b)) => tx.pushf((&a / &b)?),
+    let result = match items {
+        (stack::Item::Vector(a), stack::Item::Unit(b)) => stack::Item::Vector(a.map(|x| x / &b)?),
+        (stack::Item::Matrix(a), stack::Item::Unit(b)) => stack::Item::Matrix(a.map(|x| x / &b)?),
+        (stack::Item::Matrix(_), stack::Item::Matrix(_)) => {
+            return Err(stack::Error::TypeMismatch.into())
+        }
+        (a @ (stack::Item::Vector(_) | stack::Item::Matrix(_)), b)
+        | (a, b @ (stack::Item::Vector(_) | stack::Item::Matrix(_))) => {
+            broadcast(a, b, |x, y| x / y)?
+        }
+        (stack::Item::Float(a), stack::Item::Float(b)) => stack::Item::Float((&a / &b)?),
+        (stack::Item::Float(a), stack::Item::Integer(b)) => {
+            stack::Item::Float((&a / &b.as_units_number())?)
+        }
+        (stack::Item::Integer(a), stack::Item::Float(b)) => stack::Item::Float((&a / &b)?),
+        (stack::Item::Integer(a), stack::Item::Integer(b)) => stack::Item::Float(&a / &b),
+        (stack::Item::Unit(a), stack::Item::Unit(b)) => stack::Item::Unit((&a / &b)?),
+        (stack::Item::Float(a), stack::Item::Unit(b)) => stack::Item::Float((&a / &b)?),
+        (stack::Item::Integer(a), stack::Item::Unit(b)) => stack::Item::Float((&a / &b)?),
         _ => return Err(stack::Error::TypeMismatch.into()),
     };
+    tx.push(named(&ctx.settings, result));
     commit!(tx)
 }
 
+/// Helper for arithmetic builtins. Applies `op` element by element to two
+/// items, at least one of which is a vector or matrix. A number is paired with
+/// every element of a vector or matrix.
+fn broadcast(
+    a: stack::Item,
+    b: stack::Item,
+    op: impl Fn(&Number, &Number) -> std::result::Result<Number, units::Error>,
+) -> std::result::Result<stack::Item, Error> {
+    Ok(match (a, b) {
+        (stack::Item::Vector(a), stack::Item::Vector(b)) => {
+            same_length(&a, &b)?;
+            stack::Item::Vector(a.zip_with(&b, op)?)
+        }
+        (stack::Item::Matrix(a), stack::Item::Matrix(b)) => {
+            if (a.rows, a.cols) != (b.rows, b.cols) {
+                return Err(Error::ShapeMismatch);
+            }
+            stack::Item::Matrix(a.zip_with(&b, op)?)
+        }
+        (stack::Item::Vector(a), b) => {
+            let b = number(b)?;
+            stack::Item::Vector(a.map(|x| op(x, &b))?)
+        }
+        (a, stack::Item::Vector(b)) => {
+            let a = number(a)?;
+            stack::Item::Vector(b.map(|y| op(&a, y))?)
+        }
+        (stack::Item::Matrix(a), b) => {
+            let b = number(b)?;
+            stack::Item::Matrix(a.map(|x| op(x, &b))?)
+        }
+        (a, stack::Item::Matrix(b)) => {
+            let a = number(a)?;
+            stack::Item::Matrix(b.map(|y| op(&a, y))?)
+        }
+        (a, b) => stack::Item::Float(op(&number(a)?, &number(b)?)?),
+    })
+}
+
 /// `( a b -- a**b )` Raises `a` to the power of `b`.
 ///
 /// The following combinations of operands are accepted:
 /// - two dimensionless numbers
-/// - `a` is a number with units and `b` is a dimensionless integer
+/// - `a` is a number with units and `b` is a dimensionless fraction
+/// - `a` is a unit and `b` is a dimensionless fraction
 ///
-/// Raising a number with units to a large power is not recommended.
+/// Fractions must have small denominators, e.g. `0.5` or `-0.75`.
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - there are fewer than two items on the stack;
-/// - the operation would result in a nonsensical temperature unit; or,
+/// - the operation would result in a nonsensical temperature unit;
+/// - the exponents of the resulting unit would be too large to represent; or,
 /// - the items are not one of the accepted combinations described above.
-pub fn builtin_pow(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, b) = pop_as_ff!(tx)?;
-    tx.pushf(a.pow(&b)?);
+pub fn builtin_pow(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let result = match tx.pop2()? {
+        (stack::Item::Unit(a), b) => stack::Item::Unit(a.pow(number(b)?.as_exponent()?)?),
+        (a, b) => stack::Item::Float(number(a)?.pow(&number(b)?)?),
+    };
+    tx.push(named(&ctx.settings, result));
     commit!(tx)
 }
 
@@ -208,319 +422,2593 @@ pub fn builtin_pow(stack: &mut Stack) -> Result {
 /// Returns an error if:
 /// - the stack is empty; or,
 /// - the exponent is not dimensionless.
-pub fn builtin_exp(stack: &mut Stack) -> Result {
+pub fn builtin_exp(ctx: &mut Context) -> Result {
     // This is functionally identical to `e swap **`, which makes it a prime
     // candidate for pulling out into a library once that's possible.
-    let mut tx = stack.begin();
+    let mut tx = ctx.stack.begin();
     let x = pop_as_f!(tx)?;
     tx.pushf(units::Number::new(std::f64::consts::E).pow(&x)?);
     commit!(tx)
 }
 
-/// `( a -- a**1/2 )` Finds the square root of `a`.
+/// `( a -- a**1/2 )` Finds the square root of `a`, which may be a number or
+/// a unit.
 ///
 /// # Errors
 ///
 /// Returns an error if:
-/// - the stack is empty;
-/// - `a` has units that don't have an integral square root.
-pub fn builtin_sqrt(stack: &mut Stack) -> Result {
+/// - the stack is empty; or,
+/// - `a` is a temperature unit or has temperature units.
+pub fn builtin_sqrt(ctx: &mut Context) -> Result {
     // Another library candidate: `2 /**`
-    let mut tx = stack.begin();
-    let a = pop_as_f!(tx)?;
-    tx.pushf(a.root(&units::Number::new(2.0))?);
+    let mut tx = ctx.stack.begin();
+    let a = tx.pop()?;
+    tx.push(named(&ctx.settings, root(a, &units::Number::new(2.0))?));
     commit!(tx)
 }
 
-/// `( a -- a**1/3 )` Finds the cube root of `a`.
+/// `( a -- a**1/3 )` Finds the cube root of `a`, which may be a number or a
+/// unit.
 ///
 /// # Errors
 ///
 /// Returns an error if:
-/// - the stack is empty;
-/// - `a` has units that don't have an integral cube root.
-pub fn builtin_cbrt(stack: &mut Stack) -> Result {
+/// - the stack is empty; or,
+/// - `a` is a temperature unit or has temperature units.
+pub fn builtin_cbrt(ctx: &mut Context) -> Result {
     // Library candidate: `3 /**`
-    let mut tx = stack.begin();
-    let a = pop_as_f!(tx)?;
-    tx.pushf(a.root(&units::Number::new(3.0))?);
+    let mut tx = ctx.stack.begin();
+    let a = tx.pop()?;
+    tx.push(named(&ctx.settings, root(a, &units::Number::new(3.0))?));
     commit!(tx)
 }
 
-/// `( a b -- a**1/b )` Finds the `b`th root of `a`.
+/// `( a b -- a**1/b )` Finds the `b`th root of `a`, which may be a number or
+/// a unit.
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - there are fewer than two items on the stack;
 /// - `b` has units;
-/// - `a` has units and `b` is not whole; or,
-/// - `a` has units that don't have an integral `b`th root.
-pub fn builtin_root(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, b) = pop_as_ff!(tx)?;
-    tx.pushf(a.root(&b)?);
+/// - `a` has units and `b` is zero or is not a fraction with a small
+///   denominator; or,
+/// - `a` is a temperature unit or has temperature units.
+pub fn builtin_root(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = tx.pop2()?;
+    tx.push(named(&ctx.settings, root(a, &number(b)?)?));
     commit!(tx)
 }
 
-/// Macro for creating a trigonometric function builtin.
-macro_rules! trig {
-    ($name: ident, $fn: ident) => {
-        /// `(a -- b)` Computes a trigonometric function.
-        ///
-        /// # Errors
-        ///
-        /// Returns an error if:
-        /// - the stack is empty;
-        /// - the item on top of the stack is not a number; or,
-        /// - the number does not have units measuring an angle.
-        pub fn $name(stack: &mut Stack) -> Result {
-            let mut tx = stack.begin();
-            let n = pop_as_f!(tx)?;
-
-            if let Some(u) = n.unit {
-                let n = u.convert(n.value, &RADIAN.as_unit())?;
-                tx.pushx(n.$fn());
-                commit!(tx)
-            } else {
-                Err(Error::MissingUnit)
-            }
-        }
-    };
-}
-
-trig!(builtin_sin, sin);
-trig!(builtin_cos, cos);
-trig!(builtin_tan, tan);
-
-/// Macro for creating an inverse trigonometric function.
-macro_rules! inverse_trig {
-    ($name: ident, $fn: ident) => {
-        /// `(a -- b)` Computes an inverse trigonometric function.
-        ///
-        /// # Errors
-        ///
-        /// Returns an error if:
-        /// - the stack is empty;
-        /// - the item on top of the stack is not a number; or,
-        /// - the number is not dimensionless.
-        pub fn $name(stack: &mut Stack) -> Result {
-            let mut tx = stack.begin();
-            let n = pop_as_f!(tx)?;
-
-            if n.unit.is_none() {
-                tx.pushf(Number::new(n.value.$fn()).with_unit(RADIAN.as_unit()));
-                commit!(tx)
-            } else {
-                Err(Error::NotDimensionless)
-            }
-        }
-    };
+/// Helper for root builtins. Finds the `degree`th root of a numeric item or a
+/// unit.
+fn root(item: stack::Item, degree: &units::Number) -> std::result::Result<stack::Item, Error> {
+    match item {
+        stack::Item::Unit(u) => Ok(stack::Item::Unit(u.root(degree.as_degree()?)?)),
+        item => Ok(stack::Item::Float(number(item)?.root(degree)?)),
+    }
 }
 
-inverse_trig!(builtin_asin, asin);
-inverse_trig!(builtin_acos, acos);
-inverse_trig!(builtin_atan, atan);
-
-/// `( ... -- )` Pops everything from the stack.
-///
-/// # Errors
-///
-/// Never returns an error.
-pub fn builtin_clear(stack: &mut Stack) -> Result {
-    stack.clear();
-    Ok(())
+/// Converts a numeric item into a `units::Number`.
+fn number(item: stack::Item) -> std::result::Result<units::Number, stack::Error> {
+    match item {
+        stack::Item::Float(x) => Ok(x),
+        stack::Item::Integer(i) => Ok(i.as_units_number()),
+        stack::Item::Unit(_)
+        | stack::Item::Vector(_)
+        | stack::Item::Matrix(_)
+        | stack::Item::Block(_) => Err(stack::Error::TypeMismatch),
+    }
 }
 
-/// `( a -- a a )` Duplicates the item on top of the stack.
+/// `( a -- |a| )` Finds the absolute value of a number.
 ///
 /// # Errors
 ///
-/// An error occurs if the stack is empty.
-pub fn builtin_dup(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let a = tx.pop()?;
-    tx.push(a.clone());
-    tx.push(a);
+/// An error occurs if:
+/// - the stack is empty;
+/// - the item on top of the stack is not a number;
+/// - the number is a temperature like tempC; or,
+/// - the number is the most negative integer, whose absolute value is too
+///   large.
+#[allow(clippy::missing_panics_doc)]
+pub fn builtin_abs(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    match popn!(tx)? {
+        stack::Item::Float(x) => tx.pushf(x.abs()?),
+        stack::Item::Integer(i) => tx.pushi(i.abs().ok_or(Error::Overflow)?),
+        stack::Item::Unit(_)
+        | stack::Item::Vector(_)
+        | stack::Item::Matrix(_)
+        | stack::Item::Block(_) => panic!("invariant wasn't"),
+    }
     commit!(tx)
 }
 
-/// `( [n u] -- n )` Makes a number dimensionless.
+/// `( a -- -a )` Negates a number.
 ///
 /// # Errors
 ///
 /// An error occurs if:
-/// - the stack is empty; or,
-/// - the item on top of the stack is not a number.
-pub fn builtin_drop(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    match tx.pop()? {
-        stack::Item::Float(x) => {
-            tx.pushx(x.value);
-            commit!(tx)
-        }
-        stack::Item::Integer(_) => Ok(()),
-        stack::Item::Unit(_) => Err(Error::Stack(stack::Error::TypeMismatch)),
+/// - the stack is empty;
+/// - the item on top of the stack is not a number;
+/// - the number is a temperature like tempC; or,
+/// - the number is the most negative integer, whose negation is too large.
+#[allow(clippy::missing_panics_doc)]
+pub fn builtin_neg(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    match popn!(tx)? {
+        stack::Item::Float(x) => tx.pushf((-&x)?),
+        stack::Item::Integer(i) => tx.pushi((-&i).ok_or(Error::Overflow)?),
+        stack::Item::Unit(_)
+        | stack::Item::Vector(_)
+        | stack::Item::Matrix(_)
+        | stack::Item::Block(_) => panic!("invariant wasn't"),
     }
+    commit!(tx)
 }
 
-/// `( [n u1] u2 -- [n u2] )` Converts a number into different units.
+/// `( a -- b )` Finds -1, 0, or 1 according to the sign of a number. The
+/// result is dimensionless.
 ///
 /// # Errors
 ///
 /// An error occurs if:
-/// - there are fewer than two items on the stack;
-/// - the items are not a number and a unit; or,
-/// - the number has units that are incommensurable with `u`.
-pub fn builtin_into(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, u) = pop_as_fu!(tx)?;
-    if let Some(a_unit) = a.unit {
-        let b = a_unit.convert(a.value, &u)?;
-        tx.pushf(Number::new(b).with_unit(u));
-    } else {
-        tx.pushf(a.with_unit(u));
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a number.
+#[allow(clippy::missing_panics_doc)]
+pub fn builtin_sign(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    match popn!(tx)? {
+        stack::Item::Float(x) => tx.pushx(x.signum()),
+        stack::Item::Integer(i) => tx.push(stack::Item::Integer(i.signum())),
+        stack::Item::Unit(_)
+        | stack::Item::Vector(_)
+        | stack::Item::Matrix(_)
+        | stack::Item::Block(_) => panic!("invariant wasn't"),
     }
     commit!(tx)
 }
 
-macro_rules! bitwise {
-    ($name: ident, $op: tt) => {
-        /// `( a b -- c )` Computes a bitwise function of two integers.
+/// Macro for creating a rounding function builtin.
+macro_rules! rounding {
+    ($name: ident, $fn: ident, $int: expr) => {
+        /// `( a -- b )` Rounds a number. Numbers with units are rounded in the
+        /// unit they're displayed in, so `[2.54 cm]` rounds to `[3 cm]`.
         ///
         /// # Errors
         ///
         /// An error occurs if:
-        /// - there are fewer than two items on the stack; or,
-        /// - the items are not integers.
-        pub fn $name(stack: &mut Stack) -> Result {
-            let mut tx = stack.begin();
-            let (a, b) = pop_as_ii!(tx)?;
-            tx.pushi(integer::Integer::new(a.value $op b.value, a.repr));
+        /// - the stack is empty; or,
+        /// - the item on top of the stack is not a number.
+        #[allow(clippy::missing_panics_doc)]
+        pub fn $name(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            match popn!(tx)? {
+                stack::Item::Float(x) => tx.pushf(ctx.settings.displayed(&x).$fn()),
+                stack::Item::Integer(i) => tx.push(stack::Item::Integer($int(i))),
+                stack::Item::Unit(_)
+                | stack::Item::Vector(_)
+                | stack::Item::Matrix(_)
+                | stack::Item::Block(_) => panic!("invariant wasn't"),
+            }
             commit!(tx)
         }
     };
 }
 
-bitwise!(builtin_bitwise_and, &);
-bitwise!(builtin_bitwise_or, |);
-bitwise!(builtin_bitwise_xor, ^);
+rounding!(builtin_floor, floor, std::convert::identity);
+rounding!(builtin_ceil, ceil, std::convert::identity);
+rounding!(builtin_round, round, std::convert::identity);
+rounding!(builtin_trunc, trunc, std::convert::identity);
+rounding!(builtin_frac, fract, |i: integer::Integer| {
+    integer::Integer::new(0, i.repr)
+});
 
-/// `( a -- ~a )` Computes the bitwise complement of an integer.
+/// `( a step -- b )` Rounds `a` to the nearest multiple of `step`, e.g.
+/// `5 mm`. The result is in the units of `step`.
 ///
 /// # Errors
 ///
 /// An error occurs if:
-/// - the stack is empty; or,
-/// - the item on top of the stack is not an integer.
-pub fn builtin_bitwise_complement(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let x = pop_as_i!(tx)?;
-    tx.pushi(integer::Integer::new(!x.value, x.repr));
+/// - there are fewer than two items on the stack;
+/// - either item is not a number; or,
+/// - the items have incommensurable units.
+pub fn builtin_roundto(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, step) = popnn!(tx)?;
+    if let (stack::Item::Integer(a), stack::Item::Integer(step)) = (&a, &step) {
+        let value = if step.value == 0 {
+            a.value
+        } else {
+            (a.value + a.value.signum() * step.value.abs() / 2) / step.value * step.value
+        };
+        tx.push(stack::Item::Integer(integer::Integer::new(value, a.repr)));
+    } else {
+        tx.pushf(number(a)?.round_to(&number(step)?)?);
+    }
     commit!(tx)
 }
 
-macro_rules! binrepr {
-    ($name: ident, $repr: expr) => {
-        /// `( a -- a )` Changes the representation of an integer.
-        ///
-        /// # Errors
-        /// An error occurs if:
-        /// - the stack is empty; or,
-        /// - the item on top of the stack is not an integer.
-        pub fn $name(stack: &mut Stack) -> Result {
-            let mut tx = stack.begin();
-            let x = pop_as_i!(tx)?;
-            tx.pushi(x.with_repr($repr));
-            commit!(tx)
-        }
-    };
-}
-
-binrepr!(builtin_bin, integer::Representation::Binary);
-binrepr!(builtin_dec, integer::Representation::Decimal);
-binrepr!(builtin_oct, integer::Representation::Octal);
-binrepr!(builtin_hex, integer::Representation::Hexadecimal);
-
-/// `( a b -- [a & (1<<b)] )` Sets the bit in `a` at index `b`. The least
-/// significant bit is index zero.
+/// `( a n -- b )` Rounds `a` to `n` significant figures. Numbers with units
+/// are rounded in the unit they're displayed in.
 ///
 /// # Errors
 ///
 /// An error occurs if:
 /// - there are fewer than two items on the stack;
-/// - `a` is not an integer; or,
-/// - `b` is not a non-negative integer.
-pub fn builtin_bset(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, b) = pop_as_ii!(tx)?;
-    if b.value >= 0 {
-        tx.pushi(integer::Integer::new(a.value & (1 << b.value), a.repr));
-        commit!(tx)
-    } else {
-        Err(Error::NotNonNegative)
+/// - either item is not a number; or,
+/// - `n` is not a positive whole number.
+pub fn builtin_roundsig(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    let a = pop_as_f!(tx)?;
+    let digits = u32::try_from(n.value).map_err(|_| Error::NotPositive)?;
+    if digits == 0 {
+        return Err(Error::NotPositive);
     }
+    tx.pushf(ctx.settings.displayed(&a).round_sig(digits));
+    commit!(tx)
 }
 
-/// `( a b -- [a & ~(1<<b)] )` Clears the bit in `a` at index `b`. The least
-/// significant bit is index zero.
+/// Compares two numeric items, converting units if necessary.
+fn compare(a: &stack::Item, b: &stack::Item) -> std::result::Result<std::cmp::Ordering, Error> {
+    match (a, b) {
+        (stack::Item::Integer(a), stack::Item::Integer(b)) => Ok(a.value.cmp(&b.value)),
+        _ => Ok(number(a.clone())?.compare(&number(b.clone())?)?),
+    }
+}
+
+/// `( a b -- lesser )` Keeps the lesser of two numbers. The numbers may have
+/// different but commensurable units; the result keeps its own.
 ///
 /// # Errors
 ///
 /// An error occurs if:
 /// - there are fewer than two items on the stack;
-/// - `a` is not an integer; or,
-/// - `b` is not a non-negative integer.
-pub fn builtin_bclr(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, b) = pop_as_ii!(tx)?;
-    if b.value >= 0 {
-        tx.pushi(integer::Integer::new(a.value & !(1 << b.value), a.repr));
-        commit!(tx)
+/// - either item is not a number; or,
+/// - the items have incommensurable units.
+pub fn builtin_lesser(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = popnn!(tx)?;
+    if compare(&b, &a)?.is_lt() {
+        tx.push(b);
     } else {
-        Err(Error::NotNonNegative)
+        tx.push(a);
     }
+    commit!(tx)
 }
 
-/// `( a b -- a [(a >> b) & 1] )` Pushes the bit in `a` at index `b`. The least
-/// significant bit is index zero.
+/// `( a b -- greater )` Keeps the greater of two numbers. The numbers may have
+/// different but commensurable units; the result keeps its own.
 ///
 /// # Errors
 ///
 /// An error occurs if:
 /// - there are fewer than two items on the stack;
-/// - `a` is not an integer; or,
-/// - `b` is not a non-negative integer.
-pub fn builtin_bget(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let (a, b) = pop_as_ii!(tx)?;
-    if b.value >= 0 {
-        let bit = (a.value >> b.value) & 1;
-        tx.pushi(a);
-        tx.pushi(integer::Integer::bin(bit));
-        commit!(tx)
+/// - either item is not a number; or,
+/// - the items have incommensurable units.
+pub fn builtin_greater(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = popnn!(tx)?;
+    if compare(&b, &a)?.is_gt() {
+        tx.push(b);
     } else {
-        Err(Error::NotNonNegative)
+        tx.push(a);
     }
+    commit!(tx)
 }
 
-/// `( ... a1 ... aN N -- a1 ... aN )` Removes everything from the stack except
-/// the topmost `N` items.
+/// `( a lo hi -- b )` Limits `a` to the range from `lo` to `hi`.
 ///
 /// # Errors
 ///
-/// Returns an error if:
-/// - the stack has fewer than N+1 items; or,
-/// - the item on top of the stack is not a dimensionless, whole, non-negative
-///   number.
-pub fn builtin_keep(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
-    let n = pop_as_i!(tx)?;
-    if n.value < 0 {
-        return Err(Error::NotNonNegative);
+/// An error occurs if:
+/// - there are fewer than three items on the stack;
+/// - any of the items is not a number;
+/// - the items have incommensurable units; or,
+/// - `lo` is greater than `hi`.
+pub fn builtin_clamp(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (lo, hi) = popnn!(tx)?;
+    let a = popn!(tx)?;
+    if compare(&lo, &hi)?.is_gt() {
+        return Err(Error::BoundsReversed);
+    }
+    if compare(&a, &lo)?.is_lt() {
+        tx.push(lo);
+    } else if compare(&a, &hi)?.is_gt() {
+        tx.push(hi);
+    } else {
+        tx.push(a);
     }
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    tx.keep(n.value as usize)?;
+    commit!(tx)
+}
+
+/// Macro for creating a pair of statistical builtins: one that summarizes the
+/// whole stack and one that summarizes the top `N` items.
+macro_rules! statistic {
+    ($whole: ident, $counted: ident, $fn: ident) => {
+        /// `( ... -- x )` Computes a statistic of every item on the stack.
+        /// The items may have different but commensurable units; they're
+        /// converted to the units of the first (bottommost) item.
+        ///
+        /// # Errors
+        ///
+        /// An error occurs if:
+        /// - there are too few items on the stack;
+        /// - any of the items is not a number;
+        /// - the items have incommensurable units; or,
+        /// - the statistic doesn't make sense for temperatures like tempC
+        ///   and the items are temperatures.
+        pub fn $whole(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let items = tx.pop_n(tx.height())?;
+            tx.push($fn(&items)?);
+            commit!(tx)
+        }
+
+        /// `( a1 ... aN N -- x )` Computes a statistic of the top `N` items
+        /// on the stack. The items may have different but commensurable
+        /// units; they're converted to the units of `a1`.
+        ///
+        /// # Errors
+        ///
+        /// An error occurs if:
+        /// - there are too few items on the stack;
+        /// - the item on top of the stack is not a dimensionless, whole,
+        ///   non-negative number;
+        /// - any of the other items is not a number;
+        /// - the items have incommensurable units; or,
+        /// - the statistic doesn't make sense for temperatures like tempC
+        ///   and the items are temperatures.
+        pub fn $counted(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let n = pop_as_i!(tx)?;
+            let n = usize::try_from(n.value).map_err(|_| Error::NotNonNegative)?;
+            let items = tx.pop_n(n)?;
+            tx.push($fn(&items)?);
+            commit!(tx)
+        }
+    };
+}
+
+statistic!(builtin_sum, builtin_sumn, sum);
+statistic!(builtin_mean, builtin_meann, mean);
+statistic!(builtin_median, builtin_mediann, median);
+statistic!(builtin_stdev, builtin_stdevn, stdev);
+statistic!(builtin_var, builtin_varn, variance);
+statistic!(builtin_minimum, builtin_minn, minimum);
+statistic!(builtin_maximum, builtin_maxn, maximum);
+statistic!(builtin_range, builtin_rangen, range);
+statistic!(builtin_rms, builtin_rmsn, rms);
+statistic!(builtin_geomean, builtin_geomeann, geomean);
+
+/// Helper for statistical builtins. Returns the first item, along with how
+/// far each item is from it in the units of their differences. Differences
+/// keep temperatures like tempC honest: the spread of temperatures is in
+/// degrees, and their mean is a temperature again.
+fn sample(items: &[stack::Item]) -> std::result::Result<(Number, Vec<f64>, Option<Unit>), Error> {
+    let first = number(items.first().ok_or(stack::Error::Underflow)?.clone())?;
+    let differences = items
+        .iter()
+        .map(|item| Ok((&number(item.clone())? - &first)?))
+        .collect::<std::result::Result<Vec<_>, Error>>()?;
+    let unit = differences[0].unit.clone();
+    let deltas = differences
+        .iter()
+        .map(|d| d.value_in(unit.as_ref()))
+        .collect::<std::result::Result<_, _>>()?;
+    Ok((first, deltas, unit))
+}
+
+/// Helper for `rms` and `geomean`, which only make sense for quantities with
+/// a true zero. Returns the values of `items` in the units of the first item,
+/// along with those units.
+fn magnitudes(items: &[stack::Item]) -> std::result::Result<(Vec<f64>, Option<Unit>), Error> {
+    let first = number(items.first().ok_or(stack::Error::Underflow)?.clone())?;
+    // Temperatures like tempC can't be squared.
+    (&first * &first)?;
+    let values = items
+        .iter()
+        .map(|item| Ok(number(item.clone())?.value_in(first.unit.as_ref())?))
+        .collect::<std::result::Result<_, Error>>()?;
+    Ok((values, first.unit))
+}
+
+/// Helper for statistical builtins. Returns the items as integers if all of
+/// them are.
+fn integers(items: &[stack::Item]) -> Option<Vec<&integer::Integer>> {
+    items
+        .iter()
+        .map(|item| match item {
+            stack::Item::Integer(i) => Some(i),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Helper for statistical builtins. Makes a stack item out of a value and
+/// units.
+fn quantity(value: f64, unit: Option<Unit>) -> stack::Item {
+    stack::Item::Float(Number { value, unit })
+}
+
+/// Helper for statistical builtins. Offsets `first` by a difference in the
+/// units returned by `sample`.
+fn offset(
+    first: &Number,
+    delta: f64,
+    unit: Option<Unit>,
+) -> std::result::Result<stack::Item, Error> {
+    Ok(stack::Item::Float(
+        (first + &Number { value: delta, unit })?,
+    ))
+}
+
+/// Helper for statistical builtins. Finds the arithmetic mean of `values`.
+#[allow(clippy::cast_precision_loss)]
+fn average(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Adds up the items. The sum of integers is an integer.
+fn sum(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    if let Some(ints) = integers(items) {
+        let repr = ints.first().ok_or(stack::Error::Underflow)?.repr;
+        let total = ints.iter().map(|i| i.value).sum();
+        return Ok(stack::Item::Integer(integer::Integer::new(total, repr)));
+    }
+    let mut items = items.iter().cloned();
+    let first = number(items.next().ok_or(stack::Error::Underflow)?)?;
+    let total = items.try_fold(first, |total, item| {
+        Ok::<_, Error>((&total + &number(item)?)?)
+    })?;
+    Ok(stack::Item::Float(total))
+}
+
+/// Finds the arithmetic mean of the items.
+fn mean(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (first, deltas, unit) = sample(items)?;
+    offset(&first, average(&deltas), unit)
+}
+
+/// Finds the median of the items. The median of an even number of items is
+/// the mean of the middle two.
+fn median(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (first, mut deltas, unit) = sample(items)?;
+    deltas.sort_by(f64::total_cmp);
+    let mid = deltas.len() / 2;
+    if deltas.len() % 2 == 0 {
+        offset(&first, f64::midpoint(deltas[mid - 1], deltas[mid]), unit)
+    } else {
+        offset(&first, deltas[mid], unit)
+    }
+}
+
+/// Helper for `variance` and `stdev`. Finds the sample variance of the items
+/// in the square of the units of their differences, returning the value and
+/// the units.
+fn sample_variance(items: &[stack::Item]) -> std::result::Result<(f64, Option<Unit>), Error> {
+    if items.len() < 2 {
+        return Err(Error::Stack(stack::Error::Underflow));
+    }
+    let (_, deltas, unit) = sample(items)?;
+    let m = average(&deltas);
+    #[allow(clippy::cast_precision_loss)]
+    let var = deltas.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (deltas.len() - 1) as f64;
+    Ok((var, unit))
+}
+
+/// Finds the sample variance of the items. The result is in the square of
+/// the items' units.
+fn variance(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (var, unit) = sample_variance(items)?;
+    let var = Number {
+        value: var,
+        unit: unit.clone(),
+    };
+    Ok(stack::Item::Float((&var * &Number { value: 1.0, unit })?))
+}
+
+/// Finds the sample standard deviation of the items.
+fn stdev(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (var, unit) = sample_variance(items)?;
+    Ok(quantity(var.sqrt(), unit))
+}
+
+/// Helper for `minimum`, `maximum`, and `range`. Returns the indices of the
+/// least and greatest items.
+fn extremes(items: &[stack::Item]) -> std::result::Result<(usize, usize), Error> {
+    let (_, deltas, _) = sample(items)?;
+    let mut lo = 0;
+    let mut hi = 0;
+    for (i, x) in deltas.iter().enumerate() {
+        if x.total_cmp(&deltas[lo]).is_lt() {
+            lo = i;
+        }
+        if x.total_cmp(&deltas[hi]).is_gt() {
+            hi = i;
+        }
+    }
+    Ok((lo, hi))
+}
+
+/// Finds the least of the items. The result keeps its own units.
+fn minimum(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (lo, _) = extremes(items)?;
+    Ok(items[lo].clone())
+}
+
+/// Finds the greatest of the items. The result keeps its own units.
+fn maximum(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (_, hi) = extremes(items)?;
+    Ok(items[hi].clone())
+}
+
+/// Finds the difference between the greatest and least of the items. The
+/// range of integers is an integer.
+fn range(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (lo, hi) = extremes(items)?;
+    if let Some(ints) = integers(items) {
+        let difference = ints[hi].value - ints[lo].value;
+        return Ok(stack::Item::Integer(integer::Integer::new(
+            difference,
+            ints[0].repr,
+        )));
+    }
+    let (_, deltas, unit) = sample(items)?;
+    Ok(quantity(deltas[hi] - deltas[lo], unit))
+}
+
+/// Finds the root mean square of the items.
+fn rms(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (values, unit) = magnitudes(items)?;
+    let squares: Vec<f64> = values.iter().map(|x| x * x).collect();
+    Ok(quantity(average(&squares).sqrt(), unit))
+}
+
+/// Finds the geometric mean of the items, which must all be positive.
+fn geomean(items: &[stack::Item]) -> std::result::Result<stack::Item, Error> {
+    let (values, unit) = magnitudes(items)?;
+    if values.iter().any(|x| *x <= 0.0) {
+        return Err(Error::NotPositive);
+    }
+    let logs: Vec<f64> = values.iter().map(|x| x.ln()).collect();
+    Ok(quantity(average(&logs).exp(), unit))
+}
+
+/// Macro for creating the probability density, cumulative distribution, and
+/// inverse cumulative distribution builtins of a continuous distribution with
+/// `$n` parameters. `x` and the parameters are converted to the units of the
+/// last parameter, so a mean in `mm` can be used with an `x` in `in`.
+macro_rules! distribution {
+    ($pdf: ident, $cdf: ident, $inv: ident, $n: literal, $check: expr, $pdf_fn: expr, $cdf_fn: expr, $inv_fn: expr) => {
+        /// `( x params -- f )` Computes a probability density at `x`. The
+        /// density is per unit of the last parameter.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if:
+        /// - there are too few items on the stack;
+        /// - any of the items is not a number;
+        /// - the items have incommensurable units; or,
+        /// - the parameters are out of range.
+        pub fn $pdf(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let (params, unit) = parameters::<$n>(&mut tx, $check)?;
+            let x = pop_as_f!(tx)?.value_in(unit.as_ref())?;
+            let pdf: fn(f64, [f64; $n]) -> f64 = $pdf_fn;
+            tx.pushf(density(pdf(x, params), unit.as_ref())?);
+            commit!(tx)
+        }
+
+        /// `( x params -- p )` Computes the probability that a variable is
+        /// at most `x`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if:
+        /// - there are too few items on the stack;
+        /// - any of the items is not a number;
+        /// - the items have incommensurable units; or,
+        /// - the parameters are out of range.
+        pub fn $cdf(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let (params, unit) = parameters::<$n>(&mut tx, $check)?;
+            let x = pop_as_f!(tx)?.value_in(unit.as_ref())?;
+            let cdf: fn(f64, [f64; $n]) -> f64 = $cdf_fn;
+            tx.pushx(cdf(x, params));
+            commit!(tx)
+        }
+
+        /// `( p params -- x )` Computes the `x` at which the probability that
+        /// a variable is at most `x` is `p`. `x` is in the units of the last
+        /// parameter.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if:
+        /// - there are too few items on the stack;
+        /// - any of the items is not a number;
+        /// - the parameters have incommensurable units;
+        /// - the parameters are out of range; or,
+        /// - `p` is not a dimensionless number between 0 and 1.
+        pub fn $inv(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let (params, unit) = parameters::<$n>(&mut tx, $check)?;
+            let p = probability(&pop_as_f!(tx)?)?;
+            let inv: fn(f64, [f64; $n]) -> f64 = $inv_fn;
+            tx.pushf(Number {
+                value: inv(p, params),
+                unit,
+            });
+            commit!(tx)
+        }
+    };
+}
+
+distribution!(
+    builtin_normpdf,
+    builtin_normcdf,
+    builtin_norminv,
+    2,
+    |_, [_, sigma]| positive(sigma),
+    |x, [mu, sigma]| distributions::normal_pdf(x, mu, sigma),
+    |x, [mu, sigma]| distributions::normal_cdf(x, mu, sigma),
+    |p, [mu, sigma]| distributions::normal_inv(p, mu, sigma)
+);
+distribution!(
+    builtin_tpdf,
+    builtin_tcdf,
+    builtin_tinv,
+    1,
+    |unit, [df]| unitless(unit).and(positive(df)),
+    |x, [df]| distributions::t_pdf(x, df),
+    |x, [df]| distributions::t_cdf(x, df),
+    |p, [df]| distributions::t_inv(p, df)
+);
+distribution!(
+    builtin_chi2pdf,
+    builtin_chi2cdf,
+    builtin_chi2inv,
+    1,
+    |unit, [k]| unitless(unit).and(positive(k)),
+    |x, [k]| distributions::chi2_pdf(x, k),
+    |x, [k]| distributions::chi2_cdf(x, k),
+    |p, [k]| distributions::chi2_inv(p, k)
+);
+distribution!(
+    builtin_exppdf,
+    builtin_expcdf,
+    builtin_expinv,
+    1,
+    |_, [mean]| positive(mean),
+    |x, [mean]| distributions::exp_pdf(x, mean),
+    |x, [mean]| distributions::exp_cdf(x, mean),
+    |p, [mean]| distributions::exp_inv(p, mean)
+);
+distribution!(
+    builtin_unifpdf,
+    builtin_unifcdf,
+    builtin_unifinv,
+    2,
+    |_, [a, b]| if a < b {
+        Ok(())
+    } else {
+        Err(Error::BoundsReversed)
+    },
+    |x, [a, b]| distributions::uniform_pdf(x, a, b),
+    |x, [a, b]| distributions::uniform_cdf(x, a, b),
+    |p, [a, b]| distributions::uniform_inv(p, a, b)
+);
+
+/// Helper for distribution builtins. Pops `N` parameters and returns their
+/// values in the units of the last one, along with those units, after
+/// validating them with `check`.
+fn parameters<const N: usize>(
+    tx: &mut stack::Transaction,
+    check: fn(Option<&Unit>, [f64; N]) -> Result,
+) -> std::result::Result<([f64; N], Option<Unit>), Error> {
+    let items = tx.pop_n(N)?;
+    let numbers = items
+        .into_iter()
+        .map(number)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let unit = numbers.last().and_then(|n| n.unit.clone());
+    let mut values = [0.0; N];
+    for (value, n) in values.iter_mut().zip(&numbers) {
+        *value = n.value_in(unit.as_ref())?;
+    }
+    check(unit.as_ref(), values)?;
+    Ok((values, unit))
+}
+
+/// Helper for distribution builtins. Fails unless a parameter is positive.
+fn positive(value: f64) -> Result {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::NotPositive)
+    }
+}
+
+/// Helper for distribution builtins. Fails unless parameters are
+/// dimensionless.
+fn unitless(unit: Option<&Unit>) -> Result {
+    match unit {
+        None => Ok(()),
+        Some(_) => Err(Error::NotDimensionless),
+    }
+}
+
+/// Helper for distribution builtins. Returns the value of a number that must
+/// be a dimensionless probability. Ratios like `%` are allowed.
+fn probability(n: &Number) -> std::result::Result<f64, Error> {
+    let p = n.value_in(None).map_err(|_| Error::NotDimensionless)?;
+    if (0.0..=1.0).contains(&p) {
+        Ok(p)
+    } else {
+        Err(Error::NotAProbability)
+    }
+}
+
+/// Helper for distribution builtins. Expresses a probability density per
+/// `unit`.
+fn density(value: f64, unit: Option<&Unit>) -> std::result::Result<Number, Error> {
+    let value = Number::new(value);
+    Ok(match unit {
+        Some(unit) => (&value / unit)?,
+        None => value,
+    })
+}
+
+/// `( k n p -- P )` Computes the probability of exactly `k` successes in `n`
+/// trials that each succeed with probability `p`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than three items on the stack;
+/// - `k` or `n` is not a whole number;
+/// - `n` is negative; or,
+/// - `p` is not a dimensionless number between 0 and 1.
+pub fn builtin_binompdf(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let p = probability(&pop_as_f!(tx)?)?;
+    let (k, n) = pop_as_ii!(tx)?;
+    if n.value < 0 {
+        return Err(Error::NotNonNegative);
+    }
+    tx.pushx(distributions::binomial_pmf(k.value, n.value, p));
+    commit!(tx)
+}
+
+/// `( k n p -- P )` Computes the probability of at most `k` successes in `n`
+/// trials that each succeed with probability `p`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than three items on the stack;
+/// - `k` or `n` is not a whole number;
+/// - `n` is negative; or,
+/// - `p` is not a dimensionless number between 0 and 1.
+pub fn builtin_binomcdf(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let p = probability(&pop_as_f!(tx)?)?;
+    let (k, n) = pop_as_ii!(tx)?;
+    if n.value < 0 {
+        return Err(Error::NotNonNegative);
+    }
+    tx.pushx(distributions::binomial_cdf(k.value, n.value, p));
+    commit!(tx)
+}
+
+/// `( k lambda -- P )` Computes the probability of exactly `k` events when
+/// `lambda` are expected.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - `k` is not a whole number; or,
+/// - `lambda` is not a dimensionless, non-negative number.
+pub fn builtin_poisspdf(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let lambda = rate(&pop_as_f!(tx)?)?;
+    let k = pop_as_i!(tx)?;
+    tx.pushx(distributions::poisson_pmf(k.value, lambda));
+    commit!(tx)
+}
+
+/// `( k lambda -- P )` Computes the probability of at most `k` events when
+/// `lambda` are expected.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - `k` is not a whole number; or,
+/// - `lambda` is not a dimensionless, non-negative number.
+pub fn builtin_poisscdf(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let lambda = rate(&pop_as_f!(tx)?)?;
+    let k = pop_as_i!(tx)?;
+    tx.pushx(distributions::poisson_cdf(k.value, lambda));
+    commit!(tx)
+}
+
+/// Helper for the Poisson builtins. Returns the value of the expected number
+/// of events, which must be dimensionless and non-negative.
+fn rate(n: &Number) -> std::result::Result<f64, Error> {
+    let lambda = n.value_in(None).map_err(|_| Error::NotDimensionless)?;
+    if lambda < 0.0 {
+        return Err(Error::NotNonNegative);
+    }
+    Ok(lambda)
+}
+
+/// `( -- x )` Pushes a random number uniformly distributed in [0, 1).
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_rand(ctx: &mut Context) -> Result {
+    let x = ctx.rng.uniform();
+    ctx.stack.pushx(x);
+    Ok(())
+}
+
+/// `( lo hi -- n )` Pushes a random integer uniformly distributed between
+/// `lo` and `hi`, inclusive.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - either item is not a whole number; or,
+/// - `lo` is greater than `hi`.
+pub fn builtin_randint(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (lo, hi) = pop_as_ii!(tx)?;
+    if lo.value > hi.value {
+        return Err(Error::BoundsReversed);
+    }
+    // The span of any two i64s fits in a u64, except that the span of the
+    // whole range, 2⁶⁴, wraps to zero.
+    let span = hi.value.abs_diff(lo.value).wrapping_add(1);
+    let offset = if span == 0 {
+        ctx.rng.next_u64()
+    } else {
+        ctx.rng.below(span)
+    };
+    let n = lo.value.wrapping_add_unsigned(offset);
+    tx.pushi(integer::Integer::new(n, lo.repr));
+    commit!(tx)
+}
+
+/// `( mu sigma -- x )` Pushes a random number from the normal distribution
+/// with mean `mu` and standard deviation `sigma`. The result is in the units
+/// of `mu`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - either item is not a number;
+/// - the items have incommensurable units;
+/// - both items are temperatures like tempC; or,
+/// - `sigma` is negative.
+pub fn builtin_randn(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (mu, sigma) = pop_as_ff!(tx)?;
+    if sigma.value < 0.0 {
+        return Err(Error::NotNonNegative);
+    }
+    let deviation = Number {
+        value: sigma.value * ctx.rng.normal(),
+        unit: sigma.unit,
+    };
+    tx.pushf((&mu + &deviation)?);
+    commit!(tx)
+}
+
+/// `( n -- )` Seeds the random number generator so that the numbers that
+/// follow are reproducible.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a whole number.
+pub fn builtin_seed(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    ctx.rng = Rng::new(u64::from_ne_bytes(n.value.to_ne_bytes()));
+    commit!(tx)
+}
+
+/// `( -- )` Starts a vector. The items pushed before the matching `]` become
+/// its elements.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_open_bracket(ctx: &mut Context) -> Result {
+    ctx.track_marks();
+    let height = ctx.stack.height();
+    ctx.marks.push((height, height));
+    Ok(())
+}
+
+/// `( a1 ... aN -- v )` Ends a vector started with `[`, collecting the items
+/// pushed since then into a vector. The elements are converted to the units
+/// of `a1`. If the items are all vectors, they become the rows of a matrix
+/// instead, so `[ [ 1 2 ] [ 3 4 ] ]` is a 2 × 2 matrix.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there is no matching `[`;
+/// - items from below the `[` were popped;
+/// - some of the items are vectors and some aren't;
+/// - any of the items is not a number, unless all of them are vectors;
+/// - the items are vectors with different lengths; or,
+/// - the items have incommensurable units.
+pub fn builtin_close_bracket(ctx: &mut Context) -> Result {
+    ctx.track_marks();
+    let (mark, lowest) = ctx.marks.pop().ok_or(Error::UnmatchedBracket)?;
+    if lowest < mark {
+        return Err(Error::Stack(stack::Error::Underflow));
+    }
+    let mut tx = ctx.stack.begin();
+    let n = tx.height() - mark;
+    let items = tx.pop_n(n)?;
+    let vectors = items
+        .iter()
+        .filter(|x| matches!(x, stack::Item::Vector(_)))
+        .count();
+    if vectors == 0 {
+        tx.pushv(collect(items)?);
+    } else if vectors == items.len() {
+        tx.pushm(collect_rows(items)?);
+    } else {
+        return Err(Error::MixedBracket);
+    }
+    commit!(tx)
+}
+
+/// `( a1 ... aN N -- v )` Collects the top `N` items into a vector. The
+/// elements are converted to the units of `a1`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are too few items on the stack;
+/// - the item on top of the stack is not a dimensionless, whole,
+///   non-negative number;
+/// - any of the other items is not a number; or,
+/// - the items have incommensurable units.
+pub fn builtin_vector(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    let n = usize::try_from(n.value).map_err(|_| Error::NotNonNegative)?;
+    let items = tx.pop_n(n)?;
+    tx.pushv(collect(items)?);
+    commit!(tx)
+}
+
+/// Helper for vector builtins. Makes a vector out of numeric items.
+fn collect(items: Vec<stack::Item>) -> std::result::Result<Vector, Error> {
+    let numbers = items
+        .into_iter()
+        .map(number)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(Vector::from_numbers(&numbers)?)
+}
+
+/// Helper for matrix builtins. Makes a matrix out of vectors, which become its
+/// rows.
+fn collect_rows(items: Vec<stack::Item>) -> std::result::Result<Matrix, Error> {
+    let rows = items
+        .into_iter()
+        .map(|x| match x {
+            stack::Item::Vector(v) => Ok(v),
+            _ => Err(stack::Error::TypeMismatch),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for row in &rows[1..] {
+        same_length(&rows[0], row)?;
+    }
+    Ok(Matrix::from_rows(&rows)?)
+}
+
+/// Helper for vector builtins. Fails unless two vectors have the same length.
+fn same_length(a: &Vector, b: &Vector) -> Result {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(Error::LengthMismatch)
+    }
+}
+
+/// `( u v -- u·v )` Finds the dot product of two vectors.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - the items are not vectors;
+/// - the vectors have different lengths; or,
+/// - the operation would result in a nonsensical temperature unit.
+pub fn builtin_dot(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = popvv!(tx)?;
+    same_length(&a, &b)?;
+    tx.push(named(&ctx.settings, stack::Item::Float(a.dot(&b)?)));
+    commit!(tx)
+}
+
+/// `( u v -- u×v )` Finds the cross product of two three-element vectors.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - the items are not vectors;
+/// - either vector doesn't have three elements; or,
+/// - the operation would result in a nonsensical temperature unit.
+pub fn builtin_cross(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = popvv!(tx)?;
+    if a.len() != 3 || b.len() != 3 {
+        return Err(Error::NotThreeDimensional);
+    }
+    tx.push(named(&ctx.settings, stack::Item::Vector(a.cross(&b)?)));
+    commit!(tx)
+}
+
+/// `( v -- |v| )` Finds the Euclidean norm (length) of a vector, in the
+/// vector's units.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a vector.
+pub fn builtin_norm(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let v = popv!(tx)?;
+    tx.pushf(v.norm());
+    commit!(tx)
+}
+
+/// `( v -- v/|v| )` Finds the dimensionless unit vector in the direction of a
+/// vector.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack is empty;
+/// - the item on top of the stack is not a vector; or,
+/// - the vector is zero.
+pub fn builtin_unit_vector(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let v = popv!(tx)?;
+    tx.pushv(v.normalized().ok_or(Error::ZeroVector)?);
+    commit!(tx)
+}
+
+/// Helper for matrix builtins. Fails unless a matrix is square.
+fn square(m: &Matrix) -> Result {
+    if m.is_square() {
+        Ok(())
+    } else {
+        Err(Error::NotSquare)
+    }
+}
+
+/// `( n -- I )` Pushes the `n` × `n` identity matrix.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a dimensionless, whole, positive
+///   number.
+pub fn builtin_identity(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    let n = usize::try_from(n.value).map_err(|_| Error::NotPositive)?;
+    if n == 0 {
+        return Err(Error::NotPositive);
+    }
+    tx.pushm(Matrix::identity(n));
+    commit!(tx)
+}
+
+/// `( A -- Aᵀ )` Transposes a matrix.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a matrix.
+pub fn builtin_transpose(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let m = popm!(tx)?;
+    tx.pushm(m.transpose());
+    commit!(tx)
+}
+
+/// `( A -- |A| )` Finds the determinant of a square matrix. Its units are the
+/// matrix's units raised to the matrix's order.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack is empty;
+/// - the item on top of the stack is not a matrix;
+/// - the matrix is not square; or,
+/// - the matrix's units can't be raised to its order.
+pub fn builtin_det(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let m = popm!(tx)?;
+    square(&m)?;
+    tx.push(named(&ctx.settings, stack::Item::Float(m.det()?)));
+    commit!(tx)
+}
+
+/// `( A -- A⁻¹ )` Finds the inverse of a square matrix. Its units are the
+/// inverse of the matrix's units.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack is empty;
+/// - the item on top of the stack is not a matrix;
+/// - the matrix is not square; or,
+/// - the matrix is singular.
+pub fn builtin_inv(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let m = popm!(tx)?;
+    square(&m)?;
+    let inv = m.inverse()?.ok_or(Error::Singular)?;
+    tx.push(named(&ctx.settings, stack::Item::Matrix(inv)));
+    commit!(tx)
+}
+
+/// `( A b -- x )` Solves Ax = b for x, where A is a square matrix and b is a
+/// vector with as many elements as A has rows. b may also be a matrix with as
+/// many rows as A, in which case each of its columns is solved for.
+///
+/// `( f x0 -- x )` Alternatively, finds an x near the guess `x0` for which the
+/// block `f` leaves zero on the stack, e.g. `{ dup * 2 - } 1 solve` finds √2.
+/// `f` is evaluated on a stack of its own that holds only x, in the units of
+/// `x0`, and the result has the units of `x0`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - the items are not a matrix and a vector or matrix, or a block and a
+///   number;
+/// - A is not square;
+/// - b doesn't have as many rows as A;
+/// - A is singular;
+/// - evaluating `f` fails, leaves nothing, or doesn't leave a number;
+/// - `f` leaves numbers with incommensurable units for different x; or,
+/// - no root of `f` could be found.
+pub fn builtin_solve(ctx: &mut Context) -> Result {
+    let x = match ctx.stack.begin().pop2()? {
+        (stack::Item::Matrix(a), b) => solve_linear(&a, b)?,
+        (stack::Item::Block(f), x0) => stack::Item::Float(find_root(ctx, &f, &number(x0)?)?),
+        _ => return Err(stack::Error::TypeMismatch.into()),
+    };
+    let mut tx = ctx.stack.begin();
+    tx.pop2()?;
+    tx.push(named(&ctx.settings, x));
+    commit!(tx)
+}
+
+/// Helper for `solve`. Solves Ax = b, where b is a vector or matrix.
+fn solve_linear(a: &Matrix, b: stack::Item) -> std::result::Result<stack::Item, Error> {
+    square(a)?;
+    Ok(match b {
+        stack::Item::Vector(b) => {
+            let b = Matrix::from_rows(&[b])?.transpose();
+            if b.rows != a.rows {
+                return Err(Error::ShapeMismatch);
+            }
+            let x = a.solve(&b)?.ok_or(Error::Singular)?;
+            stack::Item::Vector(x.elements())
+        }
+        stack::Item::Matrix(b) => {
+            if b.rows != a.rows {
+                return Err(Error::ShapeMismatch);
+            }
+            stack::Item::Matrix(a.solve(&b)?.ok_or(Error::Singular)?)
+        }
+        _ => return Err(stack::Error::TypeMismatch.into()),
+    })
+}
+
+/// Helper for `solve`. Finds a root of the block `f` near `x0`. The solver
+/// works with plain numbers: x in the units of `x0`, and f(x) in the units of
+/// f(x0).
+fn find_root(ctx: &mut Context, f: &Block, x0: &Number) -> std::result::Result<Number, Error> {
+    let x_unit = x0.unit.clone();
+    let f0 = call(ctx, f, x0.clone())?;
+    let mut g = |x: f64| {
+        let y = call(
+            ctx,
+            f,
+            Number {
+                value: x,
+                unit: x_unit.clone(),
+            },
+        )?;
+        Ok::<f64, Error>(y.value_in(f0.unit.as_ref())?)
+    };
+    let x = solver::solve(&mut g, x0.value)?.ok_or(Error::NoConvergence)?;
+    Ok(Number {
+        value: x,
+        unit: x_unit,
+    })
+}
+
+/// Helper for builtins that take blocks. Evaluates `f` on a stack of its own
+/// that holds only `x`, and returns the number it leaves on top. It's an error
+/// for `f` to leave nothing.
+fn call(ctx: &mut Context, f: &Block, x: Number) -> std::result::Result<Number, Error> {
+    let stack = std::mem::take(&mut ctx.stack);
+    let marks = std::mem::take(&mut ctx.marks);
+    ctx.stack.pushf(x);
+    let result = match ctx.eval(&f.words) {
+        eval::Status::Ok | eval::Status::Halt => ctx
+            .stack
+            .pop()
+            .map_err(|_| Error::NoResult)
+            .and_then(|y| Ok(number(y)?)),
+        eval::Status::Err { error, word } => Err(Error::Block {
+            word,
+            error: Box::new(error),
+        }),
+    };
+    ctx.stack = stack;
+    ctx.marks = marks;
+    result
+}
+
+/// `( A i j -- a )` Pushes the element of a matrix in row `i` and column `j`,
+/// counting from zero.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than three items on the stack;
+/// - the items are not a matrix and two dimensionless, whole, non-negative
+///   numbers; or,
+/// - `i` or `j` is past the end of the matrix.
+pub fn builtin_elem(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (i, j) = pop_as_ii!(tx)?;
+    let m = popm!(tx)?;
+    let i = usize::try_from(i.value).map_err(|_| Error::NotNonNegative)?;
+    let j = usize::try_from(j.value).map_err(|_| Error::NotNonNegative)?;
+    if i >= m.rows || j >= m.cols {
+        return Err(Error::IndexOutOfRange);
+    }
+    tx.pushf(m.get(i, j));
+    commit!(tx)
+}
+
+/// Helper for polynomial builtins. Pops the coefficients of a polynomial,
+/// highest power first. They are either a vector or `N` numbers under a
+/// count `N`.
+fn coefficients(tx: &mut stack::Transaction) -> std::result::Result<Vec<Number>, Error> {
+    match tx.pop()? {
+        stack::Item::Vector(v) => Ok(v.numbers().collect()),
+        item => {
+            tx.push(item);
+            let n = pop_as_i!(tx)?;
+            let n = usize::try_from(n.value).map_err(|_| Error::NotNonNegative)?;
+            Ok(tx
+                .pop_n(n)?
+                .into_iter()
+                .map(number)
+                .collect::<std::result::Result<_, _>>()?)
+        }
+    }
+}
+
+/// Helper for polynomial builtins. Returns a number with a value of one in
+/// the units of `unit`.
+fn one(unit: Option<&Unit>) -> Number {
+    Number {
+        value: 1.0,
+        unit: unit.cloned(),
+    }
+}
+
+/// Helper for root-finding builtins. Finds the roots of a polynomial whose
+/// coefficients may have units, along with the unit of x, as a number to
+/// multiply the roots by.
+///
+/// The unit of x comes from the leading coefficient and the last non-zero
+/// one: if they go with xⁿ and xⁿ⁻ᵏ, x has the units of their quotient to
+/// the 1/k. Every coefficient is then converted to the units of the leading
+/// term, which fails if their powers aren't consistent.
+fn roots(c: &[Number]) -> std::result::Result<(Vec<polynomial::Complex>, Number), Error> {
+    let first = c
+        .iter()
+        .position(|x| x.value != 0.0)
+        .ok_or(Error::ZeroPolynomial)?;
+    let c = &c[first..];
+    let lead = one(c[0].unit.as_ref());
+    let x = match c.iter().rposition(|x| x.value != 0.0) {
+        #[allow(clippy::cast_precision_loss)]
+        Some(k) if k > 0 => (&one(c[k].unit.as_ref()) / &lead)?.root(&Number::new(k as f64))?,
+        _ => Number::new(1.0),
+    };
+    #[allow(clippy::cast_precision_loss)]
+    let power = |i: usize| x.pow(&Number::new((c.len() - 1 - i) as f64));
+    let target = (&lead * &power(0)?)?;
+    let mut values = Vec::with_capacity(c.len());
+    for (i, ci) in c.iter().enumerate() {
+        let term = (&one(ci.unit.as_ref()) * &power(i)?)?;
+        values.push(ci.value * term.value_in(target.unit.as_ref())?);
+    }
+    Ok((polynomial::roots(&values), x))
+}
+
+/// `( c0 ... cN N x -- p(x) )` Evaluates a polynomial at `x`. The
+/// coefficients, highest power first, are either `N` numbers under the count
+/// `N` or a single vector, as in `( v x -- p(x) )`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are too few items on the stack;
+/// - `x` or any of the coefficients is not a number;
+/// - `N` is not a dimensionless, whole, non-negative number; or,
+/// - the terms of the polynomial have incommensurable units.
+pub fn builtin_polyval(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let x = number(tx.pop()?)?;
+    let c = coefficients(&mut tx)?;
+    let mut p = Number::new(0.0);
+    for (i, ci) in c.iter().enumerate() {
+        p = if i == 0 {
+            ci.clone()
+        } else {
+            (&(&p * &x)? + ci)?
+        };
+    }
+    tx.push(named(&ctx.settings, stack::Item::Float(p)));
+    commit!(tx)
+}
+
+/// `( c0 ... cN N -- r1 ... rK )` Finds the real roots of a polynomial and
+/// pushes them in ascending order. The coefficients, highest power first,
+/// are either `N` numbers under the count `N` or a single vector. Repeated
+/// roots are pushed as many times as they repeat.
+///
+/// The coefficients may have units, as long as their powers are consistent;
+/// the roots have the units that make every term commensurable. For
+/// example, a m x² + b m⋅s⁻¹ x + c m⋅s⁻² has roots in s⁻¹.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are too few items on the stack;
+/// - any of the coefficients is not a number;
+/// - `N` is not a dimensionless, whole, non-negative number;
+/// - every coefficient is zero; or,
+/// - the coefficients' units aren't consistent.
+pub fn builtin_roots(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let c = coefficients(&mut tx)?;
+    let (roots, x) = roots(&c)?;
+    for r in roots.into_iter().filter(|r| r.im == 0.0) {
+        let r = (&Number::new(r.re) * &x)?;
+        tx.push(named(&ctx.settings, stack::Item::Float(r)));
+    }
+    commit!(tx)
+}
+
+/// `( c0 ... cN N -- z1 ... zN )` Finds all of the roots of a polynomial,
+/// real and complex, and pushes each of them as a vector `[re im]`. Like
+/// `roots`, the coefficients are either `N` numbers under the count `N` or a
+/// single vector, and may have units.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are too few items on the stack;
+/// - any of the coefficients is not a number;
+/// - `N` is not a dimensionless, whole, non-negative number;
+/// - every coefficient is zero; or,
+/// - the coefficients' units aren't consistent.
+pub fn builtin_croots(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let c = coefficients(&mut tx)?;
+    let (roots, x) = roots(&c)?;
+    for r in roots {
+        let z = Vector::new(vec![r.re, r.im]).map(|y| y * &x)?;
+        tx.push(named(&ctx.settings, stack::Item::Vector(z)));
+    }
+    commit!(tx)
+}
+
+/// `( u v -- uv )` Multiplies two polynomials whose coefficients, highest
+/// power first, are vectors.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - the items are not vectors; or,
+/// - the operation would result in a nonsensical temperature unit.
+pub fn builtin_polymul(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = popvv!(tx)?;
+    let scale = (&one(a.unit.as_ref()) * &one(b.unit.as_ref()))?;
+    let values = polynomial::mul(&a.values, &b.values)
+        .into_iter()
+        .map(|x| x * scale.value)
+        .collect();
+    let product = Vector {
+        values,
+        unit: scale.unit,
+    };
+    tx.push(named(&ctx.settings, stack::Item::Vector(product)));
+    commit!(tx)
+}
+
+/// `( u v -- q r )` Divides the polynomial `u` by `v`, pushing the quotient
+/// and the remainder. The coefficients, highest power first, are vectors.
+/// The remainder has one fewer coefficient than `v`, but at least one.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - the items are not vectors;
+/// - every coefficient of `v` is zero; or,
+/// - the operation would result in a nonsensical temperature unit.
+pub fn builtin_polydiv(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = popvv!(tx)?;
+    let scale = (&one(a.unit.as_ref()) / &one(b.unit.as_ref()))?;
+    let (q, r) = polynomial::div(&a.values, &b.values).ok_or(Error::ZeroPolynomial)?;
+    let quotient = Vector {
+        values: q.into_iter().map(|x| x * scale.value).collect(),
+        unit: scale.unit,
+    };
+    let remainder = Vector {
+        values: r,
+        unit: a.unit,
+    };
+    tx.push(named(&ctx.settings, stack::Item::Vector(quotient)));
+    tx.push(named(&ctx.settings, stack::Item::Vector(remainder)));
+    commit!(tx)
+}
+
+/// Macro for creating a trigonometric function builtin.
+macro_rules! trig {
+    ($name: ident, $fn: expr) => {
+        /// `(a -- b)` Computes a trigonometric function. Dimensionless
+        /// numbers are taken to be in the angle mode unit, if there is one.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if:
+        /// - the stack is empty;
+        /// - the item on top of the stack is not a number; or,
+        /// - the number does not have units measuring an angle and there is
+        ///   no angle mode.
+        pub fn $name(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let n = pop_as_f!(tx)?;
+
+            if let Some(u) = n.unit.as_ref().or(ctx.settings.angle_mode.as_ref()) {
+                let n = u.convert(n.value, &RADIAN.as_unit())?;
+                tx.pushx($fn(n));
+                commit!(tx)
+            } else {
+                Err(Error::MissingUnit)
+            }
+        }
+    };
+}
+
+trig!(builtin_sin, f64::sin);
+trig!(builtin_cos, f64::cos);
+trig!(builtin_tan, f64::tan);
+trig!(builtin_sec, |x: f64| x.cos().recip());
+trig!(builtin_csc, |x: f64| x.sin().recip());
+trig!(builtin_cot, |x: f64| x.tan().recip());
+
+/// Macro for creating an inverse trigonometric function.
+macro_rules! inverse_trig {
+    ($name: ident, $fn: ident) => {
+        /// `(a -- b)` Computes an inverse trigonometric function. The result
+        /// is in radians, or in the angle mode unit if `arcmode` is on.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if:
+        /// - the stack is empty;
+        /// - the item on top of the stack is not a number; or,
+        /// - the number is not dimensionless.
+        pub fn $name(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let n = pop_as_f!(tx)?;
+
+            if n.unit.is_none() {
+                tx.pushf(arc(&ctx.settings, n.value.$fn())?);
+                commit!(tx)
+            } else {
+                Err(Error::NotDimensionless)
+            }
+        }
+    };
+}
+
+/// Helper for inverse trigonometric functions. Returns an angle of `radians`
+/// in the unit `settings` calls for.
+fn arc(settings: &Settings, radians: f64) -> std::result::Result<Number, units::Error> {
+    let rad = RADIAN.as_unit();
+    match &settings.angle_mode {
+        Some(u) if settings.arc_in_mode => {
+            Ok(Number::new(rad.convert(radians, u)?).with_unit(u.clone()))
+        }
+        _ => Ok(Number::new(radians).with_unit(rad)),
+    }
+}
+
+inverse_trig!(builtin_asin, asin);
+inverse_trig!(builtin_acos, acos);
+inverse_trig!(builtin_atan, atan);
+
+/// `( y x -- angle )` Finds the angle of the point (`x`, `y`) from the
+/// positive x-axis, in the range [-π, π]. `y` and `x` may have any units as
+/// long as they're commensurable. The result is in radians, or in the angle
+/// mode unit if `arcmode` is on.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - either item is not a number; or,
+/// - `y` and `x` have incommensurable units.
+pub fn builtin_atan2(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (y, x) = pop_as_ff!(tx)?;
+    let y = y.value_in(x.unit.as_ref())?;
+    tx.pushf(arc(&ctx.settings, y.atan2(x.value))?);
+    commit!(tx)
+}
+
+/// `( a b -- √(a²+b²) )` Finds the length of the hypotenuse of a right
+/// triangle with legs `a` and `b`. The result is in the units of `a`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - either item is not a number; or,
+/// - `a` and `b` have incommensurable units.
+pub fn builtin_hypot(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = pop_as_ff!(tx)?;
+    let b = b.value_in(a.unit.as_ref())?;
+    tx.pushf(Number {
+        value: a.value.hypot(b),
+        unit: a.unit,
+    });
+    commit!(tx)
+}
+
+/// Macro for creating a builtin for a function of a dimensionless number.
+macro_rules! dimensionless {
+    ($name: ident, $fn: expr) => {
+        /// `(a -- b)` Computes a function of a dimensionless number.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if:
+        /// - the stack is empty;
+        /// - the item on top of the stack is not a number; or,
+        /// - the number is not dimensionless.
+        pub fn $name(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let n = pop_as_f!(tx)?;
+
+            if n.unit.is_none() {
+                tx.pushx($fn(n.value));
+                commit!(tx)
+            } else {
+                Err(Error::NotDimensionless)
+            }
+        }
+    };
+}
+
+dimensionless!(builtin_sinh, f64::sinh);
+dimensionless!(builtin_cosh, f64::cosh);
+dimensionless!(builtin_tanh, f64::tanh);
+dimensionless!(builtin_asinh, f64::asinh);
+dimensionless!(builtin_acosh, f64::acosh);
+dimensionless!(builtin_atanh, f64::atanh);
+dimensionless!(builtin_gamma, special::gamma);
+dimensionless!(builtin_lgamma, special::lgamma);
+dimensionless!(builtin_erf, special::erf);
+dimensionless!(builtin_erfc, special::erfc);
+dimensionless!(builtin_erfinv, special::erfinv);
+dimensionless!(builtin_j0, |x| special::bessel_j(0, x));
+dimensionless!(builtin_j1, |x| special::bessel_j(1, x));
+dimensionless!(builtin_y0, special::bessel_y0);
+
+/// `( a b -- B(a,b) )` Computes the beta function.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - either item is not a number; or,
+/// - either number is not dimensionless.
+pub fn builtin_beta(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = pop_as_ff!(tx)?;
+    if a.unit.is_some() || b.unit.is_some() {
+        return Err(Error::NotDimensionless);
+    }
+    tx.pushx(special::beta(a.value, b.value));
+    commit!(tx)
+}
+
+/// `( x n -- Jn(x) )` Computes the Bessel function of the first kind of
+/// integer order `n`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - there are fewer than two items on the stack;
+/// - `x` is not a dimensionless number; or,
+/// - `n` is not an integer that fits in 32 bits.
+pub fn builtin_jn(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    let x = pop_as_f!(tx)?;
+    if x.unit.is_some() {
+        return Err(Error::NotDimensionless);
+    }
+    let n = i32::try_from(n.value).map_err(|_| Error::Overflow)?;
+    tx.pushx(special::bessel_j(n, x.value));
+    commit!(tx)
+}
+
+/// `( u -- )` Sets the angle mode: trigonometric functions take dimensionless
+/// numbers to be in `u`, e.g. `deg`.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty;
+/// - the item on top of the stack is not a unit; or,
+/// - the unit doesn't measure angles.
+pub fn builtin_anglemode(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let u = popu!(tx)?;
+    u.convert(1.0, &RADIAN.as_unit())?;
+    ctx.settings.angle_mode = Some(u);
+    commit!(tx)
+}
+
+/// `( -- )` Clears the angle mode, so that trigonometric functions require
+/// numbers with angle units.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_noanglemode(ctx: &mut Context) -> Result {
+    ctx.settings.angle_mode = None;
+    Ok(())
+}
+
+/// `( -- )` Makes inverse trigonometric functions give results in the angle
+/// mode unit.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_arcmode(ctx: &mut Context) -> Result {
+    ctx.settings.arc_in_mode = true;
+    Ok(())
+}
+
+/// `( -- )` Makes inverse trigonometric functions give results in radians.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_noarcmode(ctx: &mut Context) -> Result {
+    ctx.settings.arc_in_mode = false;
+    Ok(())
+}
+
+/// `( ... -- )` Pops everything from the stack.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_clear(ctx: &mut Context) -> Result {
+    ctx.stack.clear();
+    Ok(())
+}
+
+/// `( a -- a a )` Duplicates the item on top of the stack.
+///
+/// # Errors
+///
+/// An error occurs if the stack is empty.
+pub fn builtin_dup(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let a = tx.pop()?;
+    tx.push(a.clone());
+    tx.push(a);
+    commit!(tx)
+}
+
+/// `( [n u] -- n )` Makes a number dimensionless.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a number.
+pub fn builtin_drop(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    match tx.pop()? {
+        stack::Item::Float(x) => {
+            tx.pushx(x.value);
+            commit!(tx)
+        }
+        stack::Item::Integer(_) => Ok(()),
+        stack::Item::Vector(v) => {
+            tx.pushv(Vector::new(v.values));
+            commit!(tx)
+        }
+        stack::Item::Matrix(m) => {
+            tx.pushm(Matrix::new(m.rows, m.cols, m.values));
+            commit!(tx)
+        }
+        stack::Item::Unit(_) | stack::Item::Block(_) => {
+            Err(Error::Stack(stack::Error::TypeMismatch))
+        }
+    }
+}
+
+/// `( [n u1] u2 -- [n u2] )` Converts a number, vector, or matrix into
+/// different units.
+///
+/// A ratio like `%` entered after a plain number is taken as the unit to
+/// convert into, so `0.05 % into` gives `[5 %]`.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - the items are not a number, vector, or matrix and a unit; or,
+/// - the number, vector, or matrix has units that are incommensurable with
+///   `u`.
+pub fn builtin_into(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let u = match tx.pop()? {
+        stack::Item::Float(Number {
+            value,
+            unit: Some(u),
+        }) if u.is_ratio() => {
+            tx.pushx(value);
+            u
+        }
+        item => {
+            tx.push(item);
+            popu!(tx)?
+        }
+    };
+    match tx.pop()? {
+        stack::Item::Vector(v) => tx.pushv(v.map(|x| convert(x.clone(), &u))?),
+        stack::Item::Matrix(m) => tx.pushm(m.map(|x| convert(x.clone(), &u))?),
+        a => tx.pushf(convert(number(a)?, &u)?),
+    }
+    commit!(tx)
+}
+
+/// Helper for `into`. Converts a number into `u`. A dimensionless number is
+/// given the unit `u`.
+fn convert(a: Number, u: &Unit) -> std::result::Result<Number, units::Error> {
+    Ok(if let Some(a_unit) = a.unit {
+        let b = a_unit.convert(a.value, u)?;
+        if u.is_scalar() {
+            Number {
+                value: b,
+                unit: Some(u.clone()),
+            }
+        } else {
+            Number::new(b).with_unit(u.clone())
+        }
+    } else if u.is_scalar() {
+        a.in_ratio(u.clone())
+    } else {
+        a.with_unit(u.clone())
+    })
+}
+
+/// `( [n u] u1 ... uN -- [n1 u1] ... [nN uN] )` Splits a number across
+/// several units, for example feet and inches or hours, minutes, and seconds.
+/// Every unit on top of the stack is used, so `1.8 m ft in split` produces
+/// `[5 ft] [10.866142 in]`. The units should be ordered from largest to
+/// smallest; every part but the last is a whole number.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the top item on the stack is not a unit;
+/// - the units are not preceded by a number with units;
+/// - any of the units are incommensurable with the number; or,
+/// - there is more than one unit and any of them is a temperature like
+///   tempC.
+pub fn builtin_split(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let mut split_units = vec![popu!(tx)?];
+    let x = loop {
+        match tx.pop()? {
+            stack::Item::Unit(u) => split_units.push(u),
+            stack::Item::Float(x) if !x.is_dimensionless() => break x,
+            _ => return Err(Error::MissingUnit),
+        }
+    };
+    split_units.reverse();
+    for part in units::mixed::split(&x, &split_units)? {
+        tx.pushf(part);
+    }
+    commit!(tx)
+}
+
+/// `( [n1 u1] ... [nN uN] -- [n u1] )` Sums the parts of a quantity in mixed
+/// units on top of the stack, such as the result of `split`. Going down from
+/// the top, each part is in a larger unit than the part above it, and the
+/// part above it is less than one of that unit; the first number that doesn't
+/// fit isn't joined. The result is in the units of the bottom-most part, so
+/// `5 ft 10 in join` produces `[5.833333 ft]`, and `1 m 5 ft 10 in join`
+/// leaves `[1 m]` alone.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the top item on the stack is not a number with units.
+pub fn builtin_join(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let top = popf!(tx)?;
+    if top.unit.is_none() {
+        return Err(Error::MissingUnit);
+    }
+    let mut parts = vec![top];
+    while !tx.is_empty() {
+        let above = &parts[parts.len() - 1];
+        match tx.pop()? {
+            stack::Item::Float(x) if nests(above, &x) => parts.push(x),
+            item => {
+                tx.push(item);
+                break;
+            }
+        }
+    }
+    let mut parts = parts.into_iter().rev();
+    let mut sum = parts.next().ok_or(stack::Error::Underflow)?;
+    for part in parts {
+        sum = (&sum + &part)?;
+    }
+    tx.pushf(sum);
+    commit!(tx)
+}
+
+/// Helper for `join`. Returns true if `below` is in a larger unit than
+/// `above`, and `above` is less than one of that unit.
+fn nests(above: &units::Number, below: &units::Number) -> bool {
+    let (Some(ua), Some(ub)) = (&above.unit, &below.unit) else {
+        return false;
+    };
+    ub.convert(1.0, ua)
+        .is_ok_and(|one| one > 1.0 && above.value.abs() < one)
+}
+
+/// `( a -- a )` Shows the dimension of a number or unit, e.g. `L¹ M¹ T⁻²` for
+/// newtons. The stack is unchanged.
+///
+/// # Errors
+///
+/// An error occurs if the stack is empty.
+pub fn builtin_dim(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let item = tx.pop()?;
+    ctx.output.push(dimension(&item)?.to_string());
+    Ok(())
+}
+
+/// `( [n1 u1] -- [n2 u2] )` Converts a number into SI base units. For example,
+/// `[2 kJ]` becomes `[2000 kg⋅m²⋅s⁻²]`.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a number.
+pub fn builtin_si(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let x = pop_as_f!(tx)?;
+    tx.pushf(units::SI.normalize(&x));
+    commit!(tx)
+}
+
+/// `( a -- a )` Shows a number or unit in SI base units, e.g.
+/// `kJ = [1000 kg⋅m²⋅s⁻²]`. The stack is unchanged.
+///
+/// # Errors
+///
+/// An error occurs if the stack is empty.
+pub fn builtin_base(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let item = tx.pop()?;
+    let x = match &item {
+        stack::Item::Unit(u) => Number::new(1.0).with_unit(u.clone()),
+        item => number(item.clone())?,
+    };
+    ctx.output
+        .push(format!("{} = {}", describe(&item), units::SI.normalize(&x)));
+    Ok(())
+}
+
+/// `( a u -- a u )` Explains whether `a` can be converted into `u` with `into`
+/// by showing the dimensions of both and, if they differ, the dimension of
+/// their quotient. The stack is unchanged.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack; or,
+/// - the item on top of the stack is not a unit.
+pub fn builtin_into_explain(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let u = popu!(tx)?;
+    let a = tx.pop()?;
+    let (a_dim, u_dim) = (dimension(&a)?, u.dimension());
+
+    ctx.output.push(format!("{}: {a_dim}", describe(&a)));
+    ctx.output.push(format!("{u}: {u_dim}"));
+    let convertible = match &a {
+        stack::Item::Float(x) => x.unit.as_ref().is_none_or(|a| a.is_commensurable_with(&u)),
+        stack::Item::Vector(Vector { unit, .. }) | stack::Item::Matrix(Matrix { unit, .. }) => {
+            unit.as_ref().is_none_or(|a| a.is_commensurable_with(&u))
+        }
+        stack::Item::Integer(_) => true,
+        stack::Item::Unit(a) => a.is_commensurable_with(&u),
+        stack::Item::Block(_) => false,
+    };
+    ctx.output.push(if convertible {
+        format!("{} can be converted into {u}", describe(&a))
+    } else if a_dim == u_dim {
+        format!("{} and {u} have different zero points", describe(&a))
+    } else {
+        let quotient = a_dim
+            .checked_div(&u_dim)
+            .map_or_else(|| "?".to_string(), |d| d.to_string());
+        format!("{} and {u} differ by {quotient}", describe(&a))
+    });
+    Ok(())
+}
+
+/// `( [n1 u1] ... [nN uN] u -- [n u] )` Finds a product of powers of the
+/// numbers on the stack that measures the same thing as `u`, and converts it
+/// into `u`. Every number with units below `u` is used. For example, given a
+/// velocity, a density, and a length, and `Pa`, the result is the density
+/// times the square of the velocity. The combination is shown.
+///
+/// Exponents are whole numbers between -3 and 3. If several combinations
+/// work, the simplest is used.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the top item on the stack is not a unit;
+/// - the unit is not preceded by a number with units;
+/// - more than six of the numbers are redundant, in that their exponents
+///   aren't determined by the others; or,
+/// - no combination of the numbers measures the same thing as the unit.
+pub fn builtin_dimsolve(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let target = popu!(tx)?;
+
+    let mut quantities = Vec::new();
+    while !tx.is_empty() {
+        match tx.pop()? {
+            stack::Item::Float(x) if !x.is_dimensionless() => quantities.push(x),
+            item => {
+                tx.push(item);
+                break;
+            }
+        }
+    }
+    if quantities.is_empty() {
+        return Err(Error::MissingUnit);
+    }
+    quantities.reverse();
+
+    let dimensions: Vec<units::Dimension> = quantities
+        .iter()
+        .map(|x| {
+            x.unit
+                .as_ref()
+                .map_or_else(units::Dimension::none, Unit::dimension)
+        })
+        .collect();
+    let exponents = units::analysis::exponents(&dimensions, &target.dimension())?
+        .ok_or(Error::NoCombination)?;
+
+    let mut product = Number::new(1.0);
+    for (x, e) in quantities.iter().zip(&exponents) {
+        if *e != 0 {
+            product = (&product * &x.pow(&Number::new(f64::from(*e)))?)?;
+        }
+    }
+    let value = match &product.unit {
+        Some(u) => u.convert(product.value, &target)?,
+        None => product.value,
+    };
+
+    ctx.output
+        .push(units::analysis::expression(&quantities, &exponents));
+    tx.pushf(Number::new(value).with_unit(target));
+    commit!(tx)
+}
+
+/// Helper for inspection builtins. Returns the dimension of a stack item.
+/// Blocks don't have one.
+fn dimension(item: &stack::Item) -> std::result::Result<units::Dimension, stack::Error> {
+    Ok(match item {
+        stack::Item::Float(Number { unit, .. })
+        | stack::Item::Vector(Vector { unit, .. })
+        | stack::Item::Matrix(Matrix { unit, .. }) => unit
+            .as_ref()
+            .map_or_else(units::Dimension::none, Unit::dimension),
+        stack::Item::Integer(_) => units::Dimension::none(),
+        stack::Item::Unit(u) => u.dimension(),
+        stack::Item::Block(_) => return Err(stack::Error::TypeMismatch),
+    })
+}
+
+/// Helper for inspection builtins. Formats a stack item the way the REPL
+/// does.
+fn describe(item: &stack::Item) -> String {
+    match item {
+        stack::Item::Float(x) => x.to_string(),
+        stack::Item::Integer(i) => i.to_string(),
+        stack::Item::Unit(u) => u.to_string(),
+        stack::Item::Vector(v) => v.to_string(),
+        stack::Item::Matrix(m) => m.to_string(),
+        stack::Item::Block(b) => b.to_string(),
+    }
+}
+
+/// `( -- )` Turns on automatic naming, which expresses the results of
+/// arithmetic in named units where possible. For example, the product of
+/// newtons and meters is expressed in joules.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_autoname(ctx: &mut Context) -> Result {
+    ctx.settings.autoname = true;
+    Ok(())
+}
+
+/// `( -- )` Turns off automatic naming.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_noautoname(ctx: &mut Context) -> Result {
+    ctx.settings.autoname = false;
+    Ok(())
+}
+
+/// `( u -- )` Makes a unit eligible for automatic naming. Any eligible units
+/// that are commensurable with `u` are replaced by it.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a unit.
+pub fn builtin_nameable(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let u = popu!(tx)?;
+    ctx.settings
+        .nameable
+        .retain(|n| !n.is_commensurable_with(&u));
+    ctx.settings.nameable.insert(0, u);
+    commit!(tx)
+}
+
+/// `( u -- )` Makes a unit ineligible for automatic naming.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a unit.
+pub fn builtin_unnameable(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let u = popu!(tx)?;
+    ctx.settings.nameable.retain(|n| *n != u);
+    commit!(tx)
+}
+
+/// `( -- )` Expresses the results of arithmetic in SI base units and, if
+/// automatic naming is on, SI named units.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_sisystem(ctx: &mut Context) -> Result {
+    ctx.settings.system = Some(units::SI.clone());
+    Ok(())
+}
+
+/// `( -- )` Expresses the results of arithmetic in US customary units, e.g.
+/// feet, pounds, and pounds-force.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_ussystem(ctx: &mut Context) -> Result {
+    ctx.settings.system = Some(units::US_CUSTOMARY.clone());
+    Ok(())
+}
+
+/// `( -- )` Expresses the results of arithmetic in CGS units, e.g.
+/// centimeters, grams, dynes, and ergs.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_cgssystem(ctx: &mut Context) -> Result {
+    ctx.settings.system = Some(units::CGS.clone());
+    Ok(())
+}
+
+/// `( -- )` Stops expressing the results of arithmetic in a unit system.
+/// Results are expressed in the units of their operands.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_nosystem(ctx: &mut Context) -> Result {
+    ctx.settings.system = None;
+    Ok(())
+}
+
+/// `( -- )` Turns on automatic scaling, which displays numbers with an SI
+/// prefix that keeps their values between 1 and 1000. For example, 0.000047 F
+/// is displayed as 47 uF. Only the display is affected; numbers on the stack
+/// keep their units.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_autoscale(ctx: &mut Context) -> Result {
+    ctx.settings.autoscale = true;
+    Ok(())
+}
+
+/// `( -- )` Turns off automatic scaling.
+///
+/// # Errors
+///
+/// Never returns an error.
+pub fn builtin_noautoscale(ctx: &mut Context) -> Result {
+    ctx.settings.autoscale = false;
+    Ok(())
+}
+
+/// `( u -- )` Displays numbers that are commensurable with `u` in `u`. Any
+/// preferred units that are commensurable with `u` are replaced by it.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a unit.
+pub fn builtin_prefer(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let u = popu!(tx)?;
+    ctx.settings
+        .preferred
+        .retain(|p| !p.is_commensurable_with(&u));
+    ctx.settings.preferred.insert(0, u);
+    commit!(tx)
+}
+
+/// `( u -- )` Stops displaying numbers in `u`.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not a unit.
+pub fn builtin_unprefer(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let u = popu!(tx)?;
+    ctx.settings.preferred.retain(|p| *p != u);
+    commit!(tx)
+}
+
+/// `( -- )` Loads exchange rates from `~/.config/calc/rates.toml`, making the
+/// currencies in it available as units. Warns if the rates are more than a
+/// week old according to the date recorded in the file.
+///
+/// # Errors
+///
+/// An error occurs if the file can't be read or isn't a valid rates file.
+pub fn builtin_loadrates(ctx: &mut Context) -> Result {
+    let rates = units::currency::Rates::load().map_err(Error::Rates)?;
+    ctx.output.push(format!(
+        "loaded {} currencies with rates from {}",
+        rates.len(),
+        rates.date
+    ));
+    let today = units::currency::Date::today();
+    if rates.is_stale(&today) {
+        ctx.output.push(format!(
+            "warning: exchange rates are {} days old",
+            rates.age(&today)
+        ));
+    }
+    ctx.rates = Some(rates);
+    Ok(())
+}
+
+macro_rules! bitwise {
+    ($name: ident, $op: tt) => {
+        /// `( a b -- c )` Computes a bitwise function of two integers.
+        ///
+        /// # Errors
+        ///
+        /// An error occurs if:
+        /// - there are fewer than two items on the stack; or,
+        /// - the items are not integers.
+        pub fn $name(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let (a, b) = pop_as_ii!(tx)?;
+            tx.pushi(integer::Integer::new(a.value $op b.value, a.repr));
+            commit!(tx)
+        }
+    };
+}
+
+bitwise!(builtin_bitwise_and, &);
+bitwise!(builtin_bitwise_or, |);
+bitwise!(builtin_bitwise_xor, ^);
+
+/// `( a -- ~a )` Computes the bitwise complement of an integer.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack is not an integer.
+pub fn builtin_bitwise_complement(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let x = pop_as_i!(tx)?;
+    tx.pushi(integer::Integer::new(!x.value, x.repr));
+    commit!(tx)
+}
+
+macro_rules! binrepr {
+    ($name: ident, $repr: expr) => {
+        /// `( a -- a )` Changes the representation of an integer.
+        ///
+        /// # Errors
+        /// An error occurs if:
+        /// - the stack is empty; or,
+        /// - the item on top of the stack is not an integer.
+        pub fn $name(ctx: &mut Context) -> Result {
+            let mut tx = ctx.stack.begin();
+            let x = pop_as_i!(tx)?;
+            tx.pushi(x.with_repr($repr));
+            commit!(tx)
+        }
+    };
+}
+
+binrepr!(builtin_bin, integer::Representation::Binary);
+binrepr!(builtin_dec, integer::Representation::Decimal);
+binrepr!(builtin_oct, integer::Representation::Octal);
+binrepr!(builtin_hex, integer::Representation::Hexadecimal);
+
+/// `( a b -- [a & (1<<b)] )` Sets the bit in `a` at index `b`. The least
+/// significant bit is index zero.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - `a` is not an integer; or,
+/// - `b` is not a non-negative integer.
+pub fn builtin_bset(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = pop_as_ii!(tx)?;
+    if b.value >= 0 {
+        tx.pushi(integer::Integer::new(a.value & (1 << b.value), a.repr));
+        commit!(tx)
+    } else {
+        Err(Error::NotNonNegative)
+    }
+}
+
+/// `( a b -- [a & ~(1<<b)] )` Clears the bit in `a` at index `b`. The least
+/// significant bit is index zero.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - `a` is not an integer; or,
+/// - `b` is not a non-negative integer.
+pub fn builtin_bclr(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = pop_as_ii!(tx)?;
+    if b.value >= 0 {
+        tx.pushi(integer::Integer::new(a.value & !(1 << b.value), a.repr));
+        commit!(tx)
+    } else {
+        Err(Error::NotNonNegative)
+    }
+}
+
+/// `( a b -- a [(a >> b) & 1] )` Pushes the bit in `a` at index `b`. The least
+/// significant bit is index zero.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - `a` is not an integer; or,
+/// - `b` is not a non-negative integer.
+pub fn builtin_bget(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = pop_as_ii!(tx)?;
+    if b.value >= 0 {
+        let bit = (a.value >> b.value) & 1;
+        tx.pushi(a);
+        tx.pushi(integer::Integer::bin(bit));
+        commit!(tx)
+    } else {
+        Err(Error::NotNonNegative)
+    }
+}
+
+/// `( n -- n! )` Finds the factorial of `n`.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty;
+/// - `n` is not a non-negative integer; or,
+/// - the result doesn't fit in a 64-bit integer.
+pub fn builtin_factorial(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    if n.value < 0 {
+        return Err(Error::NotNonNegative);
+    }
+    let result = number_theory::factorial(n.value).ok_or(Error::Overflow)?;
+    tx.pushi(integer::Integer::new(result, n.repr));
+    commit!(tx)
+}
+
+/// `( n r -- nCr )` Finds the number of ways to choose `r` of `n` items
+/// without regard to order.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - either item is not a non-negative integer; or,
+/// - the result doesn't fit in a 64-bit integer.
+pub fn builtin_ncr(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (n, r) = pop_as_ii!(tx)?;
+    if n.value < 0 || r.value < 0 {
+        return Err(Error::NotNonNegative);
+    }
+    let result = number_theory::choose(n.value, r.value).ok_or(Error::Overflow)?;
+    tx.pushi(integer::Integer::new(result, n.repr));
+    commit!(tx)
+}
+
+/// `( n r -- nPr )` Finds the number of ways to choose `r` of `n` items in
+/// order.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - either item is not a non-negative integer; or,
+/// - the result doesn't fit in a 64-bit integer.
+pub fn builtin_npr(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (n, r) = pop_as_ii!(tx)?;
+    if n.value < 0 || r.value < 0 {
+        return Err(Error::NotNonNegative);
+    }
+    let result = number_theory::permutations(n.value, r.value).ok_or(Error::Overflow)?;
+    tx.pushi(integer::Integer::new(result, n.repr));
+    commit!(tx)
+}
+
+/// `( a b -- gcd )` Finds the greatest common divisor of two integers.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - either item is not an integer; or,
+/// - the result doesn't fit in a 64-bit integer.
+pub fn builtin_gcd(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = pop_as_ii!(tx)?;
+    let result = number_theory::gcd(a.value, b.value).ok_or(Error::Overflow)?;
+    tx.pushi(integer::Integer::new(result, a.repr));
+    commit!(tx)
+}
+
+/// `( a b -- lcm )` Finds the least common multiple of two integers.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - either item is not an integer; or,
+/// - the result doesn't fit in a 64-bit integer.
+pub fn builtin_lcm(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, b) = pop_as_ii!(tx)?;
+    let result = number_theory::lcm(a.value, b.value).ok_or(Error::Overflow)?;
+    tx.pushi(integer::Integer::new(result, a.repr));
+    commit!(tx)
+}
+
+/// `( n -- 0|1 )` Pushes 1 if `n` is prime and 0 if it isn't.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - `n` is not an integer.
+pub fn builtin_isprime(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    let result = i64::from(number_theory::is_prime(n.value));
+    tx.pushi(integer::Integer::new(result, n.repr));
+    commit!(tx)
+}
+
+/// `( n -- p1 ... pk )` Pushes the prime factors of `n` in ascending order,
+/// with repeated factors repeated. 1 has no prime factors.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - `n` is not a positive integer.
+pub fn builtin_factor(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    if n.value <= 0 {
+        return Err(Error::NotPositive);
+    }
+    for p in number_theory::factor(n.value) {
+        tx.pushi(integer::Integer::new(p, n.repr));
+    }
+    commit!(tx)
+}
+
+/// `( n -- p )` Finds the smallest prime greater than `n`.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty;
+/// - `n` is not an integer; or,
+/// - the result doesn't fit in a 64-bit integer.
+pub fn builtin_nextprime(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    let result = number_theory::next_prime(n.value).ok_or(Error::Overflow)?;
+    tx.pushi(integer::Integer::new(result, n.repr));
+    commit!(tx)
+}
+
+/// `( b e m -- b**e%m )` Raises `b` to the power of `e` modulo `m`. The result
+/// is in the range [0, `m`).
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than three items on the stack;
+/// - any of the items is not an integer;
+/// - `e` is negative; or,
+/// - `m` is not positive.
+pub fn builtin_modpow(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (e, m) = pop_as_ii!(tx)?;
+    let b = pop_as_i!(tx)?;
+    if e.value < 0 {
+        return Err(Error::NotNonNegative);
+    }
+    if m.value <= 0 {
+        return Err(Error::NotPositive);
+    }
+    let result = number_theory::mod_pow(b.value, e.value, m.value);
+    tx.pushi(integer::Integer::new(result, b.repr));
+    commit!(tx)
+}
+
+/// `( a m -- x )` Finds the multiplicative inverse of `a` modulo `m`: the `x`
+/// in the range [0, `m`) for which `a*x%m` is 1.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - there are fewer than two items on the stack;
+/// - either item is not an integer;
+/// - `m` is not positive; or,
+/// - `a` and `m` aren't coprime, so there's no inverse.
+pub fn builtin_modinv(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let (a, m) = pop_as_ii!(tx)?;
+    if m.value <= 0 {
+        return Err(Error::NotPositive);
+    }
+    let result = number_theory::mod_inv(a.value, m.value).ok_or(Error::NotInvertible)?;
+    tx.pushi(integer::Integer::new(result, a.repr));
+    commit!(tx)
+}
+
+/// `( ... a1 ... aN N -- a1 ... aN )` Removes everything from the stack except
+/// the topmost `N` items.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the stack has fewer than N+1 items; or,
+/// - the item on top of the stack is not a dimensionless, whole, non-negative
+///   number.
+pub fn builtin_keep(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let n = pop_as_i!(tx)?;
+    if n.value < 0 {
+        return Err(Error::NotNonNegative);
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    tx.keep(n.value as usize)?;
     commit!(tx)
 }
 
@@ -529,8 +3017,8 @@ pub fn builtin_keep(stack: &mut Stack) -> Result {
 /// # Errors
 ///
 /// Returns an error if the stack is empty.
-pub fn builtin_pop(stack: &mut Stack) -> Result {
-    stack
+pub fn builtin_pop(ctx: &mut Context) -> Result {
+    ctx.stack
         .pop()
         .map_or(Err(Error::Stack(stack::Error::Underflow)), |_| Ok(()))
 }
@@ -540,8 +3028,8 @@ pub fn builtin_pop(stack: &mut Stack) -> Result {
 /// # Errors
 ///
 /// Returns an error if the stack has fewer than two items.
-pub fn builtin_swap(stack: &mut Stack) -> Result {
-    let mut tx = stack.begin();
+pub fn builtin_swap(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
     let (a, b) = tx.pop2()?;
     tx.push(b);
     tx.push(a);
@@ -550,60 +3038,81 @@ pub fn builtin_swap(stack: &mut Stack) -> Result {
 
 /// Builtin for words that are units.
 ///
-/// If the item on top of the stack is a dimensionless number, that number is
-/// assigned the unit `u`. Otherwise, `u` is pushed onto the stack.
-#[allow(clippy::missing_panics_doc)]
+/// If the item on top of the stack is a dimensionless number, vector, or
+/// matrix, it is assigned the unit `u`. Otherwise, `u` is pushed onto the stack.
+/// A number keeps a ratio like `%`, so that `0.05 %` is displayed as
+/// `[0.05 %]` and can be converted with `into`. A number already in a ratio
+/// counts as dimensionless, except that another ratio is pushed rather than
+/// applied to it.
 pub fn builtin_unit(u: &Unit, stack: &mut Stack) {
     let mut tx = stack.begin();
-    if let Ok(x) = popn!(tx) {
-        match x {
-            stack::Item::Float(x) => {
-                if x.is_dimensionless() {
-                    tx.pushf(x.with_unit(u.clone()));
-                    return tx.commit();
-                }
+    let in_unit = |x: Number| {
+        if u.is_ratio() {
+            Number {
+                value: x.value,
+                unit: Some(u.clone()),
             }
-            stack::Item::Integer(x) => {
-                tx.pushf(x.as_units_number().with_unit(u.clone()));
-                return tx.commit();
-            }
-            stack::Item::Unit(_) => panic!("invariant wasn't"),
+        } else {
+            x.with_unit(u.clone())
+        }
+    };
+    match tx.pop() {
+        Ok(stack::Item::Float(x)) if x.is_dimensionless() => {
+            tx.pushf(in_unit(x));
+            return tx.commit();
+        }
+        Ok(stack::Item::Float(x))
+            if !u.is_ratio() && x.unit.as_ref().is_some_and(Unit::is_ratio) =>
+        {
+            tx.pushf(in_unit(x.folded()));
+            return tx.commit();
+        }
+        Ok(stack::Item::Integer(x)) => {
+            tx.pushf(in_unit(x.as_units_number()));
+            return tx.commit();
+        }
+        Ok(stack::Item::Vector(v)) if v.unit.is_none() => {
+            tx.pushv(v.with_unit(u.clone()));
+            return tx.commit();
         }
+        Ok(stack::Item::Matrix(m)) if m.unit.is_none() => {
+            tx.pushm(m.with_unit(u.clone()));
+            return tx.commit();
+        }
+        _ => {}
     }
     stack.pushu(u.clone());
 }
 
-/// Creates a builtin for an anonymous `Unit` (a unit without a symbol) that
-/// pushes the unit.
-macro_rules! anonunit {
-    ($u:expr) => {
-        |stack| {
-            builtin_unit($u, stack);
-            Ok(())
-        }
-    };
-}
-
-/// Creates a builtin for a `Base` that pushes a unit.
-macro_rules! base {
-    ($b:expr) => {
-        ($b.symbol, anonunit!(&Unit::new(&[$b], &[]).unwrap()))
-    };
+/// Builtin for words that are physical constants, like `@c`. Pushes the
+/// constant's value, in a named unit if one is eligible.
+pub fn builtin_constant(c: &constants::Constant, ctx: &mut Context) {
+    let mut tx = ctx.stack.begin();
+    tx.push(named(&ctx.settings, stack::Item::Float(c.value.clone())));
+    tx.commit();
 }
 
-/// Creates a builtin for a named `Unit` (a unit with a symbol) that pushes the
-/// unit.
-macro_rules! unit {
-    ($u:expr) => {
-        ($u.symbol.as_ref().unwrap().as_str(), anonunit!($u))
-    };
+/// `( a -- a )` Shows the name, value, description, and uncertainty of the
+/// physical constant equal to `a`, e.g. after `@G`. The stack is unchanged.
+///
+/// # Errors
+///
+/// An error occurs if:
+/// - the stack is empty; or,
+/// - the item on top of the stack isn't one of the constants.
+pub fn builtin_const_explain(ctx: &mut Context) -> Result {
+    let mut tx = ctx.stack.begin();
+    let x = pop_as_f!(tx)?;
+    let c = constants::find(&x).ok_or(Error::NotAConstant)?;
+    ctx.output.push(c.to_string());
+    Ok(())
 }
 
 /// Creates a builtin for a dimensionless constant that pushes the constant.
 macro_rules! constx {
     ($value:expr) => {
-        |stack| {
-            stack.pushx($value);
+        |ctx| {
+            ctx.stack.pushx($value);
             Ok(())
         }
     };
@@ -612,8 +3121,9 @@ macro_rules! constx {
 /// Creates a builtin for a constant with units that pushes the constant.
 macro_rules! constf {
     ($value:expr, $unit:expr) => {
-        |stack| {
-            stack.pushf(Number::new($value).with_unit(($unit).unwrap()));
+        |ctx| {
+            ctx.stack
+                .pushf(Number::new($value).with_unit(($unit).unwrap()));
             Ok(())
         }
     };
@@ -627,10 +3137,11 @@ pub fn table() -> Table {
         // Constants
         ("c", constf!(299_792_458.0, METER / SECOND) as Builtin),
         ("e", constx!(std::f64::consts::E)),
-        ("g0", constf!(9.8065, (METER / SECOND).unwrap() / SECOND)),
+        ("g0", constf!(9.806_65, (METER / SECOND).unwrap() / SECOND)),
         ("h", constf!(6.626_070_15e-34, &*JOULE * SECOND)),
         ("hbar", constf!(1.054_571_817e-34, &*JOULE * SECOND)),
         ("pi", constx!(std::f64::consts::PI)),
+        ("const?", builtin_const_explain),
         // Arithmetic
         ("+", builtin_add),
         ("-", builtin_sub),
@@ -641,6 +3152,97 @@ pub fn table() -> Table {
         ("sqrt", builtin_sqrt),
         ("cbrt", builtin_cbrt),
         ("/**", builtin_root),
+        ("abs", builtin_abs),
+        ("neg", builtin_neg),
+        ("sign", builtin_sign),
+        ("floor", builtin_floor),
+        ("ceil", builtin_ceil),
+        ("round", builtin_round),
+        ("trunc", builtin_trunc),
+        ("frac", builtin_frac),
+        ("roundto", builtin_roundto),
+        ("roundsig", builtin_roundsig),
+        ("lesser", builtin_lesser),
+        ("greater", builtin_greater),
+        ("clamp", builtin_clamp),
+        // Special Functions
+        ("gamma", builtin_gamma),
+        ("lgamma", builtin_lgamma),
+        ("beta", builtin_beta),
+        ("erf", builtin_erf),
+        ("erfc", builtin_erfc),
+        ("erfinv", builtin_erfinv),
+        ("J0", builtin_j0),
+        ("J1", builtin_j1),
+        ("Jn", builtin_jn),
+        ("Y0", builtin_y0),
+        // Statistics
+        ("sum", builtin_sum),
+        ("sumn", builtin_sumn),
+        ("mean", builtin_mean),
+        ("meann", builtin_meann),
+        ("median", builtin_median),
+        ("mediann", builtin_mediann),
+        ("stdev", builtin_stdev),
+        ("stdevn", builtin_stdevn),
+        ("var", builtin_var),
+        ("varn", builtin_varn),
+        ("minimum", builtin_minimum),
+        ("minn", builtin_minn),
+        ("maximum", builtin_maximum),
+        ("maxn", builtin_maxn),
+        ("range", builtin_range),
+        ("rangen", builtin_rangen),
+        ("rms", builtin_rms),
+        ("rmsn", builtin_rmsn),
+        ("geomean", builtin_geomean),
+        ("geomeann", builtin_geomeann),
+        // Distributions
+        ("normpdf", builtin_normpdf),
+        ("normcdf", builtin_normcdf),
+        ("norminv", builtin_norminv),
+        ("tpdf", builtin_tpdf),
+        ("tcdf", builtin_tcdf),
+        ("tinv", builtin_tinv),
+        ("chi2pdf", builtin_chi2pdf),
+        ("chi2cdf", builtin_chi2cdf),
+        ("chi2inv", builtin_chi2inv),
+        ("exppdf", builtin_exppdf),
+        ("expcdf", builtin_expcdf),
+        ("expinv", builtin_expinv),
+        ("unifpdf", builtin_unifpdf),
+        ("unifcdf", builtin_unifcdf),
+        ("unifinv", builtin_unifinv),
+        ("binompdf", builtin_binompdf),
+        ("binomcdf", builtin_binomcdf),
+        ("poisspdf", builtin_poisspdf),
+        ("poisscdf", builtin_poisscdf),
+        // Random Numbers
+        ("rand", builtin_rand),
+        ("randint", builtin_randint),
+        ("randn", builtin_randn),
+        ("seed", builtin_seed),
+        // Vectors
+        ("[", builtin_open_bracket),
+        ("]", builtin_close_bracket),
+        ("vector", builtin_vector),
+        ("dot", builtin_dot),
+        ("cross", builtin_cross),
+        ("norm", builtin_norm),
+        ("unit", builtin_unit_vector),
+        // Matrices
+        ("identity", builtin_identity),
+        ("transpose", builtin_transpose),
+        ("det", builtin_det),
+        ("inv", builtin_inv),
+        ("solve", builtin_solve),
+        ("elem", builtin_elem),
+        // Polynomials
+        ("polyval", builtin_polyval),
+        ("roots", builtin_roots),
+        ("croots", builtin_croots),
+        ("polymul", builtin_polymul),
+        ("polydiv", builtin_polydiv),
         // Trigonometric
         ("sin", builtin_sin),
         ("cos", builtin_cos),
@@ -648,9 +3250,44 @@ pub fn table() -> Table {
         ("asin", builtin_asin),
         ("acos", builtin_acos),
         ("atan", builtin_atan),
+        ("atan2", builtin_atan2),
+        ("sec", builtin_sec),
+        ("csc", builtin_csc),
+        ("cot", builtin_cot),
+        ("hypot", builtin_hypot),
+        ("sinh", builtin_sinh),
+        ("cosh", builtin_cosh),
+        ("tanh", builtin_tanh),
+        ("asinh", builtin_asinh),
+        ("acosh", builtin_acosh),
+        ("atanh", builtin_atanh),
+        ("anglemode", builtin_anglemode),
+        ("noanglemode", builtin_noanglemode),
+        ("arcmode", builtin_arcmode),
+        ("noarcmode", builtin_noarcmode),
         // Unit Conversion
         ("drop", builtin_drop),
         ("into", builtin_into),
+        ("split", builtin_split),
+        ("join", builtin_join),
+        ("dim", builtin_dim),
+        ("si", builtin_si),
+        ("base", builtin_base),
+        ("into?", builtin_into_explain),
+        ("dimsolve", builtin_dimsolve),
+        ("autoname", builtin_autoname),
+        ("noautoname", builtin_noautoname),
+        ("nameable", builtin_nameable),
+        ("unnameable", builtin_unnameable),
+        ("sisystem", builtin_sisystem),
+        ("ussystem", builtin_ussystem),
+        ("cgssystem", builtin_cgssystem),
+        ("nosystem", builtin_nosystem),
+        ("autoscale", builtin_autoscale),
+        ("noautoscale", builtin_noautoscale),
+        ("prefer", builtin_prefer),
+        ("unprefer", builtin_unprefer),
+        ("loadrates", builtin_loadrates),
         // Bitwise Operations
         ("&", builtin_bitwise_and),
         ("|", builtin_bitwise_or),
@@ -663,219 +3300,22 @@ pub fn table() -> Table {
         ("bset", builtin_bset),
         ("bclr", builtin_bclr),
         ("bget", builtin_bget),
+        // Number Theory
+        ("!", builtin_factorial),
+        ("nCr", builtin_ncr),
+        ("nPr", builtin_npr),
+        ("gcd", builtin_gcd),
+        ("lcm", builtin_lcm),
+        ("isprime", builtin_isprime),
+        ("factor", builtin_factor),
+        ("nextprime", builtin_nextprime),
+        ("modpow", builtin_modpow),
+        ("modinv", builtin_modinv),
         // Stack Manipulation
         ("clear", builtin_clear),
         ("dup", builtin_dup),
         ("keep", builtin_keep),
         ("pop", builtin_pop),
         ("swap", builtin_swap),
-        // Units
-        // SI bases
-        base!(units::SECOND),
-        base!(units::METER),
-        base!(units::KILOGRAM),
-        base!(units::AMPERE),
-        base!(units::KELVIN),
-        base!(units::MOLE),
-        base!(units::CANDELA),
-        base!(units::RADIAN),
-        // Time
-        base!(units::DAY),
-        base!(units::HOUR),
-        base!(units::MINUTE),
-        // Length
-        base!(units::INCH),
-        base!(units::FOOT),
-        base!(units::MILE),
-        base!(units::NAUTICAL_MILE),
-        base!(units::MIL),
-        base!(units::YARD),
-        // Mass
-        base!(units::POUND_MASS),
-        base!(units::OUNCE),
-        // Temperature
-        base!(units::RANKINE),
-        base!(units::DEG_CELSIUS),
-        base!(units::DEG_FAHRENHEIT),
-        base!(units::TEMP_CELSIUS),
-        base!(units::TEMP_FAHRENHEIT),
-        // Angle
-        base!(units::DEGREE),
-        // Energy
-        unit!(&*units::JOULE),
-        // Force
-        unit!(&*units::NEWTON),
-        unit!(&*units::POUND_FORCE),
-        // Power
-        unit!(&*units::WATT),
-        // Pressure
-        unit!(&*units::PASCAL),
-        unit!(&*units::PSI),
-        // Electromagnetic
-        unit!(&*units::VOLT),
-        unit!(&*units::OHM),
-        unit!(&*units::FARAD),
-        unit!(&*units::TESLA),
-        // SI prefixes
-        base!(units::PETASECOND),
-        base!(units::TERASECOND),
-        base!(units::GIGASECOND),
-        base!(units::MEGASECOND),
-        base!(units::KILOSECOND),
-        base!(units::MILLISECOND),
-        base!(units::MICROSECOND),
-        base!(units::NANOSECOND),
-        base!(units::PICOSECOND),
-        base!(units::FEMTOSECOND),
-        base!(units::PETAMETER),
-        base!(units::TERAMETER),
-        base!(units::GIGAMETER),
-        base!(units::MEGAMETER),
-        base!(units::KILOMETER),
-        base!(units::CENTIMETER),
-        base!(units::MILLIMETER),
-        base!(units::MICROMETER),
-        base!(units::NANOMETER),
-        base!(units::PICOMETER),
-        base!(units::FEMTOMETER),
-        base!(units::PETAGRAM),
-        base!(units::TERAGRAM),
-        base!(units::GIGAGRAM),
-        base!(units::MEGAGRAM),
-        base!(units::MILLIGRAM),
-        base!(units::MICROGRAM),
-        base!(units::NANOGRAM),
-        base!(units::PICOGRAM),
-        base!(units::FEMTOGRAM),
-        base!(units::PETAAMPERE),
-        base!(units::TERAAMPERE),
-        base!(units::GIGAAMPERE),
-        base!(units::MEGAAMPERE),
-        base!(units::KILOAMPERE),
-        base!(units::MILLIAMPERE),
-        base!(units::MICROAMPERE),
-        base!(units::NANOAMPERE),
-        base!(units::PICOAMPERE),
-        base!(units::FEMTOAMPERE),
-        base!(units::PETAKELVIN),
-        base!(units::TERAKELVIN),
-        base!(units::GIGAKELVIN),
-        base!(units::MEGAKELVIN),
-        base!(units::KILOKELVIN),
-        base!(units::MILLIKELVIN),
-        base!(units::MICROKELVIN),
-        base!(units::NANOKELVIN),
-        base!(units::PICOKELVIN),
-        base!(units::FEMTOKELVIN),
-        base!(units::PETAMOLE),
-        base!(units::TERAMOLE),
-        base!(units::GIGAMOLE),
-        base!(units::MEGAMOLE),
-        base!(units::KILOMOLE),
-        base!(units::MILLIMOLE),
-        base!(units::MICROMOLE),
-        base!(units::NANOMOLE),
-        base!(units::PICOMOLE),
-        base!(units::FEMTOMOLE),
-        base!(units::PETACANDELA),
-        base!(units::TERACANDELA),
-        base!(units::GIGACANDELA),
-        base!(units::MEGACANDELA),
-        base!(units::KILOCANDELA),
-        base!(units::MILLICANDELA),
-        base!(units::MICROCANDELA),
-        base!(units::NANOCANDELA),
-        base!(units::PICOCANDELA),
-        base!(units::FEMTOCANDELA),
-        base!(units::PETARADIAN),
-        base!(units::TERARADIAN),
-        base!(units::GIGARADIAN),
-        base!(units::MEGARADIAN),
-        base!(units::KILORADIAN),
-        base!(units::MILLIRADIAN),
-        base!(units::MICRORADIAN),
-        base!(units::NANORADIAN),
-        base!(units::PICORADIAN),
-        base!(units::FEMTORADIAN),
-        unit!(&*units::PETAJOULE),
-        unit!(&*units::TERAJOULE),
-        unit!(&*units::GIGAJOULE),
-        unit!(&*units::MEGAJOULE),
-        unit!(&*units::KILOJOULE),
-        unit!(&*units::MILLIJOULE),
-        unit!(&*units::MICROJOULE),
-        unit!(&*units::NANOJOULE),
-        unit!(&*units::PICOJOULE),
-        unit!(&*units::FEMTOJOULE),
-        unit!(&*units::PETANEWTON),
-        unit!(&*units::TERANEWTON),
-        unit!(&*units::GIGANEWTON),
-        unit!(&*units::MEGANEWTON),
-        unit!(&*units::KILONEWTON),
-        unit!(&*units::MILLINEWTON),
-        unit!(&*units::MICRONEWTON),
-        unit!(&*units::NANONEWTON),
-        unit!(&*units::PICONEWTON),
-        unit!(&*units::FEMTONEWTON),
-        unit!(&*units::PETAWATT),
-        unit!(&*units::TERAWATT),
-        unit!(&*units::GIGAWATT),
-        unit!(&*units::MEGAWATT),
-        unit!(&*units::KILOWATT),
-        unit!(&*units::MILLIWATT),
-        unit!(&*units::MICROWATT),
-        unit!(&*units::NANOWATT),
-        unit!(&*units::PICOWATT),
-        unit!(&*units::FEMTOWATT),
-        unit!(&*units::PETAPASCAL),
-        unit!(&*units::TERAPASCAL),
-        unit!(&*units::GIGAPASCAL),
-        unit!(&*units::MEGAPASCAL),
-        unit!(&*units::KILOPASCAL),
-        unit!(&*units::MILLIPASCAL),
-        unit!(&*units::MICROPASCAL),
-        unit!(&*units::NANOPASCAL),
-        unit!(&*units::PICOPASCAL),
-        unit!(&*units::FEMTOPASCAL),
-        unit!(&*units::PETAVOLT),
-        unit!(&*units::TERAVOLT),
-        unit!(&*units::GIGAVOLT),
-        unit!(&*units::MEGAVOLT),
-        unit!(&*units::KILOVOLT),
-        unit!(&*units::MILLIVOLT),
-        unit!(&*units::MICROVOLT),
-        unit!(&*units::NANOVOLT),
-        unit!(&*units::PICOVOLT),
-        unit!(&*units::FEMTOVOLT),
-        unit!(&*units::PETAOHM),
-        unit!(&*units::TERAOHM),
-        unit!(&*units::GIGAOHM),
-        unit!(&*units::MEGAOHM),
-        unit!(&*units::KILOOHM),
-        unit!(&*units::MILLIOHM),
-        unit!(&*units::MICROOHM),
-        unit!(&*units::NANOOHM),
-        unit!(&*units::PICOOHM),
-        unit!(&*units::FEMTOOHM),
-        unit!(&*units::PETAFARAD),
-        unit!(&*units::TERAFARAD),
-        unit!(&*units::GIGAFARAD),
-        unit!(&*units::MEGAFARAD),
-        unit!(&*units::KILOFARAD),
-        unit!(&*units::MILLIFARAD),
-        unit!(&*units::MICROFARAD),
-        unit!(&*units::NANOFARAD),
-        unit!(&*units::PICOFARAD),
-        unit!(&*units::FEMTOFARAD),
-        unit!(&*units::PETATESLA),
-        unit!(&*units::TERATESLA),
-        unit!(&*units::GIGATESLA),
-        unit!(&*units::MEGATESLA),
-        unit!(&*units::KILOTESLA),
-        unit!(&*units::MILLITESLA),
-        unit!(&*units::MICROTESLA),
-        unit!(&*units::NANOTESLA),
-        unit!(&*units::PICOTESLA),
-        unit!(&*units::FEMTOTESLA),
     ])
 }
@@ -0,0 +1,354 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Physical constants from the CODATA 2018 recommended values.
+//!
+//! Constants are named with a leading `@`, e.g. `@e` for the elementary
+//! charge, so that they can't be confused with units or other words.
+
+use approx::relative_eq;
+use once_cell::sync::Lazy;
+
+use crate::units::{self, Number};
+
+/// The prefix that all constant names start with.
+pub const PREFIX: char = '@';
+
+/// A physical constant.
+#[derive(Clone, Debug)]
+pub struct Constant {
+    /// The constant's name, including `PREFIX`.
+    pub name: &'static str,
+    /// What the constant is, e.g. "Newtonian constant of gravitation".
+    pub description: &'static str,
+    /// The constant's value in SI units.
+    pub value: Number,
+    /// The standard uncertainty of the value relative to the value. Exact
+    /// constants have an uncertainty of zero.
+    pub uncertainty: f64,
+}
+
+impl Constant {
+    /// Returns true if the value is exact by definition.
+    #[must_use]
+    pub fn is_exact(&self) -> bool {
+        self.uncertainty == 0.0
+    }
+
+    /// Returns true if `x` is equal to this constant, possibly in different
+    /// but commensurable units.
+    #[must_use]
+    pub fn matches(&self, x: &Number) -> bool {
+        let value = match (&x.unit, &self.value.unit) {
+            (Some(from), Some(to)) => from.convert(x.value, to).ok(),
+            (None, None) => Some(x.value),
+            _ => None,
+        };
+        value
+            .is_some_and(|v| relative_eq!(v, self.value.value, epsilon = 0.0, max_relative = 1e-12))
+    }
+}
+
+impl std::fmt::Display for Constant {
+    /// Formats the constant with its value at full precision, e.g.
+    /// "@G = 6.6743e-11 m³⋅kg⁻¹⋅s⁻² (Newtonian constant of gravitation;
+    /// relative uncertainty 2.2e-5)".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let v = self.value.value;
+        if (1e-3..1e7).contains(&v.abs()) {
+            write!(f, "{} = {v}", self.name)?;
+        } else {
+            write!(f, "{} = {v:e}", self.name)?;
+        }
+        if let Some(u) = &self.value.unit {
+            write!(f, " {u}")?;
+        }
+        if self.is_exact() {
+            write!(f, " ({}; exact)", self.description)
+        } else {
+            write!(
+                f,
+                " ({}; relative uncertainty {:e})",
+                self.description, self.uncertainty
+            )
+        }
+    }
+}
+
+/// Returns the constant named `name`, which includes `PREFIX`.
+#[must_use]
+pub fn get(name: &str) -> Option<&'static Constant> {
+    CONSTANTS.iter().find(|c| c.name == name)
+}
+
+/// Returns the first constant that `x` is equal to.
+#[must_use]
+pub fn find(x: &Number) -> Option<&'static Constant> {
+    CONSTANTS.iter().find(|c| c.matches(x))
+}
+
+/// Helper for defining constants. `unit` is a unit expression like `J/K`, or
+/// empty if the constant is dimensionless.
+fn constant(
+    name: &'static str,
+    description: &'static str,
+    value: f64,
+    unit: &str,
+    uncertainty: f64,
+) -> Constant {
+    let unit = (!unit.is_empty())
+        .then(|| units::parse_with(unit, &[]).unwrap_or_else(|e| panic!("{name}: {e:?}")));
+    Constant {
+        name,
+        description,
+        value: Number { value, unit },
+        uncertainty,
+    }
+}
+
+/// All of the constants, in the order they're listed in the README.
+pub static CONSTANTS: Lazy<Vec<Constant>> = Lazy::new(|| {
+    vec![
+        // Exact
+        constant("@c", "speed of light in vacuum", 299_792_458.0, "m/s", 0.0),
+        constant("@h", "Planck constant", 6.626_070_15e-34, "J*s", 0.0),
+        constant(
+            "@hbar",
+            "reduced Planck constant",
+            6.626_070_15e-34 / (2.0 * std::f64::consts::PI),
+            "J*s",
+            0.0,
+        ),
+        constant("@e", "elementary charge", 1.602_176_634e-19, "A*s", 0.0),
+        constant("@k_B", "Boltzmann constant", 1.380_649e-23, "J/K", 0.0),
+        constant("@N_A", "Avogadro constant", 6.022_140_76e23, "mol^-1", 0.0),
+        constant(
+            "@R",
+            "molar gas constant",
+            8.314_462_618_153_24,
+            "J/(K*mol)",
+            0.0,
+        ),
+        constant(
+            "@F",
+            "Faraday constant",
+            96_485.332_123_310_02,
+            "A*s/mol",
+            0.0,
+        ),
+        constant(
+            "@sigma",
+            "Stefan-Boltzmann constant",
+            5.670_374_419e-8,
+            "W/(m^2*K^4)",
+            0.0,
+        ),
+        constant(
+            "@b",
+            "Wien wavelength displacement law constant",
+            2.897_771_955e-3,
+            "m*K",
+            0.0,
+        ),
+        constant(
+            "@Phi0",
+            "magnetic flux quantum",
+            2.067_833_848e-15,
+            "V*s",
+            0.0,
+        ),
+        constant("@G0", "conductance quantum", 7.748_091_729e-5, "A/V", 0.0),
+        constant("@R_K", "von Klitzing constant", 25_812.807_45, "ohm", 0.0),
+        constant(
+            "@g_n",
+            "standard acceleration of gravity",
+            9.806_65,
+            "m/s^2",
+            0.0,
+        ),
+        // Measured
+        constant(
+            "@G",
+            "Newtonian constant of gravitation",
+            6.674_30e-11,
+            "m^3/(kg*s^2)",
+            2.2e-5,
+        ),
+        constant(
+            "@alpha",
+            "fine-structure constant",
+            7.297_352_569_3e-3,
+            "",
+            1.5e-10,
+        ),
+        constant(
+            "@eps0",
+            "vacuum electric permittivity",
+            8.854_187_812_8e-12,
+            "F/m",
+            1.5e-10,
+        ),
+        constant(
+            "@mu0",
+            "vacuum magnetic permeability",
+            1.256_637_062_12e-6,
+            "N/A^2",
+            1.5e-10,
+        ),
+        constant(
+            "@Z0",
+            "characteristic impedance of vacuum",
+            376.730_313_668,
+            "ohm",
+            1.5e-10,
+        ),
+        constant("@m_e", "electron mass", 9.109_383_701_5e-31, "kg", 3.0e-10),
+        constant("@m_p", "proton mass", 1.672_621_923_69e-27, "kg", 3.1e-10),
+        constant("@m_n", "neutron mass", 1.674_927_498_04e-27, "kg", 5.7e-10),
+        constant(
+            "@m_u",
+            "atomic mass constant",
+            1.660_539_066_60e-27,
+            "kg",
+            3.0e-10,
+        ),
+        constant("@a0", "Bohr radius", 5.291_772_109_03e-11, "m", 1.5e-10),
+        constant(
+            "@r_e",
+            "classical electron radius",
+            2.817_940_326_2e-15,
+            "m",
+            4.5e-10,
+        ),
+        constant(
+            "@lambda_C",
+            "Compton wavelength",
+            2.426_310_238_67e-12,
+            "m",
+            3.0e-10,
+        ),
+        constant(
+            "@R_inf",
+            "Rydberg constant",
+            10_973_731.568_160,
+            "m^-1",
+            1.9e-12,
+        ),
+        constant(
+            "@mu_B",
+            "Bohr magneton",
+            9.274_010_078_3e-24,
+            "J/T",
+            3.0e-10,
+        ),
+        constant(
+            "@mu_N",
+            "nuclear magneton",
+            5.050_783_746_1e-27,
+            "J/T",
+            3.1e-10,
+        ),
+        constant(
+            "@sigma_e",
+            "Thomson cross section",
+            6.652_458_732_1e-29,
+            "m^2",
+            9.1e-10,
+        ),
+    ]
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{find, get, CONSTANTS};
+    use crate::units::{Number, Unit, JOULE, KELVIN, METER, SECOND};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn names_are_unique_and_prefixed() {
+        for (i, c) in CONSTANTS.iter().enumerate() {
+            assert!(c.name.starts_with(super::PREFIX), "{}", c.name);
+            assert!(
+                CONSTANTS[..i].iter().all(|d| d.name != c.name),
+                "{}",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn derived_constants_agree() {
+        let value = |name| get(name).unwrap().value.value;
+        assert_relative_eq!(
+            value("@R"),
+            value("@k_B") * value("@N_A"),
+            max_relative = 1e-15
+        );
+        assert_relative_eq!(
+            value("@F"),
+            value("@e") * value("@N_A"),
+            max_relative = 1e-15
+        );
+        assert_relative_eq!(
+            value("@hbar"),
+            value("@h") / (2.0 * std::f64::consts::PI),
+            max_relative = 1e-15
+        );
+        assert_relative_eq!(
+            value("@Z0"),
+            value("@mu0") * value("@c"),
+            max_relative = 1e-9
+        );
+        assert_relative_eq!(
+            value("@alpha"),
+            value("@e").powi(2) / (2.0 * value("@eps0") * value("@h") * value("@c")),
+            max_relative = 1e-9
+        );
+    }
+
+    #[test]
+    fn units() {
+        let k_b = &get("@k_B").unwrap().value;
+        let j_per_k = (&*JOULE / KELVIN).unwrap();
+        assert_relative_eq!(
+            k_b.unit
+                .as_ref()
+                .unwrap()
+                .convert(k_b.value, &j_per_k)
+                .unwrap(),
+            1.380_649e-23
+        );
+        assert_eq!(k_b.unit.as_ref().unwrap().to_string(), "J⋅K⁻¹");
+        assert!(get("@alpha").unwrap().value.unit.is_none());
+        assert!(get("@e").is_some());
+        assert!(get("e").is_none());
+    }
+
+    #[test]
+    fn finding() {
+        let c = Number::new(299_792.458).with_unit(Unit::new(&[METER], &[SECOND]).unwrap());
+        assert!(find(&c).is_none());
+        let km_per_s = (crate::units::KILOMETER / SECOND).unwrap();
+        assert_eq!(find(&c.with_unit(km_per_s)).unwrap().name, "@c");
+        assert_eq!(find(&get("@hbar").unwrap().value).unwrap().name, "@hbar");
+        assert_eq!(
+            get("@G").unwrap().to_string(),
+            "@G = 6.6743e-11 m³⋅kg⁻¹⋅s⁻² (Newtonian constant of gravitation; \
+             relative uncertainty 2.2e-5)"
+        );
+    }
+}
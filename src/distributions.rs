@@ -0,0 +1,333 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Probability density, cumulative distribution, and inverse cumulative
+//! distribution functions for common distributions.
+//!
+//! Parameters are assumed to be valid: scale parameters and degrees of freedom
+//! positive, probabilities between 0 and 1, and so on. Callers are
+//! responsible for checking them.
+
+use std::f64::consts::{PI, SQRT_2};
+
+use crate::special::{beta_inc, erfc, erfcinv, gamma_p, gamma_q, lgamma};
+
+/// The normal distribution's probability density at `x`.
+#[must_use]
+pub fn normal_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    let z = (x - mu) / sigma;
+    (-z * z / 2.0).exp() / (sigma * (2.0 * PI).sqrt())
+}
+
+/// The probability that a normally distributed variable is at most `x`.
+#[must_use]
+pub fn normal_cdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    erfc(-(x - mu) / (sigma * SQRT_2)) / 2.0
+}
+
+/// The `x` for which `normal_cdf(x, mu, sigma)` = `p`.
+#[must_use]
+pub fn normal_inv(p: f64, mu: f64, sigma: f64) -> f64 {
+    mu - sigma * SQRT_2 * erfcinv(2.0 * p)
+}
+
+/// Student's t distribution's probability density at `t`.
+#[must_use]
+pub fn t_pdf(t: f64, df: f64) -> f64 {
+    let ln = lgamma((df + 1.0) / 2.0) - lgamma(df / 2.0) - (df * PI).ln() / 2.0;
+    (ln - (df + 1.0) / 2.0 * (t * t / df).ln_1p()).exp()
+}
+
+/// The probability that a variable with Student's t distribution is at most
+/// `t`.
+#[must_use]
+pub fn t_cdf(t: f64, df: f64) -> f64 {
+    if t.is_infinite() {
+        return if t > 0.0 { 1.0 } else { 0.0 };
+    }
+    let tail = beta_inc(df / 2.0, 0.5, df / (df + t * t)) / 2.0;
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The `t` for which `t_cdf(t, df)` = `p`.
+///
+/// `t_cdf` can't tell `t` from zero when `t` is tiny, so this inverts the
+/// distribution of |t| instead, by whichever of its two incomplete beta forms
+/// keeps its precision: the probability outside ±|t| for tails, and the
+/// probability inside it otherwise.
+#[must_use]
+pub fn t_inv(p: f64, df: f64) -> f64 {
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    } else if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    let tail = p.min(1.0 - p);
+    let t = if tail >= 0.5 {
+        0.0
+    } else if tail < 0.25 {
+        let outside = |t: f64| beta_inc(df / 2.0, 0.5, df / (df + t * t)) / 2.0;
+        invert(-tail, 0.0, 1.0, |t| -outside(t))
+    } else {
+        let inside = |t: f64| beta_inc(0.5, df / 2.0, t * t / (df + t * t));
+        invert(1.0 - 2.0 * tail, 0.0, 1.0, inside)
+    };
+    if p < 0.5 {
+        -t
+    } else {
+        t
+    }
+}
+
+/// The chi-squared distribution's probability density at `x`.
+#[must_use]
+pub fn chi2_pdf(x: f64, k: f64) -> f64 {
+    if x < 0.0 {
+        return 0.0;
+    }
+    if x == 0.0 {
+        return match k.partial_cmp(&2.0) {
+            Some(std::cmp::Ordering::Less) => f64::INFINITY,
+            Some(std::cmp::Ordering::Equal) => 0.5,
+            _ => 0.0,
+        };
+    }
+    let half = k / 2.0;
+    ((half - 1.0) * x.ln() - x / 2.0 - half * 2f64.ln() - lgamma(half)).exp()
+}
+
+/// The probability that a chi-squared variable is at most `x`.
+#[must_use]
+pub fn chi2_cdf(x: f64, k: f64) -> f64 {
+    if x <= 0.0 {
+        0.0
+    } else {
+        gamma_p(k / 2.0, x / 2.0)
+    }
+}
+
+/// The `x` for which `chi2_cdf(x, k)` = `p`.
+#[must_use]
+pub fn chi2_inv(p: f64, k: f64) -> f64 {
+    if p <= 0.0 {
+        0.0
+    } else if p >= 1.0 {
+        f64::INFINITY
+    } else {
+        invert(p, 0.0, k.max(1.0), |x| chi2_cdf(x, k))
+    }
+}
+
+/// The exponential distribution's probability density at `x`, given its
+/// mean.
+#[must_use]
+pub fn exp_pdf(x: f64, mean: f64) -> f64 {
+    if x < 0.0 {
+        0.0
+    } else {
+        (-x / mean).exp() / mean
+    }
+}
+
+/// The probability that an exponentially distributed variable is at most
+/// `x`.
+#[must_use]
+pub fn exp_cdf(x: f64, mean: f64) -> f64 {
+    if x <= 0.0 {
+        0.0
+    } else {
+        -(-x / mean).exp_m1()
+    }
+}
+
+/// The `x` for which `exp_cdf(x, mean)` = `p`.
+#[must_use]
+pub fn exp_inv(p: f64, mean: f64) -> f64 {
+    -mean * (-p).ln_1p()
+}
+
+/// The continuous uniform distribution's probability density at `x`.
+#[must_use]
+pub fn uniform_pdf(x: f64, a: f64, b: f64) -> f64 {
+    if (a..=b).contains(&x) {
+        1.0 / (b - a)
+    } else {
+        0.0
+    }
+}
+
+/// The probability that a uniformly distributed variable is at most `x`.
+#[must_use]
+pub fn uniform_cdf(x: f64, a: f64, b: f64) -> f64 {
+    ((x - a) / (b - a)).clamp(0.0, 1.0)
+}
+
+/// The `x` for which `uniform_cdf(x, a, b)` = `p`.
+#[must_use]
+pub fn uniform_inv(p: f64, a: f64, b: f64) -> f64 {
+    a + p * (b - a)
+}
+
+/// The probability of exactly `k` successes in `n` trials that each succeed
+/// with probability `p`.
+#[must_use]
+pub fn binomial_pmf(k: i64, n: i64, p: f64) -> f64 {
+    if k < 0 || k > n {
+        return 0.0;
+    }
+    if p == 0.0 || p >= 1.0 {
+        let certain = if p == 0.0 { 0 } else { n };
+        return if k == certain { 1.0 } else { 0.0 };
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let (k, n) = (k as f64, n as f64);
+    let ln_choose = lgamma(n + 1.0) - lgamma(k + 1.0) - lgamma(n - k + 1.0);
+    (ln_choose + k * p.ln() + (n - k) * (-p).ln_1p()).exp()
+}
+
+/// The probability of at most `k` successes in `n` trials that each succeed
+/// with probability `p`.
+#[must_use]
+pub fn binomial_cdf(k: i64, n: i64, p: f64) -> f64 {
+    if k < 0 {
+        return 0.0;
+    }
+    if k >= n || p == 0.0 {
+        return 1.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let (k, n) = (k as f64, n as f64);
+    beta_inc(n - k, k + 1.0, 1.0 - p)
+}
+
+/// The probability of exactly `k` events when `lambda` are expected.
+#[must_use]
+pub fn poisson_pmf(k: i64, lambda: f64) -> f64 {
+    if k < 0 {
+        return 0.0;
+    }
+    if lambda == 0.0 {
+        return if k == 0 { 1.0 } else { 0.0 };
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let k = k as f64;
+    (k * lambda.ln() - lambda - lgamma(k + 1.0)).exp()
+}
+
+/// The probability of at most `k` events when `lambda` are expected.
+#[must_use]
+pub fn poisson_cdf(k: i64, lambda: f64) -> f64 {
+    if k < 0 {
+        return 0.0;
+    }
+    if lambda == 0.0 {
+        return 1.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let k = k as f64;
+    gamma_q(k + 1.0, lambda)
+}
+
+/// Finds the `x` for which the increasing function `cdf` equals `p` by
+/// widening [`lo`, `hi`] until it brackets `x` and then bisecting.
+fn invert(p: f64, mut lo: f64, mut hi: f64, cdf: impl Fn(f64) -> f64) -> f64 {
+    while cdf(lo) > p && lo.is_finite() {
+        lo *= 2.0;
+    }
+    while cdf(hi) < p && hi.is_finite() {
+        hi *= 2.0;
+    }
+    loop {
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            return mid;
+        }
+        if cdf(mid) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= expected.abs() * 1e-12,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn normal() {
+        assert_close(normal_pdf(1.0, 0.0, 1.0), 0.241_970_724_519_143_37);
+        assert_close(normal_cdf(-3.0, 0.0, 1.0), 0.001_349_898_031_630_094_6);
+        assert_close(normal_cdf(12.0, 10.0, 2.0), 0.841_344_746_068_542_9);
+        assert_close(normal_inv(0.975, 0.0, 1.0), 1.959_963_984_540_054);
+        assert_close(normal_inv(1e-10, 5.0, 2.0), -7.722_681_804_808_112);
+    }
+
+    #[test]
+    fn students_t() {
+        assert_close(t_pdf(0.5, 3.0), 0.313_180_911_008_828_6);
+        assert_close(t_cdf(2.0, 5.0), 0.949_030_260_585_070_9);
+        assert_close(t_cdf(-2.0, 5.0), 0.050_969_739_414_929_1);
+        assert_close(t_inv(0.975, 10.0), 2.228_138_851_986_274);
+        assert_close(t_inv(0.05, 1.0), -6.313_751_514_675_043);
+        assert_close(t_inv(0.5, 0.5), 0.0);
+        assert_close(t_inv(0.5, 3.0), 0.0);
+        assert_close(t_inv(0.6, 3.0), 0.276_670_662_332_69);
+        assert_close(t_inv(0.4, 3.0), -0.276_670_662_332_69);
+    }
+
+    #[test]
+    fn chi_squared() {
+        assert_close(chi2_pdf(3.0, 4.0), 0.167_347_620_111_322_37);
+        assert_close(chi2_cdf(3.0, 4.0), 0.442_174_599_628_925_9);
+        assert_close(chi2_inv(0.95, 1.0), 3.841_458_820_694_124);
+        assert_eq!(chi2_cdf(-1.0, 4.0), 0.0);
+    }
+
+    #[test]
+    fn exponential_and_uniform() {
+        assert_close(exp_pdf(1.0, 2.0), 0.303_265_329_856_316_7);
+        assert_close(exp_cdf(1.0, 2.0), 0.393_469_340_287_366_6);
+        assert_close(exp_inv(0.5, 2.0), 1.386_294_361_119_890_6);
+        assert_eq!(uniform_pdf(3.0, 2.0, 6.0), 0.25);
+        assert_eq!(uniform_pdf(7.0, 2.0, 6.0), 0.0);
+        assert_eq!(uniform_cdf(3.0, 2.0, 6.0), 0.25);
+        assert_eq!(uniform_inv(0.75, 2.0, 6.0), 5.0);
+    }
+
+    #[test]
+    fn discrete() {
+        assert_close(binomial_pmf(3, 10, 0.5), 0.117_187_5);
+        assert_close(binomial_cdf(3, 10, 0.5), 0.171_875);
+        assert_eq!(binomial_pmf(0, 10, 0.0), 1.0);
+        assert_eq!(binomial_cdf(10, 10, 0.3), 1.0);
+        assert_close(poisson_pmf(2, 3.0), 0.224_041_807_655_387_74);
+        assert_close(poisson_cdf(2, 3.0), 0.423_190_081_126_843_5);
+        assert_eq!(poisson_pmf(-1, 3.0), 0.0);
+    }
+}
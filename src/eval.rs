@@ -2,11 +2,25 @@
 
 use std::string::ToString;
 
-use crate::{builtins, integer, stack::Stack};
+use crate::{builtins, constants, integer, random::Rng, settings::Settings, stack::Stack, units};
 
 /// An evaluation context.
 pub struct Context {
     pub stack: Stack,
+    pub settings: Settings,
+    /// Lines of text written by builtins for the user to read, such as the
+    /// output of `dim`. The REPL prints and clears these after each line of
+    /// input.
+    pub output: Vec<String>,
+    /// Exchange rates loaded with `loadrates`, if any. Currency codes are
+    /// only recognized as units once rates are loaded.
+    pub rates: Option<units::currency::Rates>,
+    /// The generator used by `rand` and friends. It starts with an
+    /// unpredictable seed; `seed` makes its sequence reproducible.
+    pub rng: Rng,
+    /// The height of the stack at each `[` that hasn't been closed with `]`,
+    /// and the lowest height it has had since.
+    pub marks: Vec<(usize, usize)>,
     builtins: builtins::Table,
 }
 
@@ -23,15 +37,22 @@ pub enum Status {
 pub enum Error {
     Builtins(builtins::Error),
     UnknownWord,
+    /// A `{` was found without a matching `}`, or vice versa.
+    UnmatchedBrace,
 }
 
 impl Context {
-    /// Creates a new evaluation context with an empty stack and all of the
-    /// builtins.
+    /// Creates a new evaluation context with an empty stack, default settings,
+    /// a randomly seeded generator, and all of the builtins.
     #[must_use]
     pub fn new() -> Context {
         Context {
             stack: Stack::new(),
+            settings: Settings::new(),
+            output: Vec::new(),
+            rates: None,
+            rng: Rng::from_entropy(),
+            marks: Vec::new(),
             builtins: builtins::table(),
         }
     }
@@ -43,19 +64,37 @@ impl Context {
             match token {
                 Token::Float(n) => self.eval_float(n),
                 Token::Integer(b) => self.eval_integer(b),
+                Token::Number(x) => self.eval_number(x),
+                Token::Block(b) => self.stack.pushb(b),
                 Token::Word(w) => {
                     if w == "exit" || w == "q" {
                         return Status::Halt;
                     }
+                    if w == "{" || w == "}" {
+                        return Status::Err {
+                            error: Error::UnmatchedBrace,
+                            word: w,
+                        };
+                    }
                     if let Err(e) = self.eval_word(w.as_str()) {
                         return Status::Err { error: e, word: w };
                     }
                 }
-            };
+            }
+            self.track_marks();
         }
         Status::Ok
     }
 
+    /// Lowers the lowest height recorded for each open `[` to the lowest
+    /// height the stack has had since this was last called.
+    pub fn track_marks(&mut self) {
+        let low = self.stack.take_low_water();
+        for (_, lowest) in &mut self.marks {
+            *lowest = (*lowest).min(low);
+        }
+    }
+
     /// Evaluates an integer by pushing it onto the stack.
     fn eval_integer(&mut self, x: integer::Integer) {
         self.stack.pushi(x);
@@ -66,28 +105,54 @@ impl Context {
         self.stack.pushx(x);
     }
 
+    /// Evaluates a number-with-units token by pushing it onto the stack.
+    fn eval_number(&mut self, x: units::Number) {
+        self.stack.pushf(x);
+    }
+
     /// Evaluates a word token by looking for a builtin with the name contained
-    /// in the token and executing it.
+    /// in the token and executing it. If there is no such builtin, the word is
+    /// parsed as a unit expression like `m/s^2` and the unit is applied as if
+    /// it were a builtin.
     ///
     /// # Errors
     /// Returns an error if:
-    /// - no builtin named `w` exists; or,
+    /// - no builtin named `w` exists and `w` isn't a unit expression;
+    /// - `w` is a unit expression that would produce a nonsensical unit; or,
     /// - the builtin returns an error.
     fn eval_word(&mut self, w: &str) -> Result<(), Error> {
-        if let Some(f) = self.builtins.get(w) {
-            if let Err(e) = f(&mut self.stack) {
+        if let Some(&f) = self.builtins.get(w) {
+            if let Err(e) = f(self) {
                 Err(Error::Builtins(e))
             } else {
                 Ok(())
             }
+        } else if w.starts_with(constants::PREFIX) {
+            let c = constants::get(w).ok_or(Error::UnknownWord)?;
+            builtins::builtin_constant(c, self);
+            Ok(())
         } else {
-            Err(Error::UnknownWord)
+            let currencies = self.rates.as_ref().map_or(&[][..], |r| r.units());
+            match units::parse_with(w, currencies) {
+                Ok(u) => {
+                    builtins::builtin_unit(&u, &mut self.stack);
+                    Ok(())
+                }
+                Err(units::Error::UnknownUnit(_)) => Err(Error::UnknownWord),
+                Err(e) => Err(Error::Builtins(builtins::Error::Units(e))),
+            }
         }
     }
 
-    /// Returns the names of all the builtins, in no particular order.
+    /// Returns the names of all the builtins, constants, and unit symbols, in
+    /// no particular order.
     pub fn builtin_names(&self) -> Vec<String> {
-        self.builtins.keys().map(ToString::to_string).collect()
+        self.builtins
+            .keys()
+            .chain(constants::CONSTANTS.iter().map(|c| &c.name))
+            .chain(units::SYMBOLS.keys())
+            .map(ToString::to_string)
+            .collect()
     }
 }
 
@@ -97,26 +162,80 @@ impl Default for Context {
     }
 }
 
+/// A quoted block of words, like `{ dup * 2 - }`. Evaluating a block pushes
+/// it onto the stack without evaluating its words; builtins such as `solve`
+/// evaluate them later.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    /// The words between the braces, separated by spaces.
+    pub words: String,
+}
+
+impl std::fmt::Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if self.words.is_empty() {
+            write!(f, "{{ }}")
+        } else {
+            write!(f, "{{ {} }}", self.words)
+        }
+    }
+}
+
 /// A token parsed from user input.
 enum Token {
     Float(f64),
     Integer(integer::Integer),
+    /// A number with units, like `12:34:56` or `41°24'12"`
+    Number(units::Number),
+    Block(Block),
     Word(String),
 }
 
 impl Token {
-    /// Splits a string into a sequence of tokens.
+    /// Splits a string into a sequence of tokens. A `{` without a matching
+    /// `}`, and a `}` without a matching `{`, are left as words.
     fn split(s: &str) -> Vec<Token> {
         let mut tokens: Vec<Token> = Vec::new();
-        for word in s.split_ascii_whitespace() {
+        let mut words = s.split_ascii_whitespace();
+        while let Some(word) = words.next() {
+            if word == "{" {
+                if let Some(block) = Token::block(words.clone()) {
+                    // Skip the block's words and its closing brace.
+                    words.nth(block.len());
+                    tokens.push(Token::Block(Block {
+                        words: block.join(" "),
+                    }));
+                    continue;
+                }
+            }
             if let Some(x) = integer::Integer::parse(word) {
                 tokens.push(Token::Integer(x));
             } else if let Ok(x) = word.replace(',', "").parse::<f64>() {
                 tokens.push(Token::Float(x));
+            } else if let Some(x) = units::mixed::parse(word) {
+                tokens.push(Token::Number(x));
             } else {
                 tokens.push(Token::Word(String::from(word)));
             }
         }
         tokens
     }
+
+    /// Returns the words of a block up to, but not including, the `}` that
+    /// closes it, given the words after its `{`. Returns `None` if the block
+    /// isn't closed.
+    fn block<'a>(words: impl Iterator<Item = &'a str>) -> Option<Vec<&'a str>> {
+        let mut depth = 0;
+        let mut block = Vec::new();
+        for word in words {
+            match word {
+                "{" => depth += 1,
+                "}" if depth == 0 => return Some(block),
+                "}" => depth -= 1,
+                _ => {}
+            }
+            block.push(word);
+        }
+        None
+    }
 }
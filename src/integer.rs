@@ -126,6 +126,19 @@ impl Integer {
         }
     }
 
+    /// Returns the absolute value of this integer, or `None` if it's too
+    /// large to represent.
+    #[must_use]
+    pub fn abs(&self) -> Option<Integer> {
+        Some(Integer::new(self.value.checked_abs()?, self.repr))
+    }
+
+    /// Returns -1, 0, or 1 according to the sign of this integer.
+    #[must_use]
+    pub fn signum(&self) -> Integer {
+        Integer::new(self.value.signum(), self.repr)
+    }
+
     /// Converts this integer into a dimensionless floating-point number.
     #[must_use]
     pub fn as_units_number(&self) -> units::Number {
@@ -168,6 +181,16 @@ impl std::fmt::Display for Integer {
     }
 }
 
+impl std::ops::Neg for &Integer {
+    type Output = Option<Integer>;
+
+    /// Negates this integer. Returns `None` if the result is too large to
+    /// represent.
+    fn neg(self) -> Option<Integer> {
+        Some(Integer::new(self.value.checked_neg()?, self.repr))
+    }
+}
+
 impl std::ops::Add<&Integer> for &Integer {
     type Output = Integer;
 
@@ -272,6 +295,15 @@ mod tests {
         assert_eq!(b.to_string(), "12,345,678");
     }
 
+    #[test]
+    fn overflow() {
+        let min = Integer::dec(i64::MIN);
+        assert_eq!(min.abs(), None);
+        assert_eq!(-&min, None);
+        assert_eq!(Integer::dec(-5).abs(), Some(Integer::dec(5)));
+        assert_eq!(-&Integer::dec(i64::MAX), Some(Integer::dec(-i64::MAX)));
+    }
+
     #[test]
     fn hex_display() {
         let b = Integer::hex(0xbeefcafeface);
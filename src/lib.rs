@@ -16,7 +16,17 @@
 // calc. If not, see <https://www.gnu.org/licenses/>.
 
 pub mod builtins;
+pub mod constants;
+pub mod distributions;
 pub mod eval;
 pub mod integer;
+pub mod matrix;
+pub mod number_theory;
+pub mod polynomial;
+pub mod random;
+pub mod settings;
+pub mod solver;
+pub mod special;
 pub mod stack;
 pub mod units;
+pub mod vector;
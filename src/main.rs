@@ -18,7 +18,7 @@
 use rustyline as rl;
 use rustyline::error::ReadlineError;
 
-use calc::{builtins, eval, stack, stack::Stack, units};
+use calc::{builtins, eval, settings::Settings, stack, stack::Stack, units};
 
 /// Autocompletion helper.
 struct Completer {
@@ -75,44 +75,130 @@ fn print_error(error: &eval::Error, word: &String) {
                 stack::Error::NotDimensionless => println!("number must be dimensionless"),
             },
             builtins::Error::Units(e) => match e {
-                units::Error::IncommensurableUnits(_, _) => {
-                    println!("incommensurable units");
+                units::Error::IncommensurableUnits(a, b) => {
+                    println!(
+                        "incommensurable units {} and {}",
+                        describe_unit(a.as_deref()),
+                        describe_unit(b.as_deref())
+                    );
                 }
                 units::Error::UninvertableUnits(u) => println!("{u} can't be inverted"),
                 units::Error::NonzeroZeroPoint(b) => {
                     println!("operation would place {b} in a nonsensical position");
                 }
+                units::Error::TemperaturesAdded(a, b) => {
+                    println!(
+                        "can't add temperatures {a} and {b}; add a difference in degrees \
+                         instead"
+                    );
+                }
+                units::Error::TemperatureSubtracted(a, b) => {
+                    println!("can't subtract temperature {b} from difference {a}");
+                }
                 units::Error::ExponentHasUnits => println!("exponent has units"),
-                units::Error::ExponentNotAnInteger => {
-                    println!("exponent must be an integer when base has units");
+                units::Error::ExponentNotRational => {
+                    println!("exponent must be a simple fraction when base has units");
+                }
+                units::Error::ExponentTooLarge => {
+                    println!("exponent is too large for the base's units");
                 }
+                units::Error::ExponentIsZero => println!("unit exponent must be non-zero"),
                 units::Error::DegreeHasUnits => println!("degree has units"),
-                units::Error::DegreeNotAnInteger => {
-                    println!("degree must be an integer when radicand has units");
+                units::Error::DegreeNotRational => {
+                    println!("degree must be a simple fraction when radicand has units");
                 }
-                units::Error::UnitNotDivisible => {
-                    println!("radicand's units must be evenly divisible by the degree");
+                units::Error::DegreeIsZero => {
+                    println!("degree must be non-zero when radicand has units");
                 }
+                units::Error::UnknownUnit(s) => println!("unknown unit {s}"),
+                units::Error::TooManyQuantities(n) => println!(
+                    "too many quantities; {n} aren't determined by the others, and at most \
+                     {} can be",
+                    units::analysis::MAX_FREE_QUANTITIES
+                ),
             },
             builtins::Error::MissingUnit => println!("missing unit"),
             builtins::Error::NotDimensionless => println!("number must be dimensionless"),
             builtins::Error::NotNonNegative => println!("number must be non-negative"),
             builtins::Error::NotWhole => println!("number must be whole"),
+            builtins::Error::NotPositive => println!("number must be positive"),
+            builtins::Error::BoundsReversed => {
+                println!("lower bound is greater than upper bound");
+            }
+            builtins::Error::NotAProbability => {
+                println!("probability must be between 0 and 1");
+            }
+            builtins::Error::Overflow => println!("number is too large"),
+            builtins::Error::NotInvertible => println!("number has no modular inverse"),
+            builtins::Error::LengthMismatch => println!("vectors have different lengths"),
+            builtins::Error::NotThreeDimensional => println!("vectors must have three elements"),
+            builtins::Error::ZeroVector => println!("vector is zero and has no direction"),
+            builtins::Error::UnmatchedBracket => println!("no matching ["),
+            builtins::Error::MixedBracket => {
+                println!("can't mix vectors and other items in one [ ]");
+            }
+            builtins::Error::ShapeMismatch => println!("shapes don't match"),
+            builtins::Error::NotSquare => println!("matrix must be square"),
+            builtins::Error::Singular => println!("matrix is singular"),
+            builtins::Error::IndexOutOfRange => println!("index is out of range"),
+            builtins::Error::ZeroPolynomial => println!("polynomial is zero"),
+            builtins::Error::Block { word, error } => print_error(error, word),
+            builtins::Error::NoResult => println!("block left nothing on the stack"),
+            builtins::Error::NoConvergence => println!("no root found near the guess"),
+            builtins::Error::NotAConstant => println!("not a known constant"),
+            builtins::Error::NoCombination => {
+                println!("no combination of the numbers has those units");
+            }
+            builtins::Error::Rates(e) => match e {
+                units::currency::Error::NoHomeDirectory => {
+                    println!("can't find home directory for rates file");
+                }
+                units::currency::Error::Io(kind) => println!("can't read rates file: {kind}"),
+                units::currency::Error::Malformed(line) => println!("rates file: bad line {line}"),
+                units::currency::Error::MissingKey(key) => println!("rates file: missing {key}"),
+                units::currency::Error::InvalidCode(code) => {
+                    println!("rates file: invalid currency code {code}");
+                }
+            },
         },
         eval::Error::UnknownWord => println!("unknown word"),
+        eval::Error::UnmatchedBrace => println!("unmatched brace"),
+    }
+}
+
+/// Helper for `print_error`. Describes a unit and its dimension, e.g.
+/// "N (L¹ M¹ T⁻²)". A missing unit is dimensionless.
+fn describe_unit(u: Option<&units::Unit>) -> String {
+    match u {
+        Some(u) => format!("{u} ({})", u.dimension()),
+        None => String::from("(dimensionless)"),
     }
 }
 
 /// Returns a REPL prompt containing the elements in the stack, e.g. "(1 2) ".
+/// Numbers are displayed according to `settings`. Matrices span several
+/// lines, so the prompt may too.
 #[must_use]
-pub fn prompt(stack: &Stack) -> String {
+pub fn prompt(stack: &Stack, settings: &Settings) -> String {
     let mut prompt = String::from("(");
 
     for item in stack {
         match item {
-            stack::Item::Float(n) => prompt.push_str(format!("{n}").as_str()),
+            stack::Item::Float(n) => {
+                prompt.push_str(format!("{}", settings.displayed(n)).as_str());
+            }
             stack::Item::Integer(b) => prompt.push_str(format!("{b}").as_str()),
             stack::Item::Unit(u) => prompt.push_str(format!("{u}").as_str()),
+            stack::Item::Vector(v) => prompt.push_str(format!("{v}").as_str()),
+            stack::Item::Matrix(m) => {
+                // Start each matrix on a line of its own.
+                if prompt.ends_with(' ') {
+                    prompt.pop();
+                    prompt.push('\n');
+                }
+                prompt.push_str(format!("{m}").as_str());
+            }
+            stack::Item::Block(b) => prompt.push_str(format!("{b}").as_str()),
         };
         prompt.push(' ');
     }
@@ -155,8 +241,14 @@ fn main() -> Result<(), ReadlineError> {
 
     // Run the REPL.
     loop {
-        // Read
-        let input = match rl.readline(prompt(&ctx.stack).as_str()) {
+        // Read. Readline only redraws the last line of a prompt, so print the
+        // lines before it, which come from matrices, separately.
+        let prompt = prompt(&ctx.stack, &ctx.settings);
+        let (above, prompt) = prompt.rsplit_once('\n').unwrap_or(("", &prompt));
+        if !above.is_empty() {
+            println!("{above}");
+        }
+        let input = match rl.readline(prompt) {
             Ok(s) => s,
             Err(ReadlineError::Eof) => return Ok(()), // normal end of input; exit Ok
             Err(e) => return Err(e),
@@ -165,7 +257,11 @@ fn main() -> Result<(), ReadlineError> {
         history_path.as_ref().map(|path| rl.append_history(&path));
 
         // Evaluate
-        match ctx.eval(input.as_str()) {
+        let status = ctx.eval(input.as_str());
+        for line in ctx.output.drain(..) {
+            println!("{line}");
+        }
+        match status {
             eval::Status::Ok => { /* do nothing */ }
             eval::Status::Err { error, word } => print_error(&error, &word),
             eval::Status::Halt => return Ok(()),
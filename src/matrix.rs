@@ -0,0 +1,446 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Matrices of numbers that share a unit, and linear algebra on them.
+//!
+//! Like vectors, matrices assume that their operands have compatible shapes;
+//! callers are responsible for checking. Products, determinants, inverses,
+//! and solutions find the units of their results by carrying out the same
+//! operation on numbers with a value of one.
+
+use crate::units::{Error, Number, Unit};
+use crate::vector::Vector;
+
+/// A matrix of numbers in a single unit, stored in row-major order.
+#[derive(Clone, Debug)]
+pub struct Matrix {
+    pub rows: usize,
+    pub cols: usize,
+    pub values: Vec<f64>,
+    pub unit: Option<Unit>,
+}
+
+impl Matrix {
+    /// Returns a dimensionless `Matrix`. `values` must have `rows` × `cols`
+    /// elements.
+    #[must_use]
+    pub fn new(rows: usize, cols: usize, values: Vec<f64>) -> Matrix {
+        Matrix {
+            rows,
+            cols,
+            values,
+            unit: None,
+        }
+    }
+
+    /// Returns the `n` × `n` identity matrix.
+    #[must_use]
+    pub fn identity(n: usize) -> Matrix {
+        let mut values = vec![0.0; n * n];
+        for i in 0..n {
+            values[i * n + i] = 1.0;
+        }
+        Matrix::new(n, n, values)
+    }
+
+    /// Makes a matrix out of row vectors, which must have the same length,
+    /// converting them to the units of the first row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows have incommensurable units.
+    pub fn from_rows(rows: &[Vector]) -> Result<Matrix, Error> {
+        let unit = rows.first().and_then(|r| r.unit.clone());
+        let mut values = Vec::new();
+        for row in rows {
+            for x in row.numbers() {
+                values.push(x.value_in(unit.as_ref())?);
+            }
+        }
+        Ok(Matrix {
+            rows: rows.len(),
+            cols: rows.first().map_or(0, Vector::len),
+            values,
+            unit,
+        })
+    }
+
+    /// Returns true if the matrix has as many rows as columns.
+    #[must_use]
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Returns the element in row `i` and column `j`, counting from zero.
+    #[must_use]
+    pub fn get(&self, i: usize, j: usize) -> Number {
+        Number {
+            value: self.values[i * self.cols + j],
+            unit: self.unit.clone(),
+        }
+    }
+
+    /// Returns the elements of the matrix as a vector, row by row.
+    #[must_use]
+    pub fn elements(&self) -> Vector {
+        Vector {
+            values: self.values.clone(),
+            unit: self.unit.clone(),
+        }
+    }
+
+    /// Returns a matrix with this matrix's shape and the elements of `v`.
+    fn reshaped(&self, v: Vector) -> Matrix {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            values: v.values,
+            unit: v.unit,
+        }
+    }
+
+    /// Returns a matrix with the same values as this one but different
+    /// units. No unit conversion is performed.
+    #[must_use]
+    pub fn with_unit(&self, unit: Unit) -> Matrix {
+        self.reshaped(self.elements().with_unit(unit))
+    }
+
+    /// Applies `f` to every element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `f` does.
+    pub fn map(&self, f: impl Fn(&Number) -> Result<Number, Error>) -> Result<Matrix, Error> {
+        Ok(self.reshaped(self.elements().map(f)?))
+    }
+
+    /// Applies `f` to every pair of corresponding elements of this matrix and
+    /// `other`, which must have the same shape.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `f` does.
+    pub fn zip_with(
+        &self,
+        other: &Matrix,
+        f: impl Fn(&Number, &Number) -> Result<Number, Error>,
+    ) -> Result<Matrix, Error> {
+        Ok(self.reshaped(self.elements().zip_with(&other.elements(), f)?))
+    }
+
+    /// Returns the transpose of this matrix.
+    #[must_use]
+    pub fn transpose(&self) -> Matrix {
+        let mut values = Vec::with_capacity(self.values.len());
+        for j in 0..self.cols {
+            for i in 0..self.rows {
+                values.push(self.values[i * self.cols + j]);
+            }
+        }
+        Matrix {
+            rows: self.cols,
+            cols: self.rows,
+            values,
+            unit: self.unit.clone(),
+        }
+    }
+
+    /// Finds the matrix product of this matrix and `other`, which must have
+    /// as many rows as this matrix has columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the units can't be multiplied.
+    pub fn mul(&self, other: &Matrix) -> Result<Matrix, Error> {
+        let scale = (&self.one() * &other.one())?;
+        let mut values = vec![0.0; self.rows * other.cols];
+        for i in 0..self.rows {
+            for j in 0..other.cols {
+                values[i * other.cols + j] = scale.value
+                    * (0..self.cols)
+                        .map(|k| self.values[i * self.cols + k] * other.values[k * other.cols + j])
+                        .sum::<f64>();
+            }
+        }
+        Ok(Matrix {
+            rows: self.rows,
+            cols: other.cols,
+            values,
+            unit: scale.unit,
+        })
+    }
+
+    /// Finds the product of this matrix and the column vector `v`, which must
+    /// have as many elements as this matrix has columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the units can't be multiplied.
+    pub fn mul_vector(&self, v: &Vector) -> Result<Vector, Error> {
+        let column = Matrix {
+            rows: v.len(),
+            cols: 1,
+            values: v.values.clone(),
+            unit: v.unit.clone(),
+        };
+        Ok(self.mul(&column)?.elements())
+    }
+
+    /// Finds the determinant of this matrix, which must be square.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the units can't be raised to the matrix's order.
+    pub fn det(&self) -> Result<Number, Error> {
+        #[allow(clippy::cast_precision_loss)]
+        let scale = self.one().pow(&Number::new(self.rows as f64))?;
+        let value = match lu(self) {
+            Some(lu) => lu.sign * (0..self.rows).map(|i| lu.get(i, i)).product::<f64>(),
+            None => 0.0,
+        };
+        Ok(Number {
+            value: scale.value * value,
+            unit: scale.unit,
+        })
+    }
+
+    /// Finds the inverse of this matrix, which must be square. Returns `None`
+    /// if the matrix is singular.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the units can't be inverted.
+    pub fn inverse(&self) -> Result<Option<Matrix>, Error> {
+        let identity = Matrix::identity(self.rows);
+        self.solve(&identity)
+    }
+
+    /// Solves AX = B for X, where A is this matrix, which must be square, and
+    /// B has as many rows as A. Returns `None` if A is singular.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the units can't be divided.
+    pub fn solve(&self, b: &Matrix) -> Result<Option<Matrix>, Error> {
+        let scale = (&b.one() / &self.one())?;
+        let Some(lu) = lu(self) else {
+            return Ok(None);
+        };
+        let n = self.rows;
+        let mut values = vec![0.0; n * b.cols];
+        for c in 0..b.cols {
+            // Forward substitution with L, whose diagonal is all ones, then
+            // back substitution with U.
+            let mut y: Vec<f64> = lu
+                .pivots
+                .iter()
+                .map(|&p| b.values[p * b.cols + c])
+                .collect();
+            for i in 0..n {
+                for k in 0..i {
+                    y[i] -= lu.get(i, k) * y[k];
+                }
+            }
+            for i in (0..n).rev() {
+                for k in i + 1..n {
+                    y[i] -= lu.get(i, k) * y[k];
+                }
+                y[i] /= lu.get(i, i);
+            }
+            for (i, yi) in y.into_iter().enumerate() {
+                values[i * b.cols + c] = scale.value * yi;
+            }
+        }
+        Ok(Some(Matrix {
+            rows: n,
+            cols: b.cols,
+            values,
+            unit: scale.unit,
+        }))
+    }
+
+    /// Returns a number with a value of one in this matrix's units.
+    fn one(&self) -> Number {
+        Number {
+            value: 1.0,
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+/// An LU decomposition with partial pivoting: the rows of a matrix, reordered
+/// by `pivots`, equal LU. L and U share `values`; L's diagonal of ones isn't
+/// stored.
+struct Lu {
+    n: usize,
+    values: Vec<f64>,
+    pivots: Vec<usize>,
+    /// The determinant of the permutation, 1 or -1.
+    sign: f64,
+}
+
+impl Lu {
+    fn get(&self, i: usize, j: usize) -> f64 {
+        self.values[i * self.n + j]
+    }
+}
+
+/// Decomposes the square matrix `m`. Returns `None` if it's singular.
+fn lu(m: &Matrix) -> Option<Lu> {
+    let n = m.rows;
+    let mut a = m.values.clone();
+    let mut pivots: Vec<usize> = (0..n).collect();
+    let mut sign = 1.0;
+    // Pivots smaller than this are rounding error in a singular matrix.
+    let largest = a.iter().fold(0.0, |max: f64, x| max.max(x.abs()));
+    #[allow(clippy::cast_precision_loss)]
+    let tiny = largest * n as f64 * f64::EPSILON;
+
+    for k in 0..n {
+        let p = (k..n).max_by(|&i, &j| a[i * n + k].abs().total_cmp(&a[j * n + k].abs()))?;
+        if a[p * n + k].abs() <= tiny {
+            return None;
+        }
+        if p != k {
+            for j in 0..n {
+                a.swap(p * n + j, k * n + j);
+            }
+            pivots.swap(p, k);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            let factor = a[i * n + k] / a[k * n + k];
+            a[i * n + k] = factor;
+            for j in k + 1..n {
+                a[i * n + j] -= factor * a[k * n + j];
+            }
+        }
+    }
+    Some(Lu {
+        n,
+        values: a,
+        pivots,
+        sign,
+    })
+}
+
+impl std::fmt::Display for Matrix {
+    /// Displays a matrix with one row per line and the columns aligned, e.g.
+    /// `[[1 2]` over ` [3 4]] m`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let cells: Vec<String> = self
+            .values
+            .iter()
+            .map(|&x| Number::new(x).to_string())
+            .collect();
+        let widths: Vec<usize> = (0..self.cols)
+            .map(|j| {
+                (0..self.rows)
+                    .map(|i| cells[i * self.cols + j].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        write!(f, "[")?;
+        for i in 0..self.rows {
+            if i > 0 {
+                write!(f, "\n ")?;
+            }
+            let row: Vec<String> = (0..self.cols)
+                .map(|j| format!("{:>1$}", cells[i * self.cols + j], widths[j]))
+                .collect();
+            write!(f, "[{}]", row.join(" "))?;
+        }
+        write!(f, "]")?;
+        if let Some(u) = &self.unit {
+            write!(f, " {u}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{METER, SECOND};
+    use approx::assert_relative_eq;
+
+    fn square(values: &[f64]) -> Matrix {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let n = (values.len() as f64).sqrt() as usize;
+        Matrix::new(n, n, values.to_vec())
+    }
+
+    #[test]
+    fn transpose_and_product() {
+        let a = Matrix::new(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let t = a.transpose();
+        assert_eq!((t.rows, t.cols), (3, 2));
+        assert_eq!(t.values, [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        let p = a.mul(&t).unwrap();
+        assert_eq!((p.rows, p.cols), (2, 2));
+        assert_eq!(p.values, [14.0, 32.0, 32.0, 77.0]);
+    }
+
+    #[test]
+    fn determinants() {
+        assert_relative_eq!(square(&[1.0, 2.0, 3.0, 4.0]).det().unwrap().value, -2.0);
+        assert_relative_eq!(
+            square(&[2.0, 0.0, 1.0, 1.0, 3.0, 2.0, 1.0, 1.0, 2.0])
+                .det()
+                .unwrap()
+                .value,
+            6.0,
+            epsilon = 1e-14
+        );
+        assert_eq!(square(&[1.0, 2.0, 2.0, 4.0]).det().unwrap().value, 0.0);
+
+        let mut m = square(&[1.0, 2.0, 3.0, 4.0]);
+        m.unit = Some(METER.as_unit());
+        let d = m.det().unwrap();
+        assert_relative_eq!(d.value, -2.0);
+        assert_eq!(d.unit.unwrap().to_string(), "m²");
+    }
+
+    #[test]
+    fn inverses() {
+        let m = square(&[4.0, 7.0, 2.0, 6.0]);
+        let inv = m.inverse().unwrap().unwrap();
+        assert_relative_eq!(inv.values[..], [0.6, -0.7, -0.2, 0.4][..], epsilon = 1e-15);
+        assert!(square(&[1.0, 2.0, 2.0, 4.0]).inverse().unwrap().is_none());
+    }
+
+    #[test]
+    fn solutions() {
+        let mut a = square(&[2.0, 1.0, 1.0, 3.0]);
+        a.unit = Some(SECOND.as_unit());
+        let mut b = Matrix::new(2, 1, vec![3.0, 5.0]);
+        b.unit = Some(METER.as_unit());
+        let x = a.solve(&b).unwrap().unwrap();
+        assert_relative_eq!(x.values[..], [0.8, 1.4][..], epsilon = 1e-15);
+        assert_eq!(x.unit.unwrap().to_string(), "m⋅s⁻¹");
+    }
+
+    #[test]
+    fn display() {
+        let mut m = Matrix::new(2, 2, vec![1.0, 20.0, 300.0, 4.0]);
+        assert_eq!(m.to_string(), "[[  1 20]\n [300  4]]");
+        m.unit = Some(METER.as_unit());
+        assert_eq!(m.to_string(), "[[  1 20]\n [300  4]] m");
+    }
+}
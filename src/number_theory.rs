@@ -0,0 +1,318 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Exact combinatorics and number theory on 64-bit integers.
+//!
+//! Functions that can overflow return `None` when the result doesn't fit in
+//! an `i64`.
+
+/// The primes used for trial division and as Miller-Rabin witnesses. Testing
+/// these witnesses is deterministic for every 64-bit integer.
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Returns `n!`, or `None` if it overflows. `n` must be non-negative.
+#[must_use]
+pub fn factorial(n: i64) -> Option<i64> {
+    (2..=n).try_fold(1i64, i64::checked_mul)
+}
+
+/// Returns the number of ways to choose `r` of `n` items without regard to
+/// order, or `None` if it overflows. `n` and `r` must be non-negative.
+#[must_use]
+pub fn choose(n: i64, r: i64) -> Option<i64> {
+    if r > n {
+        return Some(0);
+    }
+    let r = r.min(n - r);
+    let mut c: i128 = 1;
+    for i in 0..r {
+        // c * (n - i) is always divisible by i + 1 because c * (n - i) / (i + 1)
+        // is itself a binomial coefficient.
+        c = c * i128::from(n - i) / i128::from(i + 1);
+        if c > i128::from(i64::MAX) {
+            return None;
+        }
+    }
+    i64::try_from(c).ok()
+}
+
+/// Returns the number of ways to choose `r` of `n` items in order, or `None`
+/// if it overflows. `n` and `r` must be non-negative.
+#[must_use]
+pub fn permutations(n: i64, r: i64) -> Option<i64> {
+    if r > n {
+        return Some(0);
+    }
+    (n - r + 1..=n).try_fold(1i64, i64::checked_mul)
+}
+
+/// Returns the greatest common divisor of `a` and `b`, which is never
+/// negative, or `None` if it overflows.
+#[must_use]
+pub fn gcd(a: i64, b: i64) -> Option<i64> {
+    i64::try_from(gcd_u64(a.unsigned_abs(), b.unsigned_abs())).ok()
+}
+
+/// Returns the least common multiple of `a` and `b`, which is never negative,
+/// or `None` if it overflows.
+#[must_use]
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+    (a / gcd_u64(a, b))
+        .checked_mul(b)
+        .and_then(|m| i64::try_from(m).ok())
+}
+
+/// Returns true if `n` is prime.
+#[must_use]
+pub fn is_prime(n: i64) -> bool {
+    u64::try_from(n).is_ok_and(is_prime_u64)
+}
+
+/// Returns the smallest prime greater than `n`, or `None` if it overflows.
+#[must_use]
+pub fn next_prime(n: i64) -> Option<i64> {
+    let mut p = n.max(1);
+    loop {
+        p = p.checked_add(1)?;
+        if is_prime(p) {
+            return Some(p);
+        }
+    }
+}
+
+/// Returns the prime factors of `n` in ascending order, with repeated
+/// factors repeated. `n` must be positive; the factorization of 1 is empty.
+#[must_use]
+pub fn factor(n: i64) -> Vec<i64> {
+    let Ok(mut n) = u64::try_from(n) else {
+        return Vec::new();
+    };
+    let mut factors = Vec::new();
+    for p in SMALL_PRIMES {
+        while n.is_multiple_of(p) {
+            factors.push(p);
+            n /= p;
+        }
+    }
+    if n > 1 {
+        factor_into(n, &mut factors);
+    }
+    factors.sort_unstable();
+    // The factors of an i64 are no greater than it, so they fit in an i64.
+    factors
+        .into_iter()
+        .filter_map(|p| i64::try_from(p).ok())
+        .collect()
+}
+
+/// Returns `base` raised to `exp` modulo `modulus`, in the range
+/// [0, `modulus`). `exp` must be non-negative and `modulus` must be positive.
+#[must_use]
+pub fn mod_pow(base: i64, exp: i64, modulus: i64) -> i64 {
+    let m = modulus.unsigned_abs();
+    let b = base.rem_euclid(modulus).unsigned_abs();
+    // The result is less than `modulus`, so it fits in an i64.
+    i64::try_from(pow_mod(b, exp.unsigned_abs(), m)).unwrap_or(0)
+}
+
+/// Returns the multiplicative inverse of `a` modulo `modulus`, in the range
+/// [0, `modulus`), or `None` if `a` and `modulus` aren't coprime. `modulus`
+/// must be positive.
+#[must_use]
+pub fn mod_inv(a: i64, modulus: i64) -> Option<i64> {
+    let m = i128::from(modulus);
+    let (mut r0, mut r1) = (m, i128::from(a).rem_euclid(m));
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    if r0 == 1 {
+        i64::try_from(t0.rem_euclid(m)).ok()
+    } else {
+        None
+    }
+}
+
+/// Returns the greatest common divisor of `a` and `b`.
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns `a * b` modulo `m`.
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    // The remainder is less than m, so it fits in a u64.
+    #[allow(clippy::cast_possible_truncation)]
+    let product = (u128::from(a) * u128::from(b) % u128::from(m)) as u64;
+    product
+}
+
+/// Returns `base` raised to `exp` modulo `m`.
+fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Tests `n` for primality with the Miller-Rabin test.
+fn is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    'witnesses: for a in SMALL_PRIMES {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witnesses;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Appends the prime factors of `n`, which has no small prime factors, to
+/// `factors`.
+fn factor_into(n: u64, factors: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime_u64(n) {
+        factors.push(n);
+        return;
+    }
+    let d = pollard_rho(n);
+    factor_into(d, factors);
+    factor_into(n / d, factors);
+}
+
+/// Finds a nontrivial divisor of the odd composite number `n` with Pollard's
+/// rho algorithm.
+fn pollard_rho(n: u64) -> u64 {
+    for offset in 1.. {
+        let step = |x: u64| (mul_mod(x, x, n) + offset) % n;
+        // The tortoise moves one step at a time and the hare two.
+        let (mut tortoise, mut hare, mut divisor) = (2, 2, 1);
+        while divisor == 1 {
+            tortoise = step(tortoise);
+            hare = step(step(hare));
+            divisor = gcd_u64(tortoise.abs_diff(hare), n);
+        }
+        if divisor != n {
+            return divisor;
+        }
+    }
+    unreachable!("a divisor is always found")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factorials() {
+        assert_eq!(factorial(0), Some(1));
+        assert_eq!(factorial(5), Some(120));
+        assert_eq!(factorial(20), Some(2_432_902_008_176_640_000));
+        assert_eq!(factorial(21), None);
+    }
+
+    #[test]
+    fn combinations_and_permutations() {
+        assert_eq!(choose(5, 2), Some(10));
+        assert_eq!(choose(5, 0), Some(1));
+        assert_eq!(choose(2, 5), Some(0));
+        assert_eq!(choose(62, 31), Some(465_428_353_255_261_088));
+        assert_eq!(choose(68, 34), None);
+        assert_eq!(permutations(5, 2), Some(20));
+        assert_eq!(permutations(5, 0), Some(1));
+        assert_eq!(permutations(30, 20), None);
+    }
+
+    #[test]
+    fn divisors() {
+        assert_eq!(gcd(12, -18), Some(6));
+        assert_eq!(gcd(0, 0), Some(0));
+        assert_eq!(gcd(i64::MIN, 0), None);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(-4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(i64::MAX, 2), None);
+    }
+
+    #[test]
+    fn primes() {
+        let primes: Vec<i64> = (0..30).filter(|n| is_prime(*n)).collect();
+        assert_eq!(primes, [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(!is_prime(-7));
+        assert!(is_prime(9_223_372_036_854_775_783));
+        assert!(!is_prime(3_215_031_751)); // a strong pseudoprime to 2, 3, 5, 7
+        assert_eq!(next_prime(-5), Some(2));
+        assert_eq!(next_prime(13), Some(17));
+        assert_eq!(next_prime(9_223_372_036_854_775_783), None);
+    }
+
+    #[test]
+    fn factorization() {
+        assert_eq!(factor(1), Vec::<i64>::new());
+        assert_eq!(factor(360), [2, 2, 2, 3, 3, 5]);
+        assert_eq!(factor(600_851_475_143), [71, 839, 1471, 6857]);
+        assert_eq!(
+            factor(4_611_686_014_132_420_609),
+            [2_147_483_647, 2_147_483_647]
+        );
+        assert_eq!(factor(i64::MAX), [7, 7, 73, 127, 337, 92_737, 649_657]);
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(-2, 3, 5), 2);
+        assert_eq!(mod_pow(7, 0, 1), 0);
+        assert_eq!(mod_pow(i64::MAX, i64::MAX, i64::MAX - 1), 1);
+        assert_eq!(mod_inv(3, 11), Some(4));
+        assert_eq!(mod_inv(-3, 11), Some(7));
+        assert_eq!(mod_inv(6, 9), None);
+    }
+}
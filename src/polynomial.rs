@@ -0,0 +1,362 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Polynomials with real coefficients: evaluation, products, quotients, and
+//! roots.
+//!
+//! Coefficients are ordered from the highest power down to the constant term,
+//! so `[1, -3, 2]` is x² - 3x + 2. Units are the caller's business; these
+//! functions work on plain numbers.
+
+/// A complex number, used for the roots of polynomials.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    /// Returns a complex number with real part `re` and imaginary part `im`.
+    #[must_use]
+    pub const fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    /// Returns the magnitude of this number.
+    #[must_use]
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Returns the principal square root of this number.
+    #[must_use]
+    pub fn sqrt(self) -> Complex {
+        let r = self.abs();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    /// Returns true if the imaginary part of this number is negligible next
+    /// to its magnitude.
+    #[must_use]
+    pub fn is_real(self) -> bool {
+        self.im.abs() <= REAL_TOLERANCE * self.abs().max(1.0)
+    }
+}
+
+impl std::ops::Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl std::ops::Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl std::ops::Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl std::ops::Mul<f64> for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: f64) -> Complex {
+        Complex::new(self.re * other, self.im * other)
+    }
+}
+
+impl std::ops::Div for Complex {
+    type Output = Complex;
+
+    fn div(self, other: Complex) -> Complex {
+        let d = other.re * other.re + other.im * other.im;
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / d,
+            (self.im * other.re - self.re * other.im) / d,
+        )
+    }
+}
+
+/// Roots whose imaginary parts are smaller than this, relative to their
+/// magnitudes, are considered real. Repeated roots are only found to about
+/// the square root of machine precision, so this can't be much tighter.
+const REAL_TOLERANCE: f64 = 1e-7;
+
+/// The most iterations Laguerre's method is given to find a root.
+const MAX_ITERATIONS: usize = 80;
+
+/// Every `BREAK_CYCLE` iterations, Laguerre's method takes a fractional step
+/// to break out of a limit cycle.
+const BREAK_CYCLE: usize = 10;
+
+/// The fractional steps taken to break limit cycles.
+const FRACTIONS: [f64; 8] = [0.5, 0.25, 0.75, 0.13, 0.38, 0.62, 0.88, 1.0];
+
+/// Evaluates the polynomial with coefficients `coeffs` at `x`.
+#[must_use]
+pub fn eval(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// Multiplies two polynomials.
+#[must_use]
+pub fn mul(a: &[f64], b: &[f64]) -> Vec<f64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product
+}
+
+/// Divides `a` by `b`, returning the quotient and the remainder. The
+/// remainder has one fewer coefficient than `b`, but always at least one, so
+/// that dividing by a constant leaves a remainder of `[0]`. Returns `None` if
+/// `b` is zero.
+#[must_use]
+pub fn div(a: &[f64], b: &[f64]) -> Option<(Vec<f64>, Vec<f64>)> {
+    let b = trim(b);
+    let lead = *b.first()?;
+    let (q, mut r) = if a.len() < b.len() {
+        let mut remainder = vec![0.0; b.len() - 1 - a.len()];
+        remainder.extend_from_slice(a);
+        (vec![0.0], remainder)
+    } else {
+        let mut r = a.to_vec();
+        let mut q = Vec::with_capacity(a.len() - b.len() + 1);
+        for i in 0..=a.len() - b.len() {
+            let factor = r[i] / lead;
+            for (j, y) in b.iter().enumerate() {
+                r[i + j] -= factor * y;
+            }
+            q.push(factor);
+        }
+        (q, r.split_off(a.len() - b.len() + 1))
+    };
+    if r.is_empty() {
+        r.push(0.0);
+    }
+    Some((q, r))
+}
+
+/// Returns `coeffs` without leading zeros.
+#[must_use]
+pub fn trim(coeffs: &[f64]) -> &[f64] {
+    let first = coeffs
+        .iter()
+        .position(|&c| c != 0.0)
+        .unwrap_or(coeffs.len());
+    &coeffs[first..]
+}
+
+/// Finds all of the roots of a polynomial, real and complex, sorted by their
+/// real parts and then their imaginary parts. A polynomial of degree n has n
+/// roots, counting repeated roots as many times as they repeat. Roots that
+/// are real to within rounding error have their imaginary parts set to zero.
+#[must_use]
+pub fn roots(coeffs: &[f64]) -> Vec<Complex> {
+    let a: Vec<Complex> = trim(coeffs).iter().map(|&c| Complex::new(c, 0.0)).collect();
+    if a.len() < 2 {
+        return Vec::new();
+    }
+
+    // Find each root of what's left after dividing out the roots found so
+    // far, then polish it against the original polynomial, since dividing
+    // accumulates rounding error.
+    let mut deflated = a.clone();
+    let mut roots = Vec::with_capacity(a.len() - 1);
+    while deflated.len() > 1 {
+        let x = laguerre(&deflated, Complex::new(0.0, 0.0));
+        // Synthetic division shifts the quotient's coefficients one place to
+        // the right of the dividend's.
+        let mut b = deflated[0];
+        for c in &mut deflated[1..] {
+            let next = *c + b * x;
+            *c = b;
+            b = next;
+        }
+        deflated.remove(0);
+        roots.push(laguerre(&a, x));
+    }
+
+    // The coefficients are real, so complex roots come in conjugate pairs.
+    // Rebuild each pair from its upper half so that the halves match exactly.
+    let mut paired: Vec<Complex> = Vec::with_capacity(roots.len());
+    for x in roots {
+        if x.is_real() {
+            paired.push(Complex::new(x.re, 0.0));
+        } else if x.im > 0.0 {
+            paired.push(Complex::new(x.re, -x.im));
+            paired.push(x);
+        }
+    }
+    paired.sort_by(|x, y| x.re.total_cmp(&y.re).then(x.im.total_cmp(&y.im)));
+    paired
+}
+
+/// Finds a root of the polynomial `a` with Laguerre's method, starting from
+/// `x`. Laguerre's method converges to some root from almost any starting
+/// point, complex or not.
+#[allow(clippy::many_single_char_names)] // b, d and f are the Horner sums of the textbook method
+fn laguerre(a: &[Complex], mut x: Complex) -> Complex {
+    #[allow(clippy::cast_precision_loss)]
+    let m = (a.len() - 1) as f64;
+    for i in 1..=MAX_ITERATIONS {
+        // Evaluate the polynomial and its first two derivatives (the second
+        // halved), and bound the rounding error in the polynomial's value.
+        let mut b = a[0];
+        let mut d = Complex::new(0.0, 0.0);
+        let mut f = Complex::new(0.0, 0.0);
+        let mut err = b.abs();
+        for &c in &a[1..] {
+            f = x * f + d;
+            d = x * d + b;
+            b = x * b + c;
+            err = b.abs() + x.abs() * err;
+        }
+        if b.abs() <= err * f64::EPSILON {
+            return x;
+        }
+
+        let g = d / b;
+        let g2 = g * g;
+        let h = g2 - f / b * 2.0;
+        let sq = ((h * m - g2) * (m - 1.0)).sqrt();
+        let gp = g + sq;
+        let gm = g - sq;
+        let denom = if gp.abs() < gm.abs() { gm } else { gp };
+        #[allow(clippy::cast_precision_loss)]
+        let dx = if denom.abs() > 0.0 {
+            Complex::new(m, 0.0) / denom
+        } else {
+            Complex::new((i as f64).cos(), (i as f64).sin()) * (1.0 + x.abs())
+        };
+
+        let next = x - dx;
+        if next == x {
+            return x;
+        }
+        x = if i % BREAK_CYCLE == 0 {
+            x - dx * FRACTIONS[(i / BREAK_CYCLE - 1) % FRACTIONS.len()]
+        } else {
+            next
+        };
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn evaluation() {
+        assert_eq!(eval(&[1.0, -3.0, 2.0], 3.0), 2.0);
+        assert_eq!(eval(&[], 3.0), 0.0);
+    }
+
+    #[test]
+    fn products_and_quotients() {
+        assert_eq!(mul(&[1.0, -1.0], &[1.0, -2.0]), [1.0, -3.0, 2.0]);
+
+        let (q, r) = div(&[1.0, -3.0, 2.0, 5.0], &[1.0, -1.0]).unwrap();
+        assert_eq!(q, [1.0, -2.0, 0.0]);
+        assert_eq!(r, [5.0]);
+
+        let (q, r) = div(&[3.0], &[0.0, 1.0, 2.0]).unwrap();
+        assert_eq!(q, [0.0]);
+        assert_eq!(r, [3.0]);
+
+        let (q, r) = div(&[1.0, -3.0, 2.0], &[0.0, 1.0]).unwrap();
+        assert_eq!(q, [1.0, -3.0, 2.0]);
+        assert_eq!(r, [0.0]);
+
+        let (q, r) = div(&[], &[2.0]).unwrap();
+        assert_eq!(q, [0.0]);
+        assert_eq!(r, [0.0]);
+
+        assert!(div(&[1.0], &[0.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn real_roots() {
+        let r = roots(&[1.0, -3.0, 2.0]);
+        assert_eq!(r.len(), 2);
+        assert_relative_eq!(r[0].re, 1.0);
+        assert_relative_eq!(r[1].re, 2.0);
+        assert!(r.iter().all(|x| x.im == 0.0));
+
+        let r = roots(&[0.0, 2.0, -12.0, 22.0, -12.0]);
+        assert_eq!(r.len(), 3);
+        for (x, expected) in r.iter().zip([1.0, 2.0, 3.0]) {
+            assert_relative_eq!(x.re, expected, epsilon = 1e-12);
+            assert_eq!(x.im, 0.0);
+        }
+    }
+
+    #[test]
+    fn repeated_roots() {
+        let r = roots(&[1.0, -3.0, 3.0, -1.0]);
+        assert_eq!(r.len(), 3);
+        for x in r {
+            assert_relative_eq!(x.re, 1.0, epsilon = 1e-4);
+            assert_eq!(x.im, 0.0);
+        }
+    }
+
+    #[test]
+    fn complex_roots() {
+        let r = roots(&[1.0, 0.0, 1.0]);
+        assert_eq!(r.len(), 2);
+        assert_relative_eq!(r[0].re, 0.0);
+        assert_relative_eq!(r[0].im, -1.0);
+        assert_relative_eq!(r[1].im, 1.0);
+
+        let r = roots(&[1.0, -1.0, 1.0, -1.0]);
+        assert_eq!(r.len(), 3);
+        assert_relative_eq!(r[0].im, -1.0, epsilon = 1e-12);
+        assert_relative_eq!(r[1].im, 1.0, epsilon = 1e-12);
+        assert_relative_eq!(r[2].re, 1.0, epsilon = 1e-12);
+        assert_eq!(r[2].im, 0.0);
+    }
+
+    #[test]
+    fn constant_polynomials_have_no_roots() {
+        assert!(roots(&[5.0]).is_empty());
+        assert!(roots(&[0.0, 0.0]).is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! A small, seedable pseudorandom number generator.
+//!
+//! The generator is xoshiro256**, seeded through `SplitMix64` so that any 64-bit
+//! seed gives a well-mixed state. It's fast and statistically sound, but not
+//! suitable for cryptography.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::distributions;
+
+/// 2⁻⁵³, the gap between the numbers `Rng::uniform` can return.
+const STEP: f64 = 1.0 / 9_007_199_254_740_992.0;
+
+/// A pseudorandom number generator.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    /// Creates a generator whose sequence is determined by `seed`.
+    #[must_use]
+    pub fn new(seed: u64) -> Rng {
+        let mut x = seed;
+        let mut state = [0; 4];
+        for s in &mut state {
+            *s = splitmix64(&mut x);
+        }
+        Rng { state }
+    }
+
+    /// Creates a generator with an unpredictable seed.
+    #[must_use]
+    pub fn from_entropy() -> Rng {
+        let mut hasher = RandomState::new().build_hasher();
+        if let Ok(t) = SystemTime::now().duration_since(UNIX_EPOCH) {
+            hasher.write_u128(t.as_nanos());
+        }
+        Rng::new(hasher.finish())
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Returns a number uniformly distributed in [0, 1).
+    pub fn uniform(&mut self) -> f64 {
+        // 53 random bits fill an f64's significand exactly.
+        #[allow(clippy::cast_precision_loss)]
+        let x = (self.next_u64() >> 11) as f64;
+        x * STEP
+    }
+
+    /// Returns a number uniformly distributed in [0, `n`). `n` must be
+    /// positive.
+    pub fn below(&mut self, n: u64) -> u64 {
+        // Lemire's method: reject the few products whose low half would bias
+        // the high half.
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let m = u128::from(self.next_u64()) * u128::from(n);
+            #[allow(clippy::cast_possible_truncation)]
+            if m as u64 >= threshold {
+                return (m >> 64) as u64;
+            }
+        }
+    }
+
+    /// Returns a number from the standard normal distribution.
+    pub fn normal(&mut self) -> f64 {
+        // Shift by half a step so the uniform number is never 0, which would
+        // give -∞.
+        #[allow(clippy::cast_precision_loss)]
+        let p = ((self.next_u64() >> 11) as f64 + 0.5) * STEP;
+        distributions::normal_inv(p, 0.0, 1.0)
+    }
+}
+
+/// Advances the `SplitMix64` generator `x` and returns its output.
+fn splitmix64(x: &mut u64) -> u64 {
+    *x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+        let xs: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..4).map(|_| b.next_u64()).collect();
+        let zs: Vec<u64> = (0..4).map(|_| c.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn reference_sequence() {
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0x99ec_5f36_cb75_f2b4);
+        assert_eq!(rng.next_u64(), 0xbf6e_1f78_4956_452a);
+        assert_eq!(rng.next_u64(), 0x1a5f_849d_4933_e6e0);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let x = rng.uniform();
+            assert!((0.0..1.0).contains(&x));
+            assert!(rng.below(6) < 6);
+            assert!(rng.normal().is_finite());
+        }
+        assert_eq!(rng.below(1), 0);
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! User-adjustable settings.
+
+use crate::units::{self, System, Unit};
+
+/// Settings that change how builtins behave and how results are presented.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// If true, results of arithmetic whose units match one of the units in
+    /// `nameable` are expressed in that unit.
+    pub autoname: bool,
+    /// Named units that results may be expressed in when `autoname` is true.
+    /// Earlier units take precedence over later ones.
+    pub nameable: Vec<Unit>,
+    /// If set, the results of arithmetic are expressed in this system's base
+    /// units and, when `autoname` is true, its named units.
+    pub system: Option<System>,
+    /// If true, numbers are displayed with an SI prefix that keeps their
+    /// values between 1 and 1000.
+    pub autoscale: bool,
+    /// Units that numbers are displayed in, regardless of `autoscale`, when
+    /// they measure the same quantity. Earlier units take precedence over
+    /// later ones.
+    pub preferred: Vec<Unit>,
+    /// If set, the angle unit that trigonometric functions assume
+    /// dimensionless numbers are in.
+    pub angle_mode: Option<Unit>,
+    /// If true and `angle_mode` is set, inverse trigonometric functions give
+    /// results in the `angle_mode` unit instead of radians.
+    pub arc_in_mode: bool,
+}
+
+impl Settings {
+    /// Returns the default settings.
+    #[must_use]
+    pub fn new() -> Settings {
+        Settings {
+            autoname: true,
+            nameable: units::NAMEABLE.clone(),
+            system: None,
+            autoscale: false,
+            preferred: Vec::new(),
+            angle_mode: None,
+            arc_in_mode: false,
+        }
+    }
+
+    /// Returns the units that the results of arithmetic may be named with. If
+    /// a system is selected, these are the nameable units that belong to the
+    /// system followed by the system's own named units.
+    #[must_use]
+    pub fn name_candidates(&self) -> Vec<Unit> {
+        match &self.system {
+            Some(system) => self
+                .nameable
+                .iter()
+                .filter(|u| system.contains(u))
+                .chain(system.named.iter())
+                .cloned()
+                .collect(),
+            None => self.nameable.clone(),
+        }
+    }
+
+    /// Returns `x` as it should be displayed: in a preferred unit if there is
+    /// one for its quantity, otherwise scaled if `autoscale` is true. The
+    /// result is equal to `x`; only its unit may differ.
+    #[must_use]
+    pub fn displayed(&self, x: &units::Number) -> units::Number {
+        let has_preferred = x
+            .unit
+            .as_ref()
+            .is_some_and(|u| self.preferred.iter().any(|p| u.is_commensurable_with(p)));
+        if has_preferred {
+            x.in_preferred(&self.preferred)
+        } else if self.autoscale {
+            x.scaled()
+        } else {
+            x.clone()
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
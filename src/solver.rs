@@ -0,0 +1,226 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Numerical root finding for functions of one variable.
+//!
+//! The function is allowed to fail, since in practice it's a block of words
+//! that can run into any error a builtin can. Its errors are passed through.
+
+/// How many times a bracket is widened before giving up on it.
+const MAX_EXPANSIONS: usize = 60;
+
+/// How much a bracket grows each time it's widened.
+const EXPANSION: f64 = 1.6;
+
+/// The most iterations Brent's and Newton's methods are given.
+const MAX_ITERATIONS: usize = 100;
+
+/// Finds an x near `x0` for which `f(x)` is zero. Returns `None` if no root
+/// was found.
+///
+/// A bracket around `x0` is widened until `f` changes sign across it, and
+/// then narrowed with Brent's method. Roots that `f` touches without
+/// crossing, like the root of x², can't be bracketed, so if widening fails,
+/// Newton's method is tried instead.
+///
+/// # Errors
+///
+/// Returns an error if `f` does.
+pub fn solve<E>(mut f: impl FnMut(f64) -> Result<f64, E>, x0: f64) -> Result<Option<f64>, E> {
+    let f0 = f(x0)?;
+    if f0 == 0.0 {
+        return Ok(Some(x0));
+    }
+    let x = match bracket(&mut f, x0)? {
+        Some(((a, fa), (b, fb))) => brent(&mut f, (a, fa), (b, fb))?,
+        None => newton(&mut f, x0)?,
+    };
+    // A sign change can also be a pole, which Brent's method converges on
+    // just as happily. A root is no farther from zero than the guess was.
+    match x {
+        Some(x) if f(x)?.abs() <= f0.abs() => Ok(Some(x)),
+        _ => Ok(None),
+    }
+}
+
+/// Widens an interval around `x0` until `f` has opposite signs at its ends.
+/// Returns the ends and the values of `f` there, or `None` if `f` didn't
+/// change sign or wasn't finite.
+#[allow(clippy::type_complexity)]
+fn bracket<E>(
+    f: &mut impl FnMut(f64) -> Result<f64, E>,
+    x0: f64,
+) -> Result<Option<((f64, f64), (f64, f64))>, E> {
+    let step = if x0 == 0.0 { 0.1 } else { 0.1 * x0.abs() };
+    let (mut a, mut b) = (x0 - step, x0 + step);
+    let (mut fa, mut fb) = (f(a)?, f(b)?);
+    for _ in 0..MAX_EXPANSIONS {
+        if !fa.is_finite() || !fb.is_finite() {
+            return Ok(None);
+        }
+        if fa.signum() != fb.signum() {
+            return Ok(Some(((a, fa), (b, fb))));
+        }
+        // Widen toward whichever end is closer to zero.
+        if fa.abs() < fb.abs() {
+            a += EXPANSION * (a - b);
+            fa = f(a)?;
+        } else {
+            b += EXPANSION * (b - a);
+            fb = f(b)?;
+        }
+    }
+    Ok(None)
+}
+
+/// Narrows the bracket [a, b], across which `f` changes sign, down to a root
+/// with Brent's method, which combines inverse quadratic interpolation and
+/// the secant method with bisection to guarantee convergence.
+#[allow(clippy::many_single_char_names)] // a through e, p, q and s are Brent's own names
+fn brent<E>(
+    f: &mut impl FnMut(f64) -> Result<f64, E>,
+    (mut a, mut fa): (f64, f64),
+    (mut b, mut fb): (f64, f64),
+) -> Result<Option<f64>, E> {
+    // c is the previous best guess, and the root is always between b and c.
+    let (mut c, mut fc) = (b, fb);
+    let (mut d, mut e) = (b - a, b - a);
+    for _ in 0..MAX_ITERATIONS {
+        if fb.signum() == fc.signum() {
+            (c, fc) = (a, fa);
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            (a, fa) = (b, fb);
+            (b, fb) = (c, fc);
+            (c, fc) = (a, fa);
+        }
+        let tol = 2.0 * f64::EPSILON * b.abs();
+        let m = (c - b) / 2.0;
+        if m.abs() <= tol || fb == 0.0 {
+            return Ok(Some(b));
+        }
+
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            // Interpolate, quadratically if there are three distinct points
+            // and linearly otherwise.
+            let s = fb / fa;
+            // a is only ever equal to c when it was copied from it.
+            #[allow(clippy::float_cmp)]
+            let (mut p, mut q) = if a == c {
+                (2.0 * m * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * m * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            // Accept the interpolation only if it falls within the bracket and
+            // is converging faster than bisection would.
+            if 2.0 * p < (3.0 * m * q - (tol * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = m;
+                e = d;
+            }
+        } else {
+            d = m;
+            e = d;
+        }
+
+        (a, fa) = (b, fb);
+        b += if d.abs() > tol { d } else { tol.copysign(m) };
+        fb = f(b)?;
+    }
+    Ok(None)
+}
+
+/// Finds a root of `f` near `x0` with Newton's method, using a central
+/// difference for the derivative. Newton's method converges only linearly on
+/// a touching root, so it stops once a step is small next to `x0` as well as
+/// next to x; otherwise it would chase a root at zero forever.
+fn newton<E>(f: &mut impl FnMut(f64) -> Result<f64, E>, x0: f64) -> Result<Option<f64>, E> {
+    let tol = f64::EPSILON.sqrt() * x0.abs().max(1.0);
+    let mut x = x0;
+    for _ in 0..MAX_ITERATIONS {
+        let fx = f(x)?;
+        if fx == 0.0 {
+            return Ok(Some(x));
+        }
+        let h = f64::EPSILON.cbrt() * x.abs().max(f64::EPSILON.cbrt());
+        let slope = (f(x + h)? - f(x - h)?) / (2.0 * h);
+        let dx = fx / slope;
+        if !dx.is_finite() {
+            return Ok(None);
+        }
+        x -= dx;
+        if dx.abs() <= tol || dx.abs() <= 4.0 * f64::EPSILON * x.abs() {
+            return Ok(Some(x));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn ok(f: impl Fn(f64) -> f64) -> impl FnMut(f64) -> Result<f64, ()> {
+        move |x| Ok(f(x))
+    }
+
+    #[test]
+    fn crossing_roots() {
+        let x = solve(ok(|x| x * x - 2.0), 1.0).unwrap().unwrap();
+        assert_relative_eq!(x, std::f64::consts::SQRT_2);
+        let x = solve(ok(|x| x * x - 2.0), -5.0).unwrap().unwrap();
+        assert_relative_eq!(x, -std::f64::consts::SQRT_2);
+        let x = solve(ok(|x| x.cos() - x), 0.0).unwrap().unwrap();
+        assert_relative_eq!(x, 0.739_085_133_215_160_6);
+        let x = solve(ok(|x| x.exp() - 1e6), 1.0).unwrap().unwrap();
+        assert_relative_eq!(x, 1e6_f64.ln());
+    }
+
+    #[test]
+    fn touching_roots() {
+        let x = solve(ok(|x| (x - 3.0) * (x - 3.0)), 1.0).unwrap().unwrap();
+        assert_relative_eq!(x, 3.0, epsilon = 1e-6);
+        let x = solve(ok(|x| x * x), 5.0).unwrap().unwrap();
+        assert_relative_eq!(x, 0.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn no_roots() {
+        assert_eq!(solve(ok(|x| x * x + 1.0), 1.0), Ok(None));
+        assert_eq!(solve(ok(|x| 1.0 / x), 1.0), Ok(None));
+    }
+
+    #[test]
+    fn errors_pass_through() {
+        let f = |x: f64| if x > 1.5 { Err("too big") } else { Ok(x - 2.0) };
+        assert_eq!(solve(f, 1.0), Err("too big"));
+    }
+}
@@ -56,7 +56,7 @@
 //! assert_eq!(stack.height(), 1);
 //! ```
 
-use crate::{integer, units};
+use crate::{eval, integer, matrix, units, vector};
 
 /// Errors returned by stack operations.
 #[derive(Debug, PartialEq)]
@@ -78,33 +78,53 @@ pub enum Item {
     Float(units::Number),
     Integer(integer::Integer),
     Unit(units::Unit),
+    Vector(vector::Vector),
+    Matrix(matrix::Matrix),
+    Block(eval::Block),
 }
 
 /// A LIFO collection of typed objects.
-pub struct Stack(Vec<Item>);
+pub struct Stack {
+    items: Vec<Item>,
+    /// The lowest height the stack has had since `take_low_water` was last
+    /// called.
+    low_water: usize,
+}
 
 impl Stack {
     /// Creates an empty stack.
     #[must_use]
     pub fn new() -> Stack {
-        Stack(Vec::new())
+        Stack {
+            items: Vec::new(),
+            low_water: 0,
+        }
     }
 
     /// Returns the number of items on the stack.
     #[must_use]
     pub fn height(&self) -> usize {
-        self.0.len()
+        self.items.len()
     }
 
     /// Returns true if the stack is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.items.is_empty()
     }
 
     /// Removes all items from the stack.
     pub fn clear(&mut self) {
-        self.0.clear();
+        self.items.clear();
+        self.low_water = 0;
+    }
+
+    /// Returns the lowest height the stack has had since the last call, and
+    /// starts tracking again from its current height. Items popped and
+    /// pushed back within a transaction count, so after `+` on a stack of
+    /// two items, the lowest height is zero.
+    pub fn take_low_water(&mut self) -> usize {
+        std::mem::replace(&mut self.low_water, self.items.len())
     }
 
     /// Pops an item off of the stack and returns it.
@@ -113,20 +133,24 @@ impl Stack {
     ///
     /// Returns an error if the stack is empty.
     pub fn pop(&mut self) -> Result<Item, Error> {
-        self.0.pop().ok_or(Error::Underflow)
+        let item = self.items.pop().ok_or(Error::Underflow)?;
+        self.low_water = self.low_water.min(self.items.len());
+        Ok(item)
     }
 
     /// Pushes a floating-point number with optional units onto the stack.
     pub fn pushf(&mut self, x: units::Number) {
-        self.0.push(Item::Float(x));
+        self.items.push(Item::Float(x));
     }
 
-    /// Pushes a unit onto the stack.
+    /// Pushes a unit onto the stack. Units without base units are pushed as
+    /// numbers, except for named ratios like `%`, which can be used with
+    /// `into`.
     pub fn pushu(&mut self, u: units::Unit) {
-        if u.numer().is_empty() && u.denom().is_empty() {
+        if u.is_scalar() && u.symbol.is_none() {
             self.pushx(u.constant());
         } else {
-            self.0.push(Item::Unit(u));
+            self.items.push(Item::Unit(u));
         }
     }
 
@@ -137,11 +161,26 @@ impl Stack {
 
     /// Pushes an integer onto the stack.
     pub fn pushi(&mut self, x: integer::Integer) {
-        self.0.push(Item::Integer(x));
+        self.items.push(Item::Integer(x));
+    }
+
+    /// Pushes a vector onto the stack.
+    pub fn pushv(&mut self, v: vector::Vector) {
+        self.items.push(Item::Vector(v));
+    }
+
+    /// Pushes a matrix onto the stack.
+    pub fn pushm(&mut self, m: matrix::Matrix) {
+        self.items.push(Item::Matrix(m));
+    }
+
+    /// Pushes a block onto the stack.
+    pub fn pushb(&mut self, b: eval::Block) {
+        self.items.push(Item::Block(b));
     }
 
     /// Starts a transaction.
-    pub fn begin(&mut self) -> Transaction {
+    pub fn begin(&mut self) -> Transaction<'_> {
         let stack_remaining = self.height();
         Transaction {
             stack: self,
@@ -192,7 +231,7 @@ impl Transaction<'_> {
         } else {
             Ok(self.pushed.pop().unwrap_or_else(|| {
                 self.stack_remaining -= 1;
-                self.stack.0[self.stack_remaining].clone()
+                self.stack.items[self.stack_remaining].clone()
             }))
         }
     }
@@ -212,16 +251,35 @@ impl Transaction<'_> {
 
         let b = self.pushed.pop().unwrap_or_else(|| {
             self.stack_remaining -= 1;
-            self.stack.0[self.stack_remaining].clone()
+            self.stack.items[self.stack_remaining].clone()
         });
         let a = self.pushed.pop().unwrap_or_else(|| {
             self.stack_remaining -= 1;
-            self.stack.0[self.stack_remaining].clone()
+            self.stack.items[self.stack_remaining].clone()
         });
 
         Ok((a, b))
     }
 
+    /// Pops `n` items off the stack and returns them in the order they were
+    /// pushed, i.e. with the topmost item last.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than `n` items on the stack.
+    pub fn pop_n(&mut self, n: usize) -> Result<Vec<Item>, Error> {
+        if self.height() < n {
+            return Err(Error::Underflow);
+        }
+
+        let mut items = Vec::with_capacity(n);
+        for _ in 0..n {
+            items.push(self.pop()?);
+        }
+        items.reverse();
+        Ok(items)
+    }
+
     /// Removes everything from the stack except the topmost `n` items.
     ///
     /// # Errors
@@ -236,7 +294,7 @@ impl Transaction<'_> {
         if self.stack_remaining > 0 && n > self.pushed.len() {
             let n_from_stack = n - self.pushed.len();
             let ix0 = self.stack_remaining - n_from_stack;
-            new_pushed.extend_from_slice(&self.stack.0[ix0..self.stack_remaining]);
+            new_pushed.extend_from_slice(&self.stack.items[ix0..self.stack_remaining]);
         }
         new_pushed.append(&mut self.pushed);
         self.pushed = new_pushed;
@@ -248,7 +306,7 @@ impl Transaction<'_> {
     /// Pushes an item onto the stack.
     pub fn push(&mut self, item: Item) {
         if let Item::Unit(u) = &item {
-            if u.numer().is_empty() && u.denom().is_empty() {
+            if u.is_scalar() && u.symbol.is_none() {
                 self.pushx(u.constant());
                 return;
             }
@@ -276,14 +334,30 @@ impl Transaction<'_> {
         self.push(Item::Integer(x));
     }
 
+    /// Pushes a vector onto the stack.
+    pub fn pushv(&mut self, v: vector::Vector) {
+        self.push(Item::Vector(v));
+    }
+
+    /// Pushes a matrix onto the stack.
+    pub fn pushm(&mut self, m: matrix::Matrix) {
+        self.push(Item::Matrix(m));
+    }
+
+    /// Pushes a block onto the stack.
+    pub fn pushb(&mut self, b: eval::Block) {
+        self.push(Item::Block(b));
+    }
+
     /// Commits all pops and pushes performed during this transaction to the
     /// stack and ends the transaction.
     ///
     /// Use the `commit!` macro for a convenient way to commit a transaction
     /// and produce an `Ok(())`.
     pub fn commit(&mut self) {
-        self.stack.0.truncate(self.stack_remaining);
-        self.stack.0.append(&mut self.pushed);
+        self.stack.low_water = self.stack.low_water.min(self.stack_remaining);
+        self.stack.items.truncate(self.stack_remaining);
+        self.stack.items.append(&mut self.pushed);
         self.stack_remaining = self.stack.height();
     }
 }
@@ -310,13 +384,14 @@ pub fn zip<T, U>(a: Result<T, Error>, b: Result<U, Error>) -> Result<(T, U), Err
 }
 
 /// Pops a numeric item off the stack. When successful, the result will always
-/// be a `units::Number`, even if the popped item was an integer.
+/// be a `units::Number`, even if the popped item was an integer. A number in a
+/// ratio like `%` is folded into a plain number.
 #[macro_export]
 macro_rules! pop_as_f {
     ($stacklike: ident) => {
         $stacklike.pop().and_then(|item| match item {
-            $crate::stack::Item::Float(x) => Ok(x),
-            $crate::stack::Item::Integer(x) => Ok($crate::units::Number::new(x.value as f64)),
+            $crate::stack::Item::Float(x) => Ok(x.folded()),
+            $crate::stack::Item::Integer(x) => Ok(x.as_units_number()),
             _ => Err($crate::stack::Error::TypeMismatch),
         })
     };
@@ -324,21 +399,23 @@ macro_rules! pop_as_f {
 
 /// Pops two numeric items off the stack. When successful, the results will
 /// always be `units::Number`s, even if any of the popped items was an integer.
+/// Numbers in ratios like `%` are folded into plain numbers.
 #[macro_export]
 macro_rules! pop_as_ff {
     ($stacklike: ident) => {
         $stacklike.pop2().and_then(|items| match items {
-            ($crate::stack::Item::Float(a), $crate::stack::Item::Float(b)) => Ok((a, b)),
+            ($crate::stack::Item::Float(a), $crate::stack::Item::Float(b)) => {
+                Ok((a.folded(), b.folded()))
+            }
             ($crate::stack::Item::Float(a), $crate::stack::Item::Integer(b)) => {
-                Ok((a, units::Number::new(b.value as f64)))
+                Ok((a.folded(), b.as_units_number()))
             }
             ($crate::stack::Item::Integer(a), $crate::stack::Item::Float(b)) => {
-                Ok((units::Number::new(a.value as f64), b))
+                Ok((a.as_units_number(), b.folded()))
+            }
+            ($crate::stack::Item::Integer(a), $crate::stack::Item::Integer(b)) => {
+                Ok((a.as_units_number(), b.as_units_number()))
             }
-            ($crate::stack::Item::Integer(a), $crate::stack::Item::Integer(b)) => Ok((
-                units::Number::new(a.value as f64),
-                units::Number::new(b.value as f64),
-            )),
             _ => Err($crate::stack::Error::TypeMismatch),
         })
     };
@@ -445,6 +522,50 @@ macro_rules! popff {
     };
 }
 
+/// Pops a vector off a stack.
+#[macro_export]
+macro_rules! popv {
+    ($tx: expr) => {
+        $tx.pop().and_then(|item| match item {
+            $crate::stack::Item::Vector(v) => Ok(v),
+            _ => Err($crate::stack::Error::TypeMismatch),
+        })
+    };
+}
+
+/// Pops two vectors off a stack.
+#[macro_export]
+macro_rules! popvv {
+    ($tx: expr) => {
+        $tx.pop2().and_then(|items| match items {
+            ($crate::stack::Item::Vector(a), $crate::stack::Item::Vector(b)) => Ok((a, b)),
+            _ => Err($crate::stack::Error::TypeMismatch),
+        })
+    };
+}
+
+/// Pops a matrix off a stack.
+#[macro_export]
+macro_rules! popm {
+    ($tx: expr) => {
+        $tx.pop().and_then(|item| match item {
+            $crate::stack::Item::Matrix(m) => Ok(m),
+            _ => Err($crate::stack::Error::TypeMismatch),
+        })
+    };
+}
+
+/// Pops a unit off a stack.
+#[macro_export]
+macro_rules! popu {
+    ($tx: expr) => {
+        $tx.pop().and_then(|items| match items {
+            $crate::stack::Item::Unit(a) => Ok(a),
+            _ => Err($crate::stack::Error::TypeMismatch),
+        })
+    };
+}
+
 /// Pops a floating-point number and a unit off a stack.
 #[macro_export]
 macro_rules! popfu {
@@ -493,7 +614,7 @@ impl<'a> IntoIterator for &'a Stack {
     /// the bottom and working upward.
     fn into_iter(self) -> Self::IntoIter {
         Iter {
-            items: self.0.as_slice(),
+            items: self.items.as_slice(),
             ix: 0,
         }
     }
@@ -771,6 +892,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn transaction_pop_n() {
+        let mut s = Stack::new();
+        s.pushx(1.0);
+        s.pushx(2.0);
+        {
+            let mut tx = s.begin();
+            tx.pushx(3.0);
+            assert!(tx.pop_n(4).is_err());
+            assert_eq!(tx.height(), 3);
+            let values: Vec<f64> = tx
+                .pop_n(3)
+                .unwrap()
+                .into_iter()
+                .map(|item| match item {
+                    Item::Float(x) => x.value,
+                    _ => panic!("expected Item::Float"),
+                })
+                .collect();
+            assert_eq!(values, [1.0, 2.0, 3.0]);
+            assert!(tx.is_empty());
+        }
+    }
+
     #[test]
     fn transaction_pop2_after_two_transaction_pushes() {
         let mut s = Stack::new();
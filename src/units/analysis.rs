@@ -0,0 +1,283 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Dimensional analysis.
+
+use super::{unit::exponent_to_superscripts, Dimension, Error, Number, Rational};
+
+/// Exponents `exponents` considers, in the order they're tried. Trying small
+/// exponents first finds simple combinations early, which lets the search
+/// prune more.
+const EXPONENTS: [i32; 7] = [0, 1, -1, 2, -2, 3, -3];
+
+/// The most quantities `exponents` will search over after solving for the
+/// rest. Each one multiplies the work by seven.
+pub const MAX_FREE_QUANTITIES: usize = 6;
+
+/// Coefficients smaller than this are treated as zero during elimination.
+const TOLERANCE: f64 = 1e-9;
+
+/// Finds integer exponents e₁ … eₙ for quantities with dimensions d₁ … dₙ
+/// such that d₁^e₁ ⋯ dₙ^eₙ is `target`. For example, given a velocity, a
+/// density, and a length, and the dimension of pressure, the exponents are 2,
+/// 1, and 0, since ρv² is a pressure.
+///
+/// Exponents are between -3 and 3, and at least one is nonzero. If several
+/// combinations work, the one with the smallest sum of absolute exponents is
+/// returned, and ties go to the combination found first. Returns `None` if no
+/// combination works.
+///
+/// The exponents are a solution to a system of linear equations, one for each
+/// physical quantity. Gaussian elimination solves for as many exponents as
+/// the equations pin down, and only the rest are searched over.
+///
+/// # Errors
+///
+/// Returns `TooManyQuantities` if more than `MAX_FREE_QUANTITIES` exponents
+/// would have to be searched over.
+pub fn exponents(dimensions: &[Dimension], target: &Dimension) -> Result<Option<Vec<i32>>, Error> {
+    let Some(system) = System::eliminate(dimensions, target) else {
+        return Ok(None);
+    };
+    if system.free.len() > MAX_FREE_QUANTITIES {
+        return Err(Error::TooManyQuantities(system.free.len()));
+    }
+    let mut search = Search {
+        dimensions,
+        target,
+        system: &system,
+        current: vec![0; dimensions.len()],
+        best: None,
+    };
+    search.run(0, 0);
+    Ok(search.best.map(|(exponents, _)| exponents))
+}
+
+/// Formats a combination of quantities like "[10 m⋅s⁻¹]² ⋅ [1.2 kg⋅m⁻³]".
+/// Quantities with an exponent of zero are omitted.
+#[must_use]
+pub fn expression(quantities: &[Number], exponents: &[i32]) -> String {
+    quantities
+        .iter()
+        .zip(exponents)
+        .filter(|(_, e)| **e != 0)
+        .map(|(x, e)| match e {
+            1 => x.to_string(),
+            e => format!("{x}{}", exponent_to_superscripts(Rational::from(*e))),
+        })
+        .collect::<Vec<String>>()
+        .join(" ⋅ ")
+}
+
+/// A system of equations for the exponents in reduced row echelon form. Each
+/// row gives the exponent of one pivot quantity in terms of the exponents of
+/// the free quantities.
+struct System {
+    /// The pivot quantity of each row.
+    pivots: Vec<usize>,
+    /// The quantities whose exponents aren't determined by the others.
+    free: Vec<usize>,
+    /// The rows, each with one coefficient per quantity followed by the
+    /// right-hand side.
+    rows: Vec<Vec<f64>>,
+}
+
+impl System {
+    /// Sets up and reduces the equations for `exponents`. Returns `None` if
+    /// they have no solution at all.
+    fn eliminate(dimensions: &[Dimension], target: &Dimension) -> Option<System> {
+        let n = dimensions.len();
+        let mut rows: Vec<Vec<f64>> = (0..target.exponents().len())
+            .map(|q| {
+                dimensions
+                    .iter()
+                    .chain(std::iter::once(target))
+                    .map(|d| d.exponents()[q].to_f64())
+                    .collect()
+            })
+            .collect();
+
+        let mut pivots = Vec::new();
+        let mut free = Vec::new();
+        for col in 0..n {
+            let r = pivots.len();
+            let best =
+                (r..rows.len()).max_by(|&i, &j| rows[i][col].abs().total_cmp(&rows[j][col].abs()));
+            let Some(best) = best.filter(|&i| rows[i][col].abs() > TOLERANCE) else {
+                free.push(col);
+                continue;
+            };
+            rows.swap(r, best);
+            let lead = rows[r][col];
+            for x in &mut rows[r] {
+                *x /= lead;
+            }
+            let pivot = rows[r].clone();
+            for (i, row) in rows.iter_mut().enumerate() {
+                let factor = row[col];
+                if i != r && factor != 0.0 {
+                    for (x, p) in row.iter_mut().zip(&pivot) {
+                        *x -= factor * p;
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+
+        // Rows left over without a pivot say 0 = rhs.
+        if rows[pivots.len()..]
+            .iter()
+            .any(|row| row[n].abs() > TOLERANCE)
+        {
+            return None;
+        }
+        rows.truncate(pivots.len());
+        Some(System { pivots, free, rows })
+    }
+}
+
+/// State of a depth-first search over the exponents of the free quantities.
+struct Search<'a> {
+    dimensions: &'a [Dimension],
+    target: &'a Dimension,
+    system: &'a System,
+    /// Exponents chosen so far. Only the free quantities visited so far are
+    /// meaningful.
+    current: Vec<i32>,
+    /// The best exponents found so far and their cost.
+    best: Option<(Vec<i32>, i32)>,
+}
+
+impl Search<'_> {
+    /// Tries every exponent for the next free quantity, given that the free
+    /// quantities visited so far cost `cost`.
+    fn run(&mut self, visited: usize, cost: i32) {
+        if self.best.as_ref().is_some_and(|(_, best)| cost >= *best) {
+            return;
+        }
+
+        let Some(&col) = self.system.free.get(visited) else {
+            self.solve_pivots(cost);
+            return;
+        };
+
+        for e in EXPONENTS {
+            self.current[col] = e;
+            self.run(visited + 1, cost + e.abs());
+        }
+        self.current[col] = 0;
+    }
+
+    /// Fills in the pivot exponents from the free ones, and keeps the result
+    /// if they're whole numbers in range and the combination beats the best
+    /// so far.
+    fn solve_pivots(&mut self, mut cost: i32) {
+        let n = self.dimensions.len();
+        for (row, &col) in self.system.rows.iter().zip(&self.system.pivots) {
+            let x = self
+                .system
+                .free
+                .iter()
+                .fold(row[n], |x, &f| x - row[f] * f64::from(self.current[f]));
+            let e = x.round();
+            if (x - e).abs() > TOLERANCE || e.abs() > 3.0 {
+                return;
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            let e = e as i32;
+            self.current[col] = e;
+            cost += e.abs();
+        }
+        if cost == 0 || self.best.as_ref().is_some_and(|(_, best)| cost >= *best) {
+            return;
+        }
+
+        // Elimination is done in floating point, so check the answer exactly.
+        let product = self
+            .dimensions
+            .iter()
+            .zip(&self.current)
+            .try_fold(Dimension::none(), |product, (d, e)| {
+                product.checked_mul(&d.checked_pow(Rational::from(*e))?)
+            });
+        if product.as_ref() == Some(self.target) {
+            self.best = Some((self.current.clone(), cost));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exponents, expression};
+    use crate::units::{Error, Number, Unit, KILOGRAM, METER, PASCAL, SECOND, WATT};
+
+    #[test]
+    fn dynamic_pressure() {
+        let velocity = Unit::new(&[METER], &[SECOND]).unwrap();
+        let density = Unit::new(&[KILOGRAM], &[METER, METER, METER]).unwrap();
+        let dims = [
+            velocity.dimension(),
+            density.dimension(),
+            METER.as_unit().dimension(),
+        ];
+        assert_eq!(
+            exponents(&dims, &PASCAL.dimension()),
+            Ok(Some(vec![2, 1, 0]))
+        );
+    }
+
+    #[test]
+    fn simplest_combination() {
+        // Both m and m⋅m⋅m⁻¹ are lengths; the simpler one wins.
+        let dims = [METER.as_unit().dimension(); 3];
+        let e = exponents(&dims, &METER.as_unit().dimension())
+            .unwrap()
+            .unwrap();
+        assert_eq!(e.iter().map(|e| e.abs()).sum::<i32>(), 1);
+    }
+
+    #[test]
+    fn no_combination() {
+        let dims = [METER.as_unit().dimension(), SECOND.as_unit().dimension()];
+        assert_eq!(exponents(&dims, &WATT.dimension()), Ok(None));
+        assert_eq!(exponents(&[], &WATT.dimension()), Ok(None));
+    }
+
+    #[test]
+    fn many_quantities() {
+        // Eight of these are redundant, but dimensions pin down the others.
+        let mut dims = vec![METER.as_unit().dimension(); 9];
+        dims.push(SECOND.as_unit().dimension());
+        let velocity = Unit::new(&[METER], &[SECOND]).unwrap();
+        assert_eq!(
+            exponents(&dims, &velocity.dimension()),
+            Err(Error::TooManyQuantities(8))
+        );
+
+        dims.drain(..3);
+        let e = exponents(&dims, &velocity.dimension()).unwrap().unwrap();
+        assert_eq!(e, [1, 0, 0, 0, 0, 0, -1]);
+    }
+
+    #[test]
+    fn expression_format() {
+        let v = Number::new(10.0).with_unit(Unit::new(&[METER], &[SECOND]).unwrap());
+        let l = Number::new(2.0).with_unit(METER.as_unit());
+        assert_eq!(expression(&[v.clone(), l.clone()], &[2, 0]), "[10 m⋅s⁻¹]²");
+        assert_eq!(expression(&[v, l], &[1, -1]), "[10 m⋅s⁻¹] ⋅ [2 m]⁻¹");
+    }
+}
@@ -15,7 +15,7 @@
 // You should have received a copy of the GNU General Public License along with
 // calc. If not, see <https://www.gnu.org/licenses/>.
 
-use super::{Error, Unit};
+use super::{Error, Rational, Unit, DEG_CELSIUS, DEG_FAHRENHEIT};
 
 /// A physical property measured by a unit.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -28,10 +28,11 @@ pub enum PhysicalQuantity {
     AmountOfSubstance,
     LuminousIntensity,
     Angle,
+    Currency,
 }
 
 /// The number of different physical quantities.
-pub const NUM_PHYSICAL_QUANTITIES: usize = 8;
+pub const NUM_PHYSICAL_QUANTITIES: usize = 9;
 
 /// A unit expressed in terms of one and only one physical quantity.
 ///
@@ -90,6 +91,25 @@ impl Base {
         }
     }
 
+    /// Returns true if this unit measures a point on a scale whose zero isn't
+    /// the SI zero, like tempC. Differences between such points are measured
+    /// in an interval unit like degC.
+    #[must_use]
+    pub fn is_point(&self) -> bool {
+        self.zero.is_some_and(|z| z != 0.0)
+    }
+
+    /// Returns the interval unit for differences between points in this unit,
+    /// e.g. degC for tempC, or `None` if there isn't one.
+    #[allow(clippy::float_cmp)] // the factors are the same constants
+    #[must_use]
+    pub fn interval(&self) -> Option<Base> {
+        [DEG_CELSIUS, DEG_FAHRENHEIT]
+            .into_iter()
+            .find(|b| b.physq == self.physq && b.factor == self.factor)
+            .filter(|_| self.is_point())
+    }
+
     /// Returns a `Unit` that consists of this base unit with an exponent of +1.
     #[allow(clippy::missing_panics_doc)]
     #[must_use]
@@ -126,9 +146,11 @@ impl std::ops::Mul<Unit> for Base {
 
     /// Produces a derived unit `self`⋅`other`.
     fn mul(self, other: Unit) -> Result<Unit, Error> {
-        let mut numer = vec![self];
-        numer.extend(other.numer());
-        Unit::new(numer.as_slice(), other.denom())
+        Unit::from_exponents(
+            [(self, Rational::ONE)]
+                .into_iter()
+                .chain(other.bases().iter().copied()),
+        )
     }
 }
 
@@ -146,9 +168,11 @@ impl std::ops::Div<Unit> for Base {
 
     /// Produces a derived unit `self`⋅`other`⁻¹.
     fn div(self, other: Unit) -> Result<Unit, Error> {
-        let mut numer = vec![self];
-        numer.extend(other.denom());
-        Unit::new(numer.as_slice(), other.numer())
+        let mut exponents = vec![(self, Rational::ONE)];
+        for (base, exp) in other.bases() {
+            exponents.push((*base, exp.checked_neg().ok_or(Error::ExponentTooLarge)?));
+        }
+        Unit::from_exponents(exponents)
     }
 }
 
@@ -0,0 +1,372 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Currencies and exchange rates.
+//!
+//! Exchange rates are read from a file the user maintains; `calc` never
+//! fetches them. The file is a small subset of TOML:
+//!
+//! ```toml
+//! date = 2024-03-01
+//! base = "USD"
+//!
+//! [rates]
+//! EUR = 0.92   # one USD buys 0.92 EUR
+//! JPY = 149.5
+//! ```
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Base, PhysicalQuantity, Unit, SYMBOLS};
+
+/// Rates older than this many days are considered stale.
+pub const STALE_AFTER_DAYS: i64 = 7;
+
+/// Signs that may be used in place of currency codes, and the codes they
+/// stand for.
+const SIGNS: [(&str, &str); 4] = [("$", "USD"), ("€", "EUR"), ("£", "GBP"), ("¥", "JPY")];
+
+/// An error that occurred while loading exchange rates.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The user's home directory couldn't be determined.
+    NoHomeDirectory,
+    /// The rates file couldn't be read.
+    Io(std::io::ErrorKind),
+    /// The given line of the rates file couldn't be understood.
+    Malformed(usize),
+    /// The rates file is missing a required key.
+    MissingKey(&'static str),
+    /// A currency code isn't made of capital letters or is already a unit.
+    InvalidCode(String),
+}
+
+/// A calendar date.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Returns today's date in UTC.
+    #[must_use]
+    pub fn today() -> Date {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Date::from_days(i64::try_from(secs / 86400).unwrap_or(0))
+    }
+
+    /// Returns the number of days from `self` until `other`.
+    #[must_use]
+    pub fn days_until(&self, other: &Date) -> i64 {
+        other.days() - self.days()
+    }
+
+    /// Returns the number of days since 1970-01-01.
+    fn days(&self) -> i64 {
+        // From Howard Hinnant's `days_from_civil`.
+        let y = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = i64::from(self.month);
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Returns the date `days` days after 1970-01-01.
+    fn from_days(days: i64) -> Date {
+        // From Howard Hinnant's `civil_from_days`.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Date {
+            year: i32::try_from(year).unwrap_or(i32::MAX),
+            month: u32::try_from(month).unwrap_or(1),
+            day: u32::try_from(day).unwrap_or(1),
+        }
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = ();
+
+    /// Parses a date like `2024-03-01`.
+    fn from_str(s: &str) -> Result<Date, ()> {
+        let mut fields = s.splitn(3, '-');
+        let mut field = || {
+            fields
+                .next()
+                .ok_or(())?
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| ())
+        };
+        let (year, month, day) = (field()?, field()?, field()?);
+        let date = Date {
+            year: i32::try_from(year).map_err(|_| ())?,
+            month,
+            day,
+        };
+        if (1..=12).contains(&date.month) && (1..=31).contains(&date.day) {
+            Ok(date)
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A table of exchange rates.
+#[derive(Clone, Debug)]
+pub struct Rates {
+    /// The date the rates were recorded.
+    pub date: Date,
+    /// Units for each currency, including the base currency, followed by
+    /// units for the currency signs.
+    units: Vec<(&'static str, Unit)>,
+}
+
+impl Rates {
+    /// Returns the path of the user's rates file, `~/.config/calc/rates.toml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user's home directory can't be determined.
+    pub fn path() -> Result<PathBuf, Error> {
+        let mut path = home::home_dir().ok_or(Error::NoHomeDirectory)?;
+        path.extend([".config", "calc", "rates.toml"]);
+        Ok(path)
+    }
+
+    /// Reads exchange rates from the user's rates file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed.
+    pub fn load() -> Result<Rates, Error> {
+        let text = std::fs::read_to_string(Self::path()?).map_err(|e| Error::Io(e.kind()))?;
+        text.parse()
+    }
+
+    /// Returns the currency units, for use with `parse_with`.
+    #[must_use]
+    pub fn units(&self) -> &[(&'static str, Unit)] {
+        &self.units
+    }
+
+    /// Returns the number of currencies, including the base currency.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.units.iter().filter(|(s, _)| !is_sign(s)).count()
+    }
+
+    /// Returns true if there are no currencies. This never happens, since
+    /// there is always a base currency.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns how many days old the rates are as of `today`.
+    #[must_use]
+    pub fn age(&self, today: &Date) -> i64 {
+        self.date.days_until(today)
+    }
+
+    /// Returns true if the rates are more than `STALE_AFTER_DAYS` old as of
+    /// `today`.
+    #[must_use]
+    pub fn is_stale(&self, today: &Date) -> bool {
+        self.age(today) > STALE_AFTER_DAYS
+    }
+}
+
+impl std::str::FromStr for Rates {
+    type Err = Error;
+
+    /// Parses the contents of a rates file.
+    fn from_str(text: &str) -> Result<Rates, Error> {
+        let mut date = None;
+        let mut base = None;
+        let mut rates: Vec<(&'static str, f64)> = Vec::new();
+        let mut in_rates = false;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_rates = line == "[rates]";
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or(Error::Malformed(i + 1))?;
+            let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+            match (in_rates, key) {
+                (false, "date") => {
+                    date = Some(value.parse().map_err(|()| Error::Malformed(i + 1))?);
+                }
+                (false, "base") => base = Some(code(value)?),
+                (false, _) => {}
+                (true, _) => {
+                    let rate: f64 = value.parse().map_err(|_| Error::Malformed(i + 1))?;
+                    if !rate.is_finite() || rate <= 0.0 {
+                        return Err(Error::Malformed(i + 1));
+                    }
+                    rates.push((code(key)?, rate));
+                }
+            }
+        }
+
+        let date = date.ok_or(Error::MissingKey("date"))?;
+        let base = base.ok_or(Error::MissingKey("base"))?;
+        let mut units: Vec<(&'static str, Unit)> = std::iter::once((base, 1.0))
+            .chain(rates.into_iter().filter(|(c, _)| *c != base))
+            .map(|(c, rate)| {
+                (
+                    c,
+                    Base::new(c, PhysicalQuantity::Currency, 1.0 / rate).as_unit(),
+                )
+            })
+            .collect();
+        for (sign, c) in SIGNS {
+            if let Some((_, u)) = units.iter().find(|(s, _)| *s == c) {
+                units.push((sign, u.clone()));
+            }
+        }
+
+        Ok(Rates { date, units })
+    }
+}
+
+/// Helper for parsing. Checks that `s` is a valid currency code and returns
+/// it with a static lifetime so that it can be the symbol of a `Base`.
+fn code(s: &str) -> Result<&'static str, Error> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_uppercase()) || SYMBOLS.contains_key(s) {
+        return Err(Error::InvalidCode(s.to_string()));
+    }
+    // Rates are loaded rarely and there are few currencies, so leaking the
+    // codes is cheaper than threading a lifetime through `Base`.
+    Ok(Box::leak(s.to_string().into_boxed_str()))
+}
+
+/// Returns true if `symbol` is one of the currency signs.
+fn is_sign(symbol: &str) -> bool {
+    SIGNS.iter().any(|(sign, _)| *sign == symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Date, Error, Rates};
+    use crate::units::parse_with;
+    use approx::assert_relative_eq;
+
+    const RATES: &str = "
+        # Rates as of the first of March.
+        date = 2024-03-01
+        base = \"USD\"
+
+        [rates]
+        EUR = 0.92
+        JPY = 149.5 # yen
+    ";
+
+    #[test]
+    fn parse() {
+        let rates: Rates = RATES.parse().unwrap();
+        assert_eq!(rates.date.to_string(), "2024-03-01");
+        assert_eq!(rates.len(), 3);
+
+        let eur = parse_with("EUR", rates.units()).unwrap();
+        let usd = parse_with("$", rates.units()).unwrap();
+        assert_relative_eq!(eur.convert(100.0, &usd).unwrap(), 100.0 / 0.92);
+        assert_eq!(usd.to_string(), "USD");
+
+        let jpy = parse_with("¥", rates.units()).unwrap();
+        assert_relative_eq!(usd.convert(1.0, &jpy).unwrap(), 149.5);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "base = \"USD\"".parse::<Rates>().err(),
+            Some(Error::MissingKey("date"))
+        );
+        assert_eq!(
+            "date = 2024-03-01".parse::<Rates>().err(),
+            Some(Error::MissingKey("base"))
+        );
+        assert_eq!(
+            "date = 2024-13-01".parse::<Rates>().err(),
+            Some(Error::Malformed(1))
+        );
+        assert_eq!(
+            "date = 2024-03-01\nbase = \"USD\"\n[rates]\nEUR"
+                .parse::<Rates>()
+                .err(),
+            Some(Error::Malformed(4))
+        );
+        assert_eq!(
+            "date = 2024-03-01\nbase = \"USD\"\n[rates]\nEUR = -1"
+                .parse::<Rates>()
+                .err(),
+            Some(Error::Malformed(4))
+        );
+        assert_eq!(
+            "date = 2024-03-01\nbase = \"kg\"".parse::<Rates>().err(),
+            Some(Error::InvalidCode("kg".to_string()))
+        );
+        assert_eq!(
+            "date = 2024-03-01\nbase = \"PA\"".parse::<Rates>().err(),
+            Some(Error::InvalidCode("PA".to_string()))
+        );
+    }
+
+    #[test]
+    fn dates() {
+        let d: Date = "2024-03-01".parse().unwrap();
+        let e: Date = "2024-02-28".parse().unwrap();
+        assert_eq!(e.days_until(&d), 2);
+        assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+        assert_eq!(Date::from_days(d.days()), d);
+        assert!("2024-03".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn staleness() {
+        let rates: Rates = RATES.parse().unwrap();
+        assert!(!rates.is_stale(&"2024-03-08".parse().unwrap()));
+        assert!(rates.is_stale(&"2024-03-09".parse().unwrap()));
+        assert_eq!(rates.age(&"2024-03-09".parse().unwrap()), 8);
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+use super::{
+    base::NUM_PHYSICAL_QUANTITIES, unit::exponent_to_superscripts, PhysicalQuantity, Rational,
+};
+
+/// Symbols for each physical quantity, in the order they're displayed. These
+/// are the ISQ dimension symbols, plus ∠ for angle and ¤ for currency.
+const SYMBOLS: [(PhysicalQuantity, &str); NUM_PHYSICAL_QUANTITIES] = [
+    (PhysicalQuantity::Length, "L"),
+    (PhysicalQuantity::Mass, "M"),
+    (PhysicalQuantity::Time, "T"),
+    (PhysicalQuantity::Current, "I"),
+    (PhysicalQuantity::Temperature, "Θ"),
+    (PhysicalQuantity::AmountOfSubstance, "N"),
+    (PhysicalQuantity::LuminousIntensity, "J"),
+    (PhysicalQuantity::Angle, "∠"),
+    (PhysicalQuantity::Currency, "¤"),
+];
+
+/// The exponent of each physical quantity measured by a unit. For example,
+/// the dimension of newtons is length¹⋅mass¹⋅time⁻². Two units are
+/// commensurable if and only if they have the same dimension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Dimension([Rational; NUM_PHYSICAL_QUANTITIES]);
+
+impl Dimension {
+    /// Returns a dimension in which every exponent is zero.
+    #[must_use]
+    pub const fn none() -> Self {
+        Self([Rational::ZERO; NUM_PHYSICAL_QUANTITIES])
+    }
+
+    /// Returns true if every exponent is zero.
+    #[must_use]
+    pub fn is_none(&self) -> bool {
+        self.0.iter().all(|e| *e == Rational::ZERO)
+    }
+
+    /// Returns the exponent of each physical quantity.
+    #[must_use]
+    pub fn exponents(&self) -> &[Rational; NUM_PHYSICAL_QUANTITIES] {
+        &self.0
+    }
+
+    /// Returns the dimension of the product of quantities with dimensions
+    /// `self` and `other`, or `None` if an exponent would overflow.
+    #[must_use]
+    pub fn checked_mul(&self, other: &Dimension) -> Option<Dimension> {
+        let mut result = Dimension::none();
+        for i in 0..NUM_PHYSICAL_QUANTITIES {
+            result.0[i] = self.0[i].checked_add(other.0[i])?;
+        }
+        Some(result)
+    }
+
+    /// Returns the dimension of a quantity with dimension `self` raised to
+    /// the power of `power`, or `None` if an exponent would overflow.
+    #[must_use]
+    pub fn checked_pow(&self, power: Rational) -> Option<Dimension> {
+        let mut result = Dimension::none();
+        for i in 0..NUM_PHYSICAL_QUANTITIES {
+            result.0[i] = self.0[i].checked_mul(power)?;
+        }
+        Some(result)
+    }
+
+    /// Returns the dimension of the quotient of quantities with dimensions
+    /// `self` and `other`, or `None` if an exponent would overflow.
+    #[must_use]
+    pub fn checked_div(&self, other: &Dimension) -> Option<Dimension> {
+        let mut result = Dimension::none();
+        for i in 0..NUM_PHYSICAL_QUANTITIES {
+            result.0[i] = self.0[i].checked_add(other.0[i].checked_neg()?)?;
+        }
+        Some(result)
+    }
+}
+
+impl std::fmt::Display for Dimension {
+    /// Formats a dimension like "L¹ M¹ T⁻²". A dimension in which every
+    /// exponent is zero is formatted as "1".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if self.is_none() {
+            return write!(f, "1");
+        }
+        let factors: Vec<String> = SYMBOLS
+            .iter()
+            .filter(|(physq, _)| self[*physq] != Rational::ZERO)
+            .map(|(physq, symbol)| format!("{symbol}{}", exponent_to_superscripts(self[*physq])))
+            .collect();
+        write!(f, "{}", factors.join(" "))
+    }
+}
+
+impl Default for Dimension {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl std::ops::Index<PhysicalQuantity> for Dimension {
+    type Output = Rational;
+
+    fn index(&self, physq: PhysicalQuantity) -> &Rational {
+        &self.0[physq as usize]
+    }
+}
+
+impl std::ops::IndexMut<PhysicalQuantity> for Dimension {
+    fn index_mut(&mut self, physq: PhysicalQuantity) -> &mut Rational {
+        &mut self.0[physq as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::units::{Dimension, PhysicalQuantity, Rational, HERTZ, METER, NEWTON, VOLT};
+
+    #[test]
+    fn index() {
+        let d = NEWTON.dimension();
+        assert_eq!(d[PhysicalQuantity::Length], Rational::from(1));
+        assert_eq!(d[PhysicalQuantity::Mass], Rational::from(1));
+        assert_eq!(d[PhysicalQuantity::Time], Rational::from(-2));
+        assert_eq!(d[PhysicalQuantity::Current], Rational::from(0));
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(VOLT.dimension(), VOLT.with_constant(1e3).dimension());
+        assert_ne!(VOLT.dimension(), NEWTON.dimension());
+        assert!(!VOLT.dimension().is_none());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(NEWTON.dimension().to_string(), "L¹ M¹ T⁻²");
+        assert_eq!(VOLT.dimension().to_string(), "L² M¹ T⁻³ I⁻¹");
+        assert_eq!(Dimension::none().to_string(), "1");
+        let d = HERTZ.root(Rational::from(2)).unwrap().dimension();
+        assert_eq!(d.to_string(), "T⁻¹ᐟ²");
+    }
+
+    #[test]
+    fn checked_mul_and_pow() {
+        let m = METER.as_unit().dimension();
+        let d = NEWTON.dimension().checked_mul(&m).unwrap();
+        assert_eq!(d.to_string(), "L² M¹ T⁻²");
+        let d = d.checked_pow(Rational::new(-1, 2).unwrap()).unwrap();
+        assert_eq!(d.to_string(), "L⁻¹ M⁻¹ᐟ² T¹");
+    }
+
+    #[test]
+    fn checked_div() {
+        let d = NEWTON.dimension().checked_div(&METER.as_unit().dimension());
+        assert_eq!(d.unwrap().to_string(), "M¹ T⁻²");
+    }
+}
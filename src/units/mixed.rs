@@ -0,0 +1,266 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Quantities expressed in more than one unit, like 5 ft 10 in, 12:34:56, or
+//! 41°24'12".
+
+use super::{Base, Error, Number, Unit, DEGREE, HOUR};
+
+/// Relative tolerance within which a quotient is considered to be a whole
+/// number when splitting. This keeps e.g. 2 hr from splitting into 1 hr
+/// 59 min 59.999999 s due to rounding error.
+const TOLERANCE: f64 = 1e-9;
+
+/// Splits `x` across `units`, which should be ordered from largest to
+/// smallest. Every part but the last is a whole number; the last part holds
+/// the remainder. For example, 1.8 m split across ft and in is 5 ft
+/// 10.866142 in. All parts have the same sign as `x`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - `units` is empty;
+/// - `x` is dimensionless or incommensurable with any of `units`; or,
+/// - there is more than one unit and any of them is a temperature like
+///   tempC.
+pub fn split(x: &Number, units: &[Unit]) -> Result<Vec<Number>, Error> {
+    let Some(last) = units.last() else {
+        return Err(Error::IncommensurableUnits(
+            x.unit.clone().map(Box::new),
+            None,
+        ));
+    };
+    let Some(unit) = &x.unit else {
+        return Err(Error::IncommensurableUnits(
+            None,
+            Some(Box::new(last.clone())),
+        ));
+    };
+
+    let mut remainder = unit.convert(x.value, last)?;
+    let mut parts = Vec::with_capacity(units.len());
+
+    for u in &units[..units.len() - 1] {
+        if !u.is_commensurable_with(last) {
+            return Err(Error::IncommensurableUnits(
+                Some(Box::new(unit.clone())),
+                Some(Box::new(u.clone())),
+            ));
+        }
+        // A temperature like tempC is a point on a scale, not a size, so it
+        // can't be counted in another unit.
+        if let Some(base) = [u, last]
+            .into_iter()
+            .find_map(|u| u.sole_base().filter(Base::is_point))
+        {
+            return Err(Error::NonzeroZeroPoint(base));
+        }
+        let size = u.convert(1.0, last)?;
+        let quotient = remainder / size;
+        let whole = if (quotient - quotient.round()).abs() <= TOLERANCE * quotient.abs().max(1.0) {
+            quotient.round()
+        } else {
+            quotient.trunc()
+        };
+        remainder -= whole * size;
+        if remainder.abs() <= TOLERANCE * size.abs() {
+            remainder = 0.0;
+        }
+        parts.push(Number::new(whole).with_unit(u.clone()));
+    }
+
+    parts.push(Number::new(remainder).with_unit(last.clone()));
+    Ok(parts)
+}
+
+/// Parses a sexagesimal quantity. Times like `12:34` and `12:34:56.7` are
+/// hours, minutes, and seconds and produce a number in hours. Angles like
+/// `41°`, `41°24'`, and `41°24'12.5"` are degrees, minutes, and seconds and
+/// produce a number in degrees. Returns `None` if `s` isn't one of these.
+#[must_use]
+pub fn parse(s: &str) -> Option<Number> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+
+    let (fields, unit) = if s.contains(':') {
+        (time_fields(s)?, HOUR.as_unit())
+    } else if s.contains('°') {
+        (angle_fields(s)?, DEGREE.as_unit())
+    } else {
+        return None;
+    };
+
+    let mut value = 0.0;
+    for (i, field) in fields.iter().enumerate().rev() {
+        let is_last = i == fields.len() - 1;
+        if (i > 0 && *field >= 60.0) || (!is_last && field.fract() != 0.0) {
+            return None;
+        }
+        value = value / 60.0 + field;
+    }
+
+    Some(Number::new(if negative { -value } else { value }).with_unit(unit))
+}
+
+/// Helper for `parse`. Splits `h:m` or `h:m:s` into fields.
+fn time_fields(s: &str) -> Option<Vec<f64>> {
+    let fields: Vec<&str> = s.split(':').collect();
+    if fields.len() > 3 {
+        return None;
+    }
+    fields.into_iter().map(field).collect()
+}
+
+/// Helper for `parse`. Splits `d°`, `d°m'`, or `d°m's"` into fields. Primes
+/// (′ and ″) may be used in place of apostrophes and quotation marks.
+fn angle_fields(s: &str) -> Option<Vec<f64>> {
+    let mut fields = Vec::new();
+    let mut rest = s;
+    for marks in [&['°'][..], &['\'', '′'], &['"', '″']] {
+        if rest.is_empty() {
+            break;
+        }
+        let (f, r) = rest.split_once(|c| marks.contains(&c))?;
+        fields.push(field(f)?);
+        rest = r;
+    }
+    if rest.is_empty() {
+        Some(fields)
+    } else {
+        None
+    }
+}
+
+/// Helper for `parse`. Parses one unsigned field.
+fn field(s: &str) -> Option<f64> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, split};
+    use crate::units::{
+        Error, Number, Unit, DEGREE, FOOT, HOUR, INCH, KELVIN, METER, MILLIKELVIN, MINUTE, SECOND,
+        TEMP_CELSIUS, TEMP_FAHRENHEIT,
+    };
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn split_feet_and_inches() {
+        let x = Number::new(1.8).with_unit(METER.as_unit());
+        let parts = split(&x, &[FOOT.as_unit(), INCH.as_unit()]).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].value, 5.0);
+        assert_eq!(parts[0].unit, Some(FOOT.as_unit()));
+        assert_relative_eq!(parts[1].value, 10.866_141_732_283_5, epsilon = 1e-9);
+        assert_eq!(parts[1].unit, Some(INCH.as_unit()));
+    }
+
+    #[test]
+    fn split_exact() {
+        let x = Number::new(2.0).with_unit(HOUR.as_unit());
+        let units = [HOUR.as_unit(), MINUTE.as_unit(), SECOND.as_unit()];
+        let values: Vec<f64> = split(&x, &units).unwrap().iter().map(|n| n.value).collect();
+        assert_eq!(values, [2.0, 0.0, 0.0]);
+
+        let x = Number::new(-7384.5).with_unit(SECOND.as_unit());
+        let values: Vec<f64> = split(&x, &units).unwrap().iter().map(|n| n.value).collect();
+        assert_eq!(values, [-2.0, -3.0, -4.5]);
+    }
+
+    #[test]
+    fn split_errors() {
+        let x = Number::new(2.0).with_unit(HOUR.as_unit());
+        assert!(split(&x, &[FOOT.as_unit(), INCH.as_unit()]).is_err());
+        assert!(split(&x, &[HOUR.as_unit(), INCH.as_unit()]).is_err());
+        assert!(split(&x, &[]).is_err());
+        assert!(split(&Number::new(2.0), &[HOUR.as_unit()]).is_err());
+    }
+
+    #[test]
+    fn split_temperatures() {
+        let x = Number::new(100.0).with_unit(TEMP_CELSIUS.as_unit());
+        assert!(matches!(
+            split(&x, &[TEMP_FAHRENHEIT.as_unit(), KELVIN.as_unit()]),
+            Err(Error::NonzeroZeroPoint(b)) if b == TEMP_FAHRENHEIT
+        ));
+        assert!(matches!(
+            split(&x, &[KELVIN.as_unit(), TEMP_FAHRENHEIT.as_unit()]),
+            Err(Error::NonzeroZeroPoint(b)) if b == TEMP_FAHRENHEIT
+        ));
+
+        // An absolute temperature can still be split across absolute units.
+        let parts = split(&x, &[KELVIN.as_unit(), MILLIKELVIN.as_unit()]).unwrap();
+        assert_eq!(parts[0].value, 373.0);
+        assert_relative_eq!(parts[1].value, 150.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn parse_times() {
+        let x = parse("12:34:56").unwrap();
+        assert_relative_eq!(x.value, 12.0 + 34.0 / 60.0 + 56.0 / 3600.0);
+        assert_eq!(x.unit, Some(HOUR.as_unit()));
+        assert_relative_eq!(parse("1:30").unwrap().value, 1.5);
+        assert_relative_eq!(parse("-0:00:36").unwrap().value, -0.01);
+        assert_relative_eq!(parse("0:0:1.8").unwrap().value, 0.0005);
+    }
+
+    #[test]
+    fn parse_angles() {
+        let x = parse("41°24'12\"").unwrap();
+        assert_relative_eq!(x.value, 41.403_333_333_333_33);
+        assert_eq!(x.unit, Some(DEGREE.as_unit()));
+        assert_relative_eq!(parse("41°24′12″").unwrap().value, 41.403_333_333_333_33);
+        assert_relative_eq!(parse("-41°30'").unwrap().value, -41.5);
+        assert_relative_eq!(parse("90°").unwrap().value, 90.0);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for s in [
+            "12",
+            "12:",
+            ":12",
+            "1:2:3:4",
+            "1:60",
+            "1:2.5:3",
+            "1:-2",
+            "41°60'",
+            "41'",
+            "41°24'x",
+            "41.5°24'",
+            "deg",
+            "°",
+        ] {
+            assert!(parse(s).is_none(), "{s}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let x = parse("12:34:56").unwrap();
+        let units: [Unit; 3] = [HOUR.as_unit(), MINUTE.as_unit(), SECOND.as_unit()];
+        let values: Vec<f64> = split(&x, &units).unwrap().iter().map(|n| n.value).collect();
+        assert_eq!(values[..2], [12.0, 34.0]);
+        assert_relative_eq!(values[2], 56.0, epsilon = 1e-6);
+    }
+}
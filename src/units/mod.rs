@@ -18,14 +18,28 @@
 //! Unit conversion.
 #![allow(dead_code)]
 
+use std::collections::HashMap;
+
 use once_cell::sync::Lazy;
 
+pub mod analysis;
 mod base;
+pub mod currency;
+mod dimension;
+pub mod mixed;
 mod number;
+mod parse;
+mod prefix;
+mod rational;
+mod system;
 mod unit;
 
 pub use base::{Base, PhysicalQuantity};
+pub use dimension::Dimension;
 pub use number::Number;
+pub use parse::parse_with;
+pub use rational::Rational;
+pub use system::{System, CGS, SI, US_CUSTOMARY};
 pub use unit::Unit;
 
 #[derive(Debug, PartialEq)]
@@ -33,11 +47,17 @@ pub enum Error {
     IncommensurableUnits(Option<Box<Unit>>, Option<Box<Unit>>),
     UninvertableUnits(Box<Unit>),
     NonzeroZeroPoint(Base),
+    TemperaturesAdded(Base, Base),
+    TemperatureSubtracted(Base, Base),
     ExponentHasUnits,
-    ExponentNotAnInteger,
+    ExponentNotRational,
+    ExponentTooLarge,
+    ExponentIsZero,
     DegreeHasUnits,
-    DegreeNotAnInteger,
-    UnitNotDivisible,
+    DegreeNotRational,
+    DegreeIsZero,
+    UnknownUnit(String),
+    TooManyQuantities(usize),
 }
 
 #[allow(clippy::enum_glob_use)]
@@ -74,8 +94,10 @@ pub static MIL: Base = Base::new("mil", Length, 0.000_304_8 / 12.0);
 pub static YARD: Base = Base::new("yd", Length, 0.3048 * 3.0);
 
 // More masses
+pub static GRAM: Base = Base::new("g", Mass, 1e-3);
 pub static POUND_MASS: Base = Base::new("lb", Mass, 0.453_592_37);
 pub static OUNCE: Base = Base::new("oz", Mass, 0.028_349_523_125);
+pub static TONNE: Base = Base::new("t", Mass, 1e3);
 
 // More temperatures
 pub static RANKINE: Base = Base::new("R", Temperature, 5.0 / 9.0);
@@ -86,6 +108,11 @@ pub static TEMP_FAHRENHEIT: Base = Base::new("tempF", Temperature, 5.0 / 9.0).wi
 
 // More angles
 pub static DEGREE: Base = Base::new("deg", Angle, std::f64::consts::PI / 180.0);
+pub static GRADIAN: Base = Base::new("grad", Angle, std::f64::consts::PI / 200.0);
+pub static TURN: Base = Base::new("turn", Angle, std::f64::consts::TAU);
+pub static ARCMINUTE: Base = Base::new("arcmin", Angle, std::f64::consts::PI / 10_800.0);
+pub static ARCSECOND: Base = Base::new("arcsec", Angle, std::f64::consts::PI / 648_000.0);
+pub static MILLIARCSECOND: Base = Base::new("mas", Angle, std::f64::consts::PI / 648_000_000.0);
 
 // Energy
 pub static JOULE: Lazy<Unit> = Lazy::new(|| {
@@ -93,6 +120,15 @@ pub static JOULE: Lazy<Unit> = Lazy::new(|| {
         .unwrap()
         .with_symbol("J")
 });
+pub static WATT_HOUR: Lazy<Unit> = Lazy::new(|| JOULE.with_constant(3600.0).with_symbol("Wh"));
+pub static KILOWATT_HOUR: Lazy<Unit> = Lazy::new(|| JOULE.with_constant(3.6e6).with_symbol("kWh"));
+/// Foot-pound force, the US customary unit of energy. Its symbol can't be
+/// entered as a single word, but `ft.lbf` produces the same unit.
+pub static FOOT_POUND_FORCE: Lazy<Unit> =
+    Lazy::new(|| (&*POUND_FORCE * FOOT).unwrap().with_symbol("ft⋅lbf"));
+
+// Frequency
+pub static HERTZ: Lazy<Unit> = Lazy::new(|| Unit::new(&[], &[SECOND]).unwrap().with_symbol("Hz"));
 
 // Force
 pub static NEWTON: Lazy<Unit> = Lazy::new(|| {
@@ -101,9 +137,9 @@ pub static NEWTON: Lazy<Unit> = Lazy::new(|| {
         .with_symbol("N")
 });
 pub static POUND_FORCE: Lazy<Unit> = Lazy::new(|| {
-    Unit::new(&[POUND_MASS, METER], &[SECOND, SECOND])
+    Unit::new(&[POUND_MASS, FOOT], &[SECOND, SECOND])
         .unwrap()
-        .with_constant(9.80665) // standard acceleration due to Earth's gravity
+        .with_constant(9.80665 / 0.3048) // standard acceleration due to Earth's gravity
         .with_symbol("lbf")
 });
 
@@ -117,8 +153,9 @@ pub static PASCAL: Lazy<Unit> = Lazy::new(|| {
         .with_symbol("Pa")
 });
 pub static PSI: Lazy<Unit> = Lazy::new(|| {
-    ((&*POUND_FORCE / INCH).unwrap() / INCH)
+    ((&*POUND_FORCE / FOOT).unwrap() / FOOT)
         .unwrap()
+        .with_constant(POUND_FORCE.constant() * 144.0) // 144 in² per ft²
         .with_symbol("psi")
 });
 
@@ -150,6 +187,76 @@ pub static TESLA: Lazy<Unit> = Lazy::new(|| {
         .with_symbol("T")
 });
 
+// CGS
+pub static DYNE: Lazy<Unit> = Lazy::new(|| {
+    Unit::new(&[GRAM, CENTIMETER], &[SECOND, SECOND])
+        .unwrap()
+        .with_symbol("dyn")
+});
+pub static ERG: Lazy<Unit> = Lazy::new(|| (&*DYNE * CENTIMETER).unwrap().with_symbol("erg"));
+pub static BARYE: Lazy<Unit> = Lazy::new(|| {
+    ((&*DYNE / CENTIMETER).unwrap() / CENTIMETER)
+        .unwrap()
+        .with_symbol("Ba")
+});
+pub static GAL: Lazy<Unit> = Lazy::new(|| {
+    Unit::new(&[CENTIMETER], &[SECOND, SECOND])
+        .unwrap()
+        .with_symbol("Gal")
+});
+pub static POISE: Lazy<Unit> = Lazy::new(|| {
+    Unit::new(&[GRAM], &[CENTIMETER, SECOND])
+        .unwrap()
+        .with_symbol("P")
+});
+pub static STOKES: Lazy<Unit> = Lazy::new(|| {
+    Unit::new(&[CENTIMETER, CENTIMETER], &[SECOND])
+        .unwrap()
+        .with_symbol("St")
+});
+pub static GAUSS: Lazy<Unit> = Lazy::new(|| {
+    Unit::new(&[GRAM], &[SECOND, SECOND, AMPERE])
+        .unwrap()
+        .with_constant(0.1) // 10⁻⁴ T
+        .with_symbol("G")
+});
+pub static MAXWELL: Lazy<Unit> = Lazy::new(|| {
+    Unit::new(&[GRAM, CENTIMETER, CENTIMETER], &[SECOND, SECOND, AMPERE])
+        .unwrap()
+        .with_constant(0.1) // 10⁻⁸ Wb
+        .with_symbol("Mx")
+});
+
+// Ratios. These are dimensionless; a number entered in one of them is
+// converted to a plain number, but `into` can express a number in them.
+pub static PERCENT: Lazy<Unit> = Lazy::new(|| ratio(1e-2, "%"));
+pub static PERMILLE: Lazy<Unit> = Lazy::new(|| ratio(1e-3, "permille"));
+pub static PPM: Lazy<Unit> = Lazy::new(|| ratio(1e-6, "ppm"));
+pub static PPB: Lazy<Unit> = Lazy::new(|| ratio(1e-9, "ppb"));
+
+/// Helper for defining ratios.
+fn ratio(constant: f64, symbol: &str) -> Unit {
+    Unit::new(&[], &[])
+        .unwrap()
+        .with_constant(constant)
+        .with_symbol(symbol)
+}
+
+/// Named derived units that the results of arithmetic can be expressed in
+/// automatically. No two of these units are commensurable with each other.
+pub static NAMEABLE: Lazy<Vec<Unit>> = Lazy::new(|| {
+    vec![
+        JOULE.clone(),
+        NEWTON.clone(),
+        WATT.clone(),
+        PASCAL.clone(),
+        VOLT.clone(),
+        OHM.clone(),
+        FARAD.clone(),
+        TESLA.clone(),
+    ]
+});
+
 // SI prefixes
 pub static PETASECOND: Base = Base::new("Ps", Time, 1e15);
 pub static TERASECOND: Base = Base::new("Ts", Time, 1e12);
@@ -174,15 +281,15 @@ pub static NANOMETER: Base = Base::new("nm", Length, 1e-9);
 pub static PICOMETER: Base = Base::new("pm", Length, 1e-12);
 pub static FEMTOMETER: Base = Base::new("fm", Length, 1e-15);
 
-pub static PETAGRAM: Base = Base::new("Pg", Mass, 1e15);
-pub static TERAGRAM: Base = Base::new("Tg", Mass, 1e12);
-pub static GIGAGRAM: Base = Base::new("Gg", Mass, 1e9);
-pub static MEGAGRAM: Base = Base::new("Mg", Mass, 1e6);
-pub static MILLIGRAM: Base = Base::new("mg", Mass, 1e-3);
-pub static MICROGRAM: Base = Base::new("ug", Mass, 1e-6);
-pub static NANOGRAM: Base = Base::new("ng", Mass, 1e-9);
-pub static PICOGRAM: Base = Base::new("pg", Mass, 1e-12);
-pub static FEMTOGRAM: Base = Base::new("fg", Mass, 1e-15);
+pub static PETAGRAM: Base = Base::new("Pg", Mass, 1e12);
+pub static TERAGRAM: Base = Base::new("Tg", Mass, 1e9);
+pub static GIGAGRAM: Base = Base::new("Gg", Mass, 1e6);
+pub static MEGAGRAM: Base = Base::new("Mg", Mass, 1e3);
+pub static MILLIGRAM: Base = Base::new("mg", Mass, 1e-6);
+pub static MICROGRAM: Base = Base::new("ug", Mass, 1e-9);
+pub static NANOGRAM: Base = Base::new("ng", Mass, 1e-12);
+pub static PICOGRAM: Base = Base::new("pg", Mass, 1e-15);
+pub static FEMTOGRAM: Base = Base::new("fg", Mass, 1e-18);
 
 pub static PETAAMPERE: Base = Base::new("PA", Current, 1e15);
 pub static TERAAMPERE: Base = Base::new("TA", Current, 1e12);
@@ -250,6 +357,17 @@ pub static NANOJOULE: Lazy<Unit> = Lazy::new(|| JOULE.with_constant(1e-9).with_s
 pub static PICOJOULE: Lazy<Unit> = Lazy::new(|| JOULE.with_constant(1e-12).with_symbol("pJ"));
 pub static FEMTOJOULE: Lazy<Unit> = Lazy::new(|| JOULE.with_constant(1e-15).with_symbol("fJ"));
 
+pub static PETAHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e15).with_symbol("PHz"));
+pub static TERAHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e12).with_symbol("THz"));
+pub static GIGAHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e9).with_symbol("GHz"));
+pub static MEGAHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e6).with_symbol("MHz"));
+pub static KILOHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e3).with_symbol("kHz"));
+pub static MILLIHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e-3).with_symbol("mHz"));
+pub static MICROHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e-6).with_symbol("uHz"));
+pub static NANOHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e-9).with_symbol("nHz"));
+pub static PICOHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e-12).with_symbol("pHz"));
+pub static FEMTOHERTZ: Lazy<Unit> = Lazy::new(|| HERTZ.with_constant(1e-15).with_symbol("fHz"));
+
 pub static PETANEWTON: Lazy<Unit> = Lazy::new(|| NEWTON.with_constant(1e15).with_symbol("PN"));
 pub static TERANEWTON: Lazy<Unit> = Lazy::new(|| NEWTON.with_constant(1e12).with_symbol("TN"));
 pub static GIGANEWTON: Lazy<Unit> = Lazy::new(|| NEWTON.with_constant(1e9).with_symbol("GN"));
@@ -326,3 +444,264 @@ pub static MICROTESLA: Lazy<Unit> = Lazy::new(|| TESLA.with_constant(1e-6).with_
 pub static NANOTESLA: Lazy<Unit> = Lazy::new(|| TESLA.with_constant(1e-9).with_symbol("nT"));
 pub static PICOTESLA: Lazy<Unit> = Lazy::new(|| TESLA.with_constant(1e-12).with_symbol("pT"));
 pub static FEMTOTESLA: Lazy<Unit> = Lazy::new(|| TESLA.with_constant(1e-15).with_symbol("fT"));
+
+/// Every unit that can be entered by its symbol, keyed by symbol.
+pub static SYMBOLS: Lazy<HashMap<&'static str, Unit>> = Lazy::new(|| {
+    let bases: [&Base; 112] = [
+        // SI bases
+        &SECOND,
+        &METER,
+        &KILOGRAM,
+        &AMPERE,
+        &KELVIN,
+        &MOLE,
+        &CANDELA,
+        &RADIAN,
+        // Time
+        &DAY,
+        &HOUR,
+        &MINUTE,
+        // Length
+        &INCH,
+        &FOOT,
+        &MILE,
+        &NAUTICAL_MILE,
+        &MIL,
+        &YARD,
+        // Mass
+        &GRAM,
+        &POUND_MASS,
+        &OUNCE,
+        &TONNE,
+        // Temperature
+        &RANKINE,
+        &DEG_CELSIUS,
+        &DEG_FAHRENHEIT,
+        &TEMP_CELSIUS,
+        &TEMP_FAHRENHEIT,
+        // Angle
+        &DEGREE,
+        &GRADIAN,
+        &TURN,
+        &ARCMINUTE,
+        &ARCSECOND,
+        &MILLIARCSECOND,
+        // SI prefixes
+        &PETASECOND,
+        &TERASECOND,
+        &GIGASECOND,
+        &MEGASECOND,
+        &KILOSECOND,
+        &MILLISECOND,
+        &MICROSECOND,
+        &NANOSECOND,
+        &PICOSECOND,
+        &FEMTOSECOND,
+        &PETAMETER,
+        &TERAMETER,
+        &GIGAMETER,
+        &MEGAMETER,
+        &KILOMETER,
+        &CENTIMETER,
+        &MILLIMETER,
+        &MICROMETER,
+        &NANOMETER,
+        &PICOMETER,
+        &FEMTOMETER,
+        &PETAGRAM,
+        &TERAGRAM,
+        &GIGAGRAM,
+        &MEGAGRAM,
+        &MILLIGRAM,
+        &MICROGRAM,
+        &NANOGRAM,
+        &PICOGRAM,
+        &FEMTOGRAM,
+        &PETAAMPERE,
+        &TERAAMPERE,
+        &GIGAAMPERE,
+        &MEGAAMPERE,
+        &KILOAMPERE,
+        &MILLIAMPERE,
+        &MICROAMPERE,
+        &NANOAMPERE,
+        &PICOAMPERE,
+        &FEMTOAMPERE,
+        &PETAKELVIN,
+        &TERAKELVIN,
+        &GIGAKELVIN,
+        &MEGAKELVIN,
+        &KILOKELVIN,
+        &MILLIKELVIN,
+        &MICROKELVIN,
+        &NANOKELVIN,
+        &PICOKELVIN,
+        &FEMTOKELVIN,
+        &PETAMOLE,
+        &TERAMOLE,
+        &GIGAMOLE,
+        &MEGAMOLE,
+        &KILOMOLE,
+        &MILLIMOLE,
+        &MICROMOLE,
+        &NANOMOLE,
+        &PICOMOLE,
+        &FEMTOMOLE,
+        &PETACANDELA,
+        &TERACANDELA,
+        &GIGACANDELA,
+        &MEGACANDELA,
+        &KILOCANDELA,
+        &MILLICANDELA,
+        &MICROCANDELA,
+        &NANOCANDELA,
+        &PICOCANDELA,
+        &FEMTOCANDELA,
+        &PETARADIAN,
+        &TERARADIAN,
+        &GIGARADIAN,
+        &MEGARADIAN,
+        &KILORADIAN,
+        &MILLIRADIAN,
+        &MICRORADIAN,
+        &NANORADIAN,
+        &PICORADIAN,
+        &FEMTORADIAN,
+    ];
+    let named: [&Unit; 115] = [
+        // Energy
+        &*JOULE,
+        &*WATT_HOUR,
+        &*KILOWATT_HOUR,
+        // Frequency
+        &*HERTZ,
+        // Force
+        &*NEWTON,
+        &*POUND_FORCE,
+        // Power
+        &*WATT,
+        // Pressure
+        &*PASCAL,
+        &*PSI,
+        // Electromagnetic
+        &*VOLT,
+        &*OHM,
+        &*FARAD,
+        &*TESLA,
+        // CGS
+        &*DYNE,
+        &*ERG,
+        &*BARYE,
+        &*GAL,
+        &*POISE,
+        &*STOKES,
+        &*GAUSS,
+        &*MAXWELL,
+        // Ratios
+        &*PERCENT,
+        &*PERMILLE,
+        &*PPM,
+        &*PPB,
+        // SI prefixes
+        &*PETAJOULE,
+        &*TERAJOULE,
+        &*GIGAJOULE,
+        &*MEGAJOULE,
+        &*KILOJOULE,
+        &*MILLIJOULE,
+        &*MICROJOULE,
+        &*NANOJOULE,
+        &*PICOJOULE,
+        &*FEMTOJOULE,
+        &*PETAHERTZ,
+        &*TERAHERTZ,
+        &*GIGAHERTZ,
+        &*MEGAHERTZ,
+        &*KILOHERTZ,
+        &*MILLIHERTZ,
+        &*MICROHERTZ,
+        &*NANOHERTZ,
+        &*PICOHERTZ,
+        &*FEMTOHERTZ,
+        &*PETANEWTON,
+        &*TERANEWTON,
+        &*GIGANEWTON,
+        &*MEGANEWTON,
+        &*KILONEWTON,
+        &*MILLINEWTON,
+        &*MICRONEWTON,
+        &*NANONEWTON,
+        &*PICONEWTON,
+        &*FEMTONEWTON,
+        &*PETAWATT,
+        &*TERAWATT,
+        &*GIGAWATT,
+        &*MEGAWATT,
+        &*KILOWATT,
+        &*MILLIWATT,
+        &*MICROWATT,
+        &*NANOWATT,
+        &*PICOWATT,
+        &*FEMTOWATT,
+        &*PETAPASCAL,
+        &*TERAPASCAL,
+        &*GIGAPASCAL,
+        &*MEGAPASCAL,
+        &*KILOPASCAL,
+        &*MILLIPASCAL,
+        &*MICROPASCAL,
+        &*NANOPASCAL,
+        &*PICOPASCAL,
+        &*FEMTOPASCAL,
+        &*PETAVOLT,
+        &*TERAVOLT,
+        &*GIGAVOLT,
+        &*MEGAVOLT,
+        &*KILOVOLT,
+        &*MILLIVOLT,
+        &*MICROVOLT,
+        &*NANOVOLT,
+        &*PICOVOLT,
+        &*FEMTOVOLT,
+        &*PETAOHM,
+        &*TERAOHM,
+        &*GIGAOHM,
+        &*MEGAOHM,
+        &*KILOOHM,
+        &*MILLIOHM,
+        &*MICROOHM,
+        &*NANOOHM,
+        &*PICOOHM,
+        &*FEMTOOHM,
+        &*PETAFARAD,
+        &*TERAFARAD,
+        &*GIGAFARAD,
+        &*MEGAFARAD,
+        &*KILOFARAD,
+        &*MILLIFARAD,
+        &*MICROFARAD,
+        &*NANOFARAD,
+        &*PICOFARAD,
+        &*FEMTOFARAD,
+        &*PETATESLA,
+        &*TERATESLA,
+        &*GIGATESLA,
+        &*MEGATESLA,
+        &*KILOTESLA,
+        &*MILLITESLA,
+        &*MICROTESLA,
+        &*NANOTESLA,
+        &*PICOTESLA,
+        &*FEMTOTESLA,
+    ];
+
+    bases
+        .into_iter()
+        .map(|b| (b.symbol, b.as_unit()))
+        .chain(
+            named
+                .into_iter()
+                .filter_map(|u| u.symbol.as_deref().map(|symbol| (symbol, u.clone()))),
+        )
+        .collect()
+});
@@ -17,9 +17,7 @@
 
 //! Arithmetic with units.
 
-use itertools::{any, Itertools};
-
-use super::{Base, Error, Unit};
+use super::{prefix, Base, Error, Rational, Unit};
 
 /// A number with an optional unit.
 #[derive(Clone, Debug)]
@@ -39,7 +37,7 @@ impl Number {
     /// No unit conversion is performed.
     #[must_use]
     pub fn with_unit(&self, unit: Unit) -> Number {
-        if unit.numer().is_empty() && unit.denom().is_empty() {
+        if unit.is_scalar() {
             Number::new(self.value * unit.constant())
         } else {
             Number {
@@ -49,6 +47,183 @@ impl Number {
         }
     }
 
+    /// Returns this number expressed in `unit`, which must be a ratio like `%`
+    /// (a unit without base units). Unlike `with_unit`, the unit is kept so
+    /// that the number is displayed in it: 0.05 in `%` is `[5 %]`.
+    #[must_use]
+    pub fn in_ratio(&self, unit: Unit) -> Number {
+        Number {
+            value: self.value / unit.constant(),
+            unit: Some(unit),
+        }
+    }
+
+    /// Returns this number as a plain number if it's in a ratio like `%`, so
+    /// that `[5 %]` is 0.05. Other numbers are returned unchanged.
+    #[must_use]
+    pub fn folded(self) -> Number {
+        match &self.unit {
+            Some(unit) if unit.is_scalar() => Number::new(self.value * unit.constant()),
+            _ => self,
+        }
+    }
+
+    /// Returns the value of this number converted to `unit`, or to a plain
+    /// number if `unit` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this number's units are incommensurable with
+    /// `unit`.
+    pub fn value_in(&self, unit: Option<&Unit>) -> Result<f64, Error> {
+        match (&self.unit, unit) {
+            (Some(from), Some(to)) => from.convert(self.value, to),
+            (None, None) => Ok(self.value),
+            (Some(from), None) if from.is_scalar() => Ok(self.value * from.constant()),
+            (None, Some(to)) if to.is_scalar() => Ok(self.value / to.constant()),
+            (from, to) => Err(Error::IncommensurableUnits(
+                from.clone().map(Box::new),
+                to.cloned().map(Box::new),
+            )),
+        }
+    }
+
+    /// Compares this number with another number in commensurable units.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the numbers have incommensurable units.
+    pub fn compare(&self, other: &Number) -> Result<std::cmp::Ordering, Error> {
+        let other = other.value_in(self.unit.as_ref())?;
+        Ok(self.value.total_cmp(&other))
+    }
+
+    /// Returns a `Number` with the same unit as this one and its value mapped
+    /// through `f`.
+    fn map(&self, f: impl FnOnce(f64) -> f64) -> Number {
+        Number {
+            value: f(self.value),
+            unit: self.unit.clone(),
+        }
+    }
+
+    /// Fails if this number is a point on a scale whose zero isn't the SI
+    /// zero, like tempC, since its sign says nothing about its magnitude.
+    fn signed(&self) -> Result<&Number, Error> {
+        match self.unit.as_ref().and_then(Unit::sole_base) {
+            Some(base) if base.is_point() => Err(Error::NonzeroZeroPoint(base)),
+            _ => Ok(self),
+        }
+    }
+
+    /// Multiplies this number by a number without units. A unit with a
+    /// symbol, like psi or kJ, is kept as it is; any other unit is simplified
+    /// the same way multiplication by a number with units does.
+    fn times(&self, k: f64) -> Result<Number, Error> {
+        match self.signed()?.unit.as_ref() {
+            Some(u) if u.symbol.is_none() => Number::new(self.value * k * u.constant())
+                .with_unit(u.with_constant(1.0))
+                .simplified(),
+            _ => Ok(self.map(|v| v * k)),
+        }
+    }
+
+    /// Returns the absolute value of this number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this number is a temperature like tempC.
+    pub fn abs(&self) -> Result<Number, Error> {
+        Ok(self.signed()?.map(f64::abs))
+    }
+
+    /// Returns the largest whole number of this number's units less than or
+    /// equal to it.
+    #[must_use]
+    pub fn floor(&self) -> Number {
+        self.map(f64::floor)
+    }
+
+    /// Returns the smallest whole number of this number's units greater than
+    /// or equal to it.
+    #[must_use]
+    pub fn ceil(&self) -> Number {
+        self.map(f64::ceil)
+    }
+
+    /// Returns the nearest whole number of this number's units. Halfway cases
+    /// are rounded away from zero.
+    #[must_use]
+    pub fn round(&self) -> Number {
+        self.map(f64::round)
+    }
+
+    /// Returns the whole part of this number.
+    #[must_use]
+    pub fn trunc(&self) -> Number {
+        self.map(f64::trunc)
+    }
+
+    /// Returns the fractional part of this number. The result has the same
+    /// sign as this number.
+    #[must_use]
+    pub fn fract(&self) -> Number {
+        self.map(f64::fract)
+    }
+
+    /// Returns -1, 0, or 1 according to the sign of this number.
+    #[must_use]
+    pub fn signum(&self) -> f64 {
+        if self.value == 0.0 {
+            0.0
+        } else {
+            self.value.signum()
+        }
+    }
+
+    /// Returns this number rounded to the nearest multiple of `step`, in the
+    /// units of `step`. A step of zero leaves the value unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this number and `step` have incommensurable units.
+    pub fn round_to(&self, step: &Number) -> Result<Number, Error> {
+        let value = self.value_in(step.unit.as_ref())?;
+        Ok(step.map(|s| {
+            if s == 0.0 {
+                value
+            } else {
+                (value / s).round() * s
+            }
+        }))
+    }
+
+    /// Returns this number rounded to `digits` significant figures. Since an
+    /// `f64` has at most 17 significant figures, larger values of `digits`
+    /// leave the number unchanged.
+    #[must_use]
+    pub fn round_sig(&self, digits: u32) -> Number {
+        self.map(|v| {
+            if v == 0.0 || !v.is_finite() || digits > 17 {
+                return v;
+            }
+            // Scale so that the digits to keep are left of the decimal point,
+            // dividing rather than multiplying by negative powers of ten
+            // because they aren't exactly representable.
+            #[allow(clippy::cast_possible_truncation)]
+            let magnitude = v.abs().log10().floor() as i32;
+            #[allow(clippy::cast_possible_wrap)]
+            let shift = digits as i32 - 1 - magnitude;
+            if shift >= 0 {
+                let scale = 10f64.powi(shift);
+                (v * scale).round() / scale
+            } else {
+                let scale = 10f64.powi(-shift);
+                (v / scale).round() * scale
+            }
+        })
+    }
+
     /// Returns true if this number has no units.
     #[must_use]
     pub fn is_dimensionless(&self) -> bool {
@@ -61,6 +236,75 @@ impl Number {
         self.value.fract() == 0.0
     }
 
+    /// Returns a `Number` equal to this one but expressed in the first of
+    /// `candidates` that its unit can be converted to. If this number's unit
+    /// already has a symbol, or none of the candidates match, the number is
+    /// returned unchanged.
+    ///
+    /// This recovers named units that arithmetic loses. For example, the
+    /// product of newtons and meters is expressed in base units as
+    /// kg⋅m²⋅s⁻²; given joules as a candidate, it is expressed in J.
+    #[must_use]
+    pub fn named(&self, candidates: &[Unit]) -> Number {
+        self.unit
+            .as_ref()
+            .and_then(|u| {
+                u.named(candidates).and_then(|n| {
+                    u.convert(self.value, n)
+                        .ok()
+                        .map(|v| Number::new(v).with_unit(n.clone()))
+                })
+            })
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// Returns a `Number` equal to this one but expressed in the first of
+    /// `preferred` that its unit can be converted to. Unlike `named`, this
+    /// applies to units that already have a symbol. If none of the units
+    /// match, the number is returned unchanged.
+    #[must_use]
+    pub fn in_preferred(&self, preferred: &[Unit]) -> Number {
+        self.unit
+            .as_ref()
+            .and_then(|u| {
+                preferred.iter().find_map(|p| {
+                    u.convert(self.value, p)
+                        .ok()
+                        .map(|v| Number::new(v).with_unit(p.clone()))
+                })
+            })
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// Returns a `Number` equal to this one but with an SI prefix chosen so
+    /// that its value is in [1, 1000). For example, 0.000047 F is 47 uF. If
+    /// the value is out of range of the prefixes, the largest or smallest
+    /// prefix is used. Numbers without units, and numbers whose units can't
+    /// be prefixed, are returned unchanged.
+    #[must_use]
+    pub fn scaled(&self) -> Number {
+        let Some(unit) = self.unit.as_ref() else {
+            return self.clone();
+        };
+        if self.value == 0.0 || !self.value.is_finite() {
+            return self.clone();
+        }
+
+        let mut best = None;
+        for candidate in prefix::family(unit) {
+            let Ok(value) = unit.convert(self.value, &candidate) else {
+                continue;
+            };
+            best = Some(Number::new(value).with_unit(candidate));
+            // Allow for rounding error so that e.g. 1 m isn't shown as
+            // 1000 mm.
+            if value.abs() >= 1.0 - 1e-9 {
+                break;
+            }
+        }
+        best.unwrap_or_else(|| self.clone())
+    }
+
     /// Performs more advanced simplification than `Unit` is capable of doing
     /// on its own. Returns a `Number` that is mathematically equal to this one
     /// but with the units simplified by physical quantity. For example, `Unit`
@@ -69,143 +313,177 @@ impl Number {
     /// `Unit` doesn't have access to. `Number` is able to simplify `m*s/ft`
     /// into `s` because it can apply the conversion factor to its value.
     fn simplified(&self) -> Result<Number, Error> {
-        if let Some(u) = self.unit.as_ref() {
-            let mut value = self.value;
-            let mut s_numer = u.numer().clone();
-            let mut s_denom = u.denom().clone();
-            let mut should_incr: bool;
-
-            // Cancel out like physical quantities from numerator/denominator
-            let mut numer_ix = 0;
-
-            while numer_ix < s_numer.len() {
-                should_incr = true;
-
-                for denom_ix in 0..s_denom.len() {
-                    if s_numer[numer_ix].physq == s_denom[denom_ix].physq {
-                        value *= s_numer[numer_ix].factor;
-                        value /= s_denom[denom_ix].factor;
-                        s_numer.remove(numer_ix);
-                        s_denom.remove(denom_ix);
-                        should_incr = false;
-                        break;
-                    }
-                }
+        let Some(u) = self.unit.as_ref() else {
+            return Ok(self.clone());
+        };
+        if u.bases().iter().any(|(_, exp)| !exp.is_integer()) {
+            return self.simplified_by_exponent(u);
+        }
 
-                if should_incr {
-                    numer_ix += 1;
+        let mut value = self.value;
+        let mut s_numer = u.numer();
+        let mut s_denom = u.denom();
+        let mut should_incr: bool;
+
+        // Cancel out like physical quantities from numerator/denominator
+        let mut numer_ix = 0;
+
+        while numer_ix < s_numer.len() {
+            should_incr = true;
+
+            for denom_ix in 0..s_denom.len() {
+                if s_numer[numer_ix].physq == s_denom[denom_ix].physq {
+                    value *= s_numer[numer_ix].factor;
+                    value /= s_denom[denom_ix].factor;
+                    s_numer.remove(numer_ix);
+                    s_denom.remove(denom_ix);
+                    should_incr = false;
+                    break;
                 }
             }
 
-            // Make like physical quantities the same base
-            value = combine_bases(&mut s_numer, value, false);
-            value = combine_bases(&mut s_denom, value, true);
+            if should_incr {
+                numer_ix += 1;
+            }
+        }
 
-            if s_numer.is_empty() && s_denom.is_empty() {
-                Ok(Number::new(value))
-            } else {
-                Unit::new(&s_numer, &s_denom).map(|u| Number {
-                    value,
-                    unit: Some(u),
-                })
+        // Make like physical quantities the same base
+        value = combine_bases(&mut s_numer, value, false);
+        value = combine_bases(&mut s_denom, value, true);
+
+        if s_numer.is_empty() && s_denom.is_empty() {
+            Ok(Number::new(value))
+        } else {
+            Unit::new(&s_numer, &s_denom).map(|u| Number {
+                value,
+                unit: Some(u),
+            })
+        }
+    }
+
+    /// Helper for `simplified`. Units with fractional exponents can't be
+    /// split into a numerator and denominator, so every physical quantity is
+    /// expressed in the first base unit that measures it instead.
+    fn simplified_by_exponent(&self, u: &Unit) -> Result<Number, Error> {
+        let mut value = self.value;
+        let mut exponents: Vec<(Base, Rational)> = Vec::with_capacity(u.bases().len());
+        for (base, exp) in u.bases() {
+            let first = exponents
+                .iter()
+                .map(|(b, _)| *b)
+                .find(|b| b.physq == base.physq)
+                .unwrap_or(*base);
+            if first != *base {
+                value *= exp.raise(base.factor / first.factor);
             }
+            exponents.push((first, *exp));
+        }
+
+        let unit = Unit::from_exponents(exponents)?;
+        if unit.is_scalar() {
+            Ok(Number::new(value))
         } else {
-            Ok(self.clone())
+            Ok(Number {
+                value,
+                unit: Some(unit),
+            })
         }
     }
 
-    /// Raises this number to the power of another number.
+    /// Returns this number as the exponent of a unit.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - the other number is not dimensionless; or,
-    /// - this number has units and the other number is not whole.
+    /// - this number is not dimensionless;
+    /// - this number is not a fraction with a small denominator; or,
+    /// - this number is too large to be an exponent.
+    pub fn as_exponent(&self) -> Result<Rational, Error> {
+        if !self.is_dimensionless() {
+            Err(Error::ExponentHasUnits)
+        } else if let Some(power) = Rational::from_f64(self.value) {
+            Ok(power)
+        } else if self.is_whole() {
+            Err(Error::ExponentTooLarge)
+        } else {
+            Err(Error::ExponentNotRational)
+        }
+    }
+
+    /// Returns this number as the degree of a root of a unit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - this number is not dimensionless;
+    /// - this number is not a fraction with a small denominator; or,
+    /// - this number is too large to be an exponent.
+    pub fn as_degree(&self) -> Result<Rational, Error> {
+        self.as_exponent().map_err(|e| match e {
+            Error::ExponentHasUnits => Error::DegreeHasUnits,
+            Error::ExponentNotRational => Error::DegreeNotRational,
+            _ => e,
+        })
+    }
+
+    /// Raises this number to the power of another number. If this number has
+    /// units, their exponents are multiplied by the other number, which may
+    /// be a fraction like 1/2 or -3/4.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - the other number is not dimensionless;
+    /// - this number has units and the other number is not a fraction with a
+    ///   small denominator; or,
+    /// - this number has units and the resulting exponents would overflow.
     pub fn pow(&self, other: &Number) -> Result<Number, Error> {
         if !other.is_dimensionless() {
             Err(Error::ExponentHasUnits)
-        } else if self.is_dimensionless() {
-            Ok(Number::new(self.value.powf(other.value)))
-        } else if !other.is_whole() {
-            Err(Error::ExponentNotAnInteger)
         } else if other.value == 0.0 {
             Ok(Number::new(1.0))
+        } else if let Some(u) = &self.unit {
+            let unit = u.pow(other.as_exponent()?)?;
+            Ok(Number::new(self.value.powf(other.value)).with_folded_unit(unit))
         } else {
-            let mut numer: Vec<Base> = Vec::new();
-            let mut denom: Vec<Base> = Vec::new();
-
-            // this will always succeed but i'd rather not use unwrap() and
-            // have to allow missing panics docs in case a real panic gets
-            // added later
-            if let Some(u) = self.unit.as_ref() {
-                // allowed because we tested for wholeness and use abs()
-                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-                for _ in 0..(other.value.abs() as usize) {
-                    numer.extend_from_slice(u.numer());
-                    denom.extend_from_slice(u.denom());
-                }
-            }
-
-            if other.value < 0.0 {
-                (numer, denom) = (denom, numer);
-            }
-
-            Unit::new(&numer, &denom)
-                .map(|u| Number::new(self.value.powf(other.value)).with_unit(u))
+            Ok(Number::new(self.value.powf(other.value)))
         }
     }
 
-    /// Finds the Nth root of this number.
+    /// Finds the Nth root of this number. If this number has units, their
+    /// exponents are divided by N, which may result in fractional exponents.
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - the other number is not dimensionless;
-    /// - this number has units and the other number is not whole; or,
-    /// - this number has units that are not evenly divisible by N.
+    /// - this number has units and the other number is zero or is not a
+    ///   fraction with a small denominator; or,
+    /// - this number has units and the resulting exponents would overflow.
     pub fn root(&self, other: &Number) -> Result<Number, Error> {
         if !other.is_dimensionless() {
             Err(Error::DegreeHasUnits)
-        } else if self.is_dimensionless() {
-            Ok(Number::new(self.value.powf(1.0 / other.value)))
-        } else if !other.is_whole() {
-            Err(Error::DegreeNotAnInteger)
+        } else if let Some(u) = &self.unit {
+            let unit = u.root(other.as_degree()?)?;
+            Ok(Number::new(self.value.powf(1.0 / other.value)).with_folded_unit(unit))
         } else {
-            let mut numer: Vec<Base> = Vec::new();
-            let mut denom: Vec<Base> = Vec::new();
-            #[allow(clippy::cast_possible_truncation)] // already tested for wholeness
-            let degree = other.value as isize;
-            let abs_degree = degree.unsigned_abs();
-
-            // this will always succeed but i'd rather not use unwrap() and
-            // have to allow missing panics docs in case a real panic gets
-            // added later
-            if let Some(u) = &self.unit {
-                let (numer_bases, numer_counts) = base_counts(u.numer());
-                let (denom_bases, denom_counts) = base_counts(u.denom());
-
-                #[allow(clippy::cast_possible_wrap)] // absurd
-                if any(&numer_counts, |n| (*n as isize) % degree != 0)
-                    || any(&denom_counts, |n| (*n as isize) % degree != 0)
-                {
-                    return Err(Error::UnitNotDivisible);
-                }
-
-                numer = divide_base_counts(&numer_bases, &numer_counts, abs_degree);
-                denom = divide_base_counts(&denom_bases, &denom_counts, abs_degree);
-                if degree < 0 {
-                    (numer, denom) = (denom, numer);
-                }
-            }
+            Ok(Number::new(self.value.powf(1.0 / other.value)))
+        }
+    }
 
-            Unit::new(&numer, &denom)
-                .map(|u| Number::new(self.value.powf(1.0 / other.value)).with_unit(u))
+    /// Like `with_unit`, but if `unit` has no symbol its constant is folded
+    /// into the value, the same way multiplication and division do.
+    fn with_folded_unit(&self, unit: Unit) -> Number {
+        if unit.symbol.is_some() {
+            self.with_unit(unit)
+        } else {
+            Number::new(self.value * unit.constant()).with_unit(unit.with_constant(1.0))
         }
     }
 }
 
 /// Helper for `simplified`.
-fn combine_bases(bases: &mut Vec<Base>, value: f64, inverse: bool) -> f64 {
+fn combine_bases(bases: &mut [Base], value: f64, inverse: bool) -> f64 {
     let mut value = value;
     let mut i = 0;
     while i < bases.len() {
@@ -228,37 +506,6 @@ fn combine_bases(bases: &mut Vec<Base>, value: f64, inverse: bool) -> f64 {
     value
 }
 
-/// Helper for `root`. Counts the number of times each unique base appears in
-/// `bases`. Returns parallel vectors containing de-duplicated `bases` and the
-/// number of times each base appears in `bases`.
-fn base_counts(bases: &[Base]) -> (Vec<Base>, Vec<usize>) {
-    let mut uniq_bases: Vec<Base> = Vec::new();
-    let mut counts: Vec<usize> = Vec::new();
-
-    for base in bases {
-        if let Some((ix, _)) = uniq_bases.iter().find_position(|&b| b == base) {
-            counts[ix] += 1;
-        } else {
-            uniq_bases.push(*base);
-            counts.push(1);
-        }
-    }
-
-    (uniq_bases, counts)
-}
-
-/// Helper for `root`. Takes parallel vectors `bases` and `counts` and returns
-/// a vector with each base `bases[n]` appearing `counts[n]` / `divisor` times.
-fn divide_base_counts(bases: &[Base], counts: &[usize], divisor: usize) -> Vec<Base> {
-    let mut result: Vec<Base> = Vec::new();
-    for ix in 0..bases.len() {
-        for _ in 0..(counts[ix] / divisor) {
-            result.push(bases[ix]);
-        }
-    }
-    result
-}
-
 /// Helper for `std::fmt::Display` implementation.
 fn should_use_exponent_format(x: f64) -> bool {
     // I don't know if these thresholds make sense, or if thresholds are even
@@ -302,6 +549,46 @@ impl std::fmt::Display for Number {
     }
 }
 
+/// Helper for `Add` and `Sub`. Temperatures like tempC are points on a scale,
+/// so a difference (degC or degF) can be added to or subtracted from one, and
+/// two of them can be subtracted to give a difference, but two of them can't
+/// be added. K and R are temperatures too, measured from absolute zero, in
+/// whichever order they appear. Returns `None` unless one of `a` and `b` is a
+/// temperature like tempC and the other is a temperature or difference, so
+/// the usual rules apply.
+fn temperature_sum(a: &Number, b: &Number, subtract: bool) -> Option<Result<Number, Error>> {
+    let (ua, ub) = (a.unit.as_ref()?, b.unit.as_ref()?);
+    let (ba, bb) = (ua.sole_base()?, ub.sole_base()?);
+    if ba.physq != bb.physq || !(ba.is_point() || bb.is_point()) {
+        return None;
+    }
+    let sign = if subtract { -1.0 } else { 1.0 };
+
+    Some(match (ba.zero.is_some(), bb.zero.is_some(), subtract) {
+        (true, true, false) => Err(Error::TemperaturesAdded(ba, bb)),
+        (true, true, true) => {
+            let v = if ba == bb {
+                Ok(b.value)
+            } else {
+                ub.convert(b.value, ua)
+            };
+            v.map(|v| {
+                let interval = ba.interval().unwrap_or(ba.without_zero());
+                Number::new(a.value - v).with_unit(interval.as_unit())
+            })
+        }
+        (true, false, _) => {
+            let v = b.value * bb.factor / ba.factor;
+            Ok(Number::new(a.value + sign * v).with_unit(ua.clone()))
+        }
+        (false, _, false) => {
+            let v = a.value * ba.factor / bb.factor;
+            Ok(Number::new(v + b.value).with_unit(ub.clone()))
+        }
+        (false, _, true) => Err(Error::TemperatureSubtracted(ba, bb)),
+    })
+}
+
 impl std::ops::Add<&Number> for &Number {
     type Output = Result<Number, Error>;
 
@@ -309,8 +596,13 @@ impl std::ops::Add<&Number> for &Number {
     ///
     /// # Errors
     ///
-    /// Returns an error if `self` and `other` have incommensurable units.
+    /// Returns an error if:
+    /// - `self` and `other` have incommensurable units; or,
+    /// - both numbers are temperatures like tempC.
     fn add(self, other: &Number) -> Result<Number, Error> {
+        if let Some(result) = temperature_sum(self, other, false) {
+            return result;
+        }
         let v1 = self.value;
         let v2 = other.value;
 
@@ -319,6 +611,8 @@ impl std::ops::Add<&Number> for &Number {
                 .convert(v2, u1)
                 .map(|v2| Number::new(v1 + v2).with_unit(u1.clone())),
             (None, None) => Ok(Number::new(v1 + v2)),
+            (Some(u1), None) if u1.is_scalar() => Ok(Number::new(v1 * u1.constant() + v2)),
+            (None, Some(u2)) if u2.is_scalar() => Ok(Number::new(v1 + v2 * u2.constant())),
             (Some(u1), None) => Err(Error::IncommensurableUnits(
                 Some(Box::new(u1.clone())),
                 None,
@@ -338,8 +632,13 @@ impl std::ops::Sub<&Number> for &Number {
     ///
     /// # Errors
     ///
-    /// Returns an error if `self` and `other` have incommensurable units.
+    /// Returns an error if:
+    /// - `self` and `other` have incommensurable units; or,
+    /// - `self` is a difference in degrees and `other` is a temperature.
     fn sub(self, other: &Number) -> Result<Number, Error> {
+        if let Some(result) = temperature_sum(self, other, true) {
+            return result;
+        }
         let v1 = self.value;
         let v2 = other.value;
 
@@ -348,6 +647,8 @@ impl std::ops::Sub<&Number> for &Number {
                 .convert(v2, u1)
                 .map(|v2| Number::new(v1 - v2).with_unit(u1.clone())),
             (None, None) => Ok(Number::new(v1 - v2)),
+            (Some(u1), None) if u1.is_scalar() => Ok(Number::new(v1 * u1.constant() - v2)),
+            (None, Some(u2)) if u2.is_scalar() => Ok(Number::new(v1 - v2 * u2.constant())),
             (Some(u1), None) => Err(Error::IncommensurableUnits(
                 Some(Box::new(u1.clone())),
                 None,
@@ -360,24 +661,45 @@ impl std::ops::Sub<&Number> for &Number {
     }
 }
 
+impl std::ops::Neg for &Number {
+    type Output = Result<Number, Error>;
+
+    /// Negates this number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this number is a temperature like tempC.
+    fn neg(self) -> Result<Number, Error> {
+        Ok(self.signed()?.map(std::ops::Neg::neg))
+    }
+}
+
 impl std::ops::Mul<&Number> for &Number {
     type Output = Result<Number, Error>;
 
-    /// Multiplies this number by another.
+    /// Multiplies this number by another. Multiplying by a number without
+    /// units keeps the other number's unit as it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - a temperature like tempC is multiplied by a number without units; or,
+    /// - the units can't be multiplied.
     fn mul(self, other: &Number) -> Result<Number, Error> {
         let v1 = self.value;
         let v2 = other.value;
 
         match (&self.unit, &other.unit) {
-            (Some(u1), Some(u2)) => {
-                (u1 * u2).map(|u| Number::new(v1 * v2 * u1.constant() * u2.constant()).with_unit(u))
-            }
-            (Some(u), None) | (None, Some(u)) => {
-                Ok(Number::new(v1 * v2 * u.constant()).with_unit(u.clone()))
-            }
+            (Some(u1), Some(u2)) => (u1 * u2)
+                .map(|u| {
+                    Number::new(v1 * v2 * u1.constant() * u2.constant())
+                        .with_unit(u.with_constant(1.0))
+                })
+                .and_then(|n| n.simplified()),
+            (Some(_), None) => self.times(v2),
+            (None, Some(_)) => other.times(v1),
             (None, None) => Ok(Number::new(v1 * v2)),
         }
-        .and_then(|n| n.simplified())
     }
 }
 
@@ -390,29 +712,39 @@ impl std::ops::Mul<&Unit> for &Number {
         self.unit
             .as_ref()
             .map_or(Ok(other.clone()), |u| u * other)
-            .map(|u| self.with_unit(u))
+            .map(|u| self.with_folded_unit(u))
     }
 }
 
 impl std::ops::Div<&Number> for &Number {
     type Output = Result<Number, Error>;
 
-    /// Divides this number by another.
+    /// Divides this number by another. Dividing by a number without units
+    /// keeps this number's unit as it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - a temperature like tempC is divided by a number without units; or,
+    /// - the units can't be divided.
     fn div(self, other: &Number) -> Result<Number, Error> {
         let v1 = self.value;
         let v2 = other.value;
 
         match (&self.unit, &other.unit) {
-            (Some(u1), Some(u2)) => {
-                (u1 / u2).map(|u| Number::new(v1 / v2 * u1.constant() / u2.constant()).with_unit(u))
-            }
-            (Some(u1), None) => Ok(Number::new(v1 / v2 * u1.constant()).with_unit(u1.clone())),
+            (Some(u1), Some(u2)) => (u1 / u2)
+                .map(|u| {
+                    Number::new(v1 / v2 * u1.constant() / u2.constant())
+                        .with_unit(u.with_constant(1.0))
+                })
+                .and_then(|n| n.simplified()),
+            (Some(_), None) => self.times(1.0 / v2),
             (None, Some(u2)) => u2
                 .inverse()
-                .map(|u| Number::new(v1 / v2 / u2.constant()).with_unit(u)),
+                .map(|u| Number::new(v1 / v2 / u2.constant()).with_unit(u))
+                .and_then(|n| n.simplified()),
             (None, None) => Ok(Number::new(v1 / v2)),
         }
-        .and_then(|u| u.simplified())
     }
 }
 
@@ -425,14 +757,18 @@ impl std::ops::Div<&Unit> for &Number {
         self.unit
             .as_ref()
             .map_or(other.inverse(), |u| u / other)
-            .map(|u| self.with_unit(u))
+            .map(|u| self.with_folded_unit(u))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::units::Number;
-    use crate::units::{HOUR, KILOGRAM, METER, MILE, SECOND, TEMP_CELSIUS};
+    use crate::units::{Error, Number, Rational, Unit};
+    use crate::units::{
+        DEG_CELSIUS, DEG_FAHRENHEIT, FARAD, FOOT, HOUR, JOULE, KELVIN, KILOGRAM, KILOJOULE, METER,
+        MILE, NEWTON, PERCENT, POUND_MASS, PPM, SECOND, TEMP_CELSIUS, TEMP_FAHRENHEIT, WATT,
+    };
+    use approx::assert_relative_eq;
 
     #[test]
     fn dimensionless_added_to_dimensionless() {
@@ -470,6 +806,91 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn ratio_added_to_dimensionless() {
+        let pct = Number::new(0.05).in_ratio(PERCENT.clone());
+        assert_eq!(pct.to_string(), "[5 %]");
+        let x = (&pct + &Number::new(1.0)).unwrap();
+        assert_relative_eq!(x.value, 1.05);
+        assert!(x.is_dimensionless());
+        let x = (&Number::new(1.0) - &pct).unwrap();
+        assert_relative_eq!(x.value, 0.95);
+        assert!(x.is_dimensionless());
+    }
+
+    #[test]
+    fn ratios_fold_into_products() {
+        assert!(Number::new(5.0)
+            .with_unit(PERCENT.clone())
+            .is_dimensionless());
+        let tempco = Number::new(50.0).with_unit((&*PPM / DEG_CELSIUS).unwrap());
+        let x = (&tempco * &Number::new(10.0).with_unit(DEG_CELSIUS.as_unit())).unwrap();
+        assert_relative_eq!(x.value, 5e-4);
+        assert!(x.is_dimensionless());
+        let x = (&Number::new(3.0).with_unit(METER.as_unit()) * &*PERCENT).unwrap();
+        assert_relative_eq!(x.value, 0.03);
+    }
+
+    #[test]
+    fn scaled_units_cancel() {
+        let x = (&Number::new(2.0).with_unit(KILOJOULE.clone())
+            / &Number::new(4.0).with_unit(JOULE.clone()))
+            .unwrap();
+        assert_relative_eq!(x.value, 500.0);
+        assert!(x.is_dimensionless());
+        let x = (&Number::new(3.0).with_unit(METER.as_unit()) * &*KILOJOULE).unwrap();
+        assert_relative_eq!(x.value, 3000.0);
+    }
+
+    #[test]
+    fn temperatures_subtracted() {
+        let t1 = Number::new(30.0).with_unit(TEMP_CELSIUS.as_unit());
+        let t2 = Number::new(50.0).with_unit(TEMP_FAHRENHEIT.as_unit());
+        let x = (&t1 - &t2).unwrap();
+        assert_relative_eq!(x.value, 20.0, max_relative = 1e-12);
+        assert_eq!(x.unit.unwrap().to_string(), "degC");
+        let x = (&t2 - &t1).unwrap();
+        assert_relative_eq!(x.value, -36.0, max_relative = 1e-12);
+        assert_eq!(x.unit.unwrap().to_string(), "degF");
+    }
+
+    #[test]
+    fn kelvins_are_temperatures() {
+        // K is absolute in either order.
+        let t = Number::new(20.0).with_unit(TEMP_CELSIUS.as_unit());
+        let k = Number::new(300.0).with_unit(KELVIN.as_unit());
+        let x = (&t - &k).unwrap();
+        assert_relative_eq!(x.value, -6.85, max_relative = 1e-12);
+        assert_eq!(x.unit.unwrap().to_string(), "degC");
+        let x = (&k - &t).unwrap();
+        assert_relative_eq!(x.value, 6.85, max_relative = 1e-12);
+        assert_eq!(x.unit.unwrap().to_string(), "K");
+        assert!(matches!(&t + &k, Err(Error::TemperaturesAdded(_, _))));
+        assert!(matches!(&k + &t, Err(Error::TemperaturesAdded(_, _))));
+    }
+
+    #[test]
+    fn differences_added_to_temperatures() {
+        let t = Number::new(30.0).with_unit(TEMP_CELSIUS.as_unit());
+        let d = Number::new(9.0).with_unit(DEG_FAHRENHEIT.as_unit());
+        let x = (&t + &d).unwrap();
+        assert_relative_eq!(x.value, 35.0);
+        assert_eq!(x.unit.unwrap().to_string(), "tempC");
+        let x = (&d + &t).unwrap();
+        assert_relative_eq!(x.value, 35.0);
+        assert_eq!(x.unit.unwrap().to_string(), "tempC");
+        let x = (&t - &Number::new(5.0).with_unit(DEG_CELSIUS.as_unit())).unwrap();
+        assert_relative_eq!(x.value, 25.0);
+    }
+
+    #[test]
+    fn temperature_errors() {
+        let t = Number::new(30.0).with_unit(TEMP_CELSIUS.as_unit());
+        let d = Number::new(5.0).with_unit(DEG_CELSIUS.as_unit());
+        assert!(matches!(&t + &t, Err(Error::TemperaturesAdded(_, _))));
+        assert!(matches!(&d - &t, Err(Error::TemperatureSubtracted(_, _))));
+    }
+
     #[test]
     fn dimensionless_subtracted_from_dimensionless() {
         let x = (&Number::new(5.0) - &Number::new(10.0)).unwrap();
@@ -531,6 +952,16 @@ mod tests {
         assert_eq!(*x.unit.unwrap().denom(), vec![SECOND]);
     }
 
+    #[test]
+    fn named_unit_multiplied_by_dimensionless() {
+        let x = (&Number::new(2.0).with_unit(KILOJOULE.clone()) * &Number::new(3.0)).unwrap();
+        assert_eq!(x.value, 6.0);
+        assert_eq!(x.unit.unwrap(), *KILOJOULE);
+
+        let x = &Number::new(20.0).with_unit(TEMP_CELSIUS.as_unit()) * &Number::new(2.0);
+        assert!(matches!(x, Err(Error::NonzeroZeroPoint(b)) if b == TEMP_CELSIUS));
+    }
+
     #[test]
     fn number_with_unit_multiplied_by_number_with_unit() {
         let x = (&Number::new(5.0).with_unit((METER / SECOND).unwrap())
@@ -626,6 +1057,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::excessive_precision)]
     fn display_dimensionless_with_exponent_format() {
         // six decimal places max
         assert_eq!(Number::new(0.000898359204909915).to_string(), "8.983592e-4");
@@ -693,6 +1125,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::excessive_precision)]
     fn display_with_units_with_exponent_format() {
         let u = (METER / SECOND).unwrap();
         // six decimal places max
@@ -906,5 +1339,184 @@ mod tests {
         let a = Number::new(30.149042744979106).with_unit(METER.as_unit());
         let b = Number::new(19.85259661704478);
         assert!(a.pow(&b).is_err());
+        let a = Number::new(1.0).with_unit((METER / SECOND).unwrap());
+        let b = Number::new(1e10);
+        assert_eq!(a.pow(&b).unwrap_err(), Error::ExponentTooLarge);
+    }
+
+    #[test]
+    fn pow_with_large_exponent() {
+        let a = Number::new(1.0).with_unit(METER.as_unit());
+        let b = Number::new(5000.0);
+        let c = a.pow(&b).unwrap();
+        assert_eq!(
+            c.unit.as_ref().unwrap().bases(),
+            &[(METER, Rational::from(5000))]
+        );
+        let c = c.root(&b).unwrap();
+        assert_eq!(c.unit.unwrap().bases(), &[(METER, Rational::ONE)]);
+    }
+
+    #[test]
+    fn fractional_exponents() {
+        let a = Number::new(16.0).with_unit((METER / SECOND).unwrap());
+        let c = a.root(&Number::new(2.0)).unwrap();
+        assert_eq!(c.value, 4.0);
+        assert_eq!(
+            c.unit.unwrap().bases(),
+            &[
+                (METER, Rational::new(1, 2).unwrap()),
+                (SECOND, Rational::new(-1, 2).unwrap())
+            ]
+        );
+
+        let a = Number::new(16.0).with_unit(METER.as_unit());
+        let c = a.pow(&Number::new(-0.75)).unwrap();
+        assert_eq!(c.value, 0.125);
+        assert_eq!(
+            c.unit.unwrap().bases(),
+            &[(METER, Rational::new(-3, 4).unwrap())]
+        );
+
+        // Fractional exponents that sum to an integer become integers again
+        let a = Number::new(2.0).with_unit(METER.as_unit());
+        let b = a.root(&Number::new(2.0)).unwrap();
+        let c = (&b * &b).unwrap();
+        assert_eq!(c.unit.unwrap().bases(), &[(METER, Rational::ONE)]);
+    }
+
+    #[test]
+    fn root_errors() {
+        let a = Number::new(4.0).with_unit(METER.as_unit());
+        assert_eq!(a.root(&Number::new(0.0)).unwrap_err(), Error::DegreeIsZero);
+        assert_eq!(
+            a.root(&Number::new(std::f64::consts::PI)).unwrap_err(),
+            Error::DegreeNotRational
+        );
+        assert_eq!(
+            a.root(&Number::new(2.0).with_unit(METER.as_unit()))
+                .unwrap_err(),
+            Error::DegreeHasUnits
+        );
+    }
+
+    #[test]
+    fn named() {
+        let candidates = [WATT.clone(), JOULE.clone()];
+
+        let x = (&Number::new(100.0).with_unit(NEWTON.clone())
+            * &Number::new(2.0).with_unit(METER.as_unit()))
+            .unwrap()
+            .named(&candidates);
+        assert_eq!(x.value, 200.0);
+        assert_eq!(x.unit.unwrap().symbol.as_deref(), Some("J"));
+
+        // No candidate measures speed.
+        let x = Number::new(3.0)
+            .with_unit((METER / SECOND).unwrap())
+            .named(&candidates);
+        assert_eq!(x.value, 3.0);
+        assert_eq!(x.unit.unwrap().symbol, None);
+
+        // Naming never changes the base units a quantity is measured in.
+        let x = Number::new(5.0)
+            .with_unit(Unit::new(&[POUND_MASS, FOOT, FOOT], &[SECOND, SECOND]).unwrap())
+            .named(&candidates);
+        assert_eq!(x.value, 5.0);
+        assert_eq!(x.unit.unwrap().symbol, None);
+
+        // Units that already have symbols are left alone.
+        let x = Number::new(4.0)
+            .with_unit(NEWTON.clone())
+            .named(&[NEWTON.with_constant(1e3).with_symbol("kN")]);
+        assert_eq!(x.value, 4.0);
+        assert_eq!(x.unit.unwrap().symbol.as_deref(), Some("N"));
+    }
+
+    #[test]
+    fn in_preferred() {
+        let mm = METER.as_unit().with_constant(1e-3).with_symbol("mm");
+        let x = Number::new(1.5)
+            .with_unit(METER.as_unit())
+            .in_preferred(&[WATT.clone(), mm]);
+        assert_relative_eq!(x.value, 1500.0);
+        assert_eq!(x.unit.unwrap().symbol.as_deref(), Some("mm"));
+
+        let x = Number::new(2.0).with_unit(HOUR.as_unit()).in_preferred(&[]);
+        assert_eq!(x.value, 2.0);
+    }
+
+    #[test]
+    fn scaled() {
+        let x = Number::new(0.000_047).with_unit(FARAD.clone()).scaled();
+        assert_eq!(x.to_string(), "[47 uF]");
+
+        let x = Number::new(1.0).with_unit(METER.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[1 m]");
+
+        let x = Number::new(-2500.0).with_unit(METER.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[-2.5 km]");
+
+        let x = Number::new(0.5).with_unit(SECOND.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[500 ms]");
+
+        let x = Number::new(3e20).with_unit(WATT.clone()).scaled();
+        assert_eq!(x.to_string(), "[300000 PW]");
+
+        let x = Number::new(1500.0).with_unit(KILOGRAM.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[1.5 t]");
+        let x = Number::new(1.5e6).with_unit(KILOGRAM.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[1.5 Gg]");
+        let x = Number::new(0.25).with_unit(KILOGRAM.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[250 g]");
+
+        // Units without prefixed forms are left alone.
+        let x = Number::new(0.001).with_unit(MILE.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[0.001 mi]");
+        let x = Number::new(0.0).with_unit(METER.as_unit()).scaled();
+        assert_eq!(x.to_string(), "[0 m]");
+    }
+
+    #[test]
+    fn value_in() {
+        let x = Number::new(1.0).with_unit(MILE.as_unit());
+        assert_relative_eq!(x.value_in(Some(&METER.as_unit())).unwrap(), 1609.344);
+        assert!(matches!(
+            x.value_in(Some(&SECOND.as_unit())),
+            Err(Error::IncommensurableUnits(_, _))
+        ));
+        assert!(x.value_in(None).is_err());
+
+        let pct = Number::new(0.05).in_ratio(PERCENT.clone());
+        assert_relative_eq!(pct.value_in(None).unwrap(), 0.05);
+        assert_relative_eq!(Number::new(0.05).value_in(Some(&PERCENT)).unwrap(), 5.0);
+        assert_eq!(Number::new(2.0).value_in(None).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn rounding() {
+        let x = Number::new(2.54).with_unit(METER.as_unit());
+        assert_eq!(x.round().to_string(), "[3 m]");
+        assert_eq!((-&x).unwrap().floor().to_string(), "[-3 m]");
+        assert_eq!((-&x).unwrap().trunc().to_string(), "[-2 m]");
+        assert_eq!(Number::new(-0.0).signum(), 0.0);
+
+        let step = Number::new(0.5).with_unit(MILE.as_unit());
+        let x = Number::new(1000.0).with_unit(METER.as_unit());
+        assert_eq!(x.round_to(&step).unwrap().to_string(), "[0.5 mi]");
+        assert!(x.round_to(&Number::new(5.0)).is_err());
+
+        assert_eq!(Number::new(123_456.0).round_sig(2).value, 120_000.0);
+        assert_eq!(Number::new(-0.001_234_5).round_sig(3).value, -0.001_23);
+        assert_eq!(Number::new(0.1).round_sig(20).value, 0.1);
+    }
+
+    #[test]
+    fn compare() {
+        let x = Number::new(1.0).with_unit(MILE.as_unit());
+        let y = Number::new(1000.0).with_unit(METER.as_unit());
+        assert!(x.compare(&y).unwrap().is_gt());
+        assert!(y.compare(&x).unwrap().is_lt());
+        assert!(x.compare(&Number::new(1.0)).is_err());
     }
 }
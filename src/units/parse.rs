@@ -0,0 +1,507 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing of unit expressions like `m/s^2`, `kg⋅m²⋅s⁻²`, and `N.m`.
+//!
+//! Multiplication binds more tightly than division, so `W/m⋅K` is watts per
+//! meter-kelvin. Exponents may be written with superscripts as `Unit`'s
+//! `Display` implementation emits them (`s⁻¹ᐟ²`), with a caret (`s^-2`,
+//! `s^(-1/2)`, `s^-1/2`), or as digits directly after a symbol (`m2`, `s-2`).
+
+use super::{unit::exponents_to_string, Error, Rational, Unit, SYMBOLS};
+
+/// Alternative symbols, along with the symbols they stand for. `h` is only
+/// recognized inside unit expressions, since on its own it's the Planck
+/// constant.
+const ALIASES: [(&str, &str); 3] = [("h", "hr"), ("tempK", "K"), ("tempR", "R")];
+
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+/// A token in a unit expression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'a> {
+    Symbol(&'a str),
+    Digits(i32),
+    /// Digits with too large a value to be an exponent.
+    Overflow,
+    Superscript(Rational),
+    Minus,
+    Mul,
+    Div,
+    Caret,
+    Open,
+    Close,
+}
+
+/// A partially-parsed unit expression: the unit itself, and the symbols and
+/// exponents it was written with.
+struct Term {
+    unit: Unit,
+    factors: Vec<(&'static str, Rational)>,
+}
+
+impl Term {
+    /// The dimensionless term that `1/s` divides by `s`.
+    fn one() -> Result<Term, Error> {
+        Ok(Term {
+            unit: Unit::new(&[], &[])?,
+            factors: Vec::new(),
+        })
+    }
+
+    fn mul(self, other: Term) -> Result<Term, Error> {
+        let unit = (&self.unit * &other.unit)?;
+        let mut factors = self.factors;
+        for (symbol, exp) in other.factors {
+            if let Some(entry) = factors.iter_mut().find(|(s, _)| *s == symbol) {
+                entry.1 = entry.1.checked_add(exp).ok_or(Error::ExponentTooLarge)?;
+            } else {
+                factors.push((symbol, exp));
+            }
+        }
+        factors.retain(|(_, exp)| *exp != Rational::ZERO);
+        Ok(Term { unit, factors })
+    }
+
+    fn div(self, other: Term) -> Result<Term, Error> {
+        self.mul(other.pow(Rational::from(-1))?)
+    }
+
+    fn pow(self, power: Rational) -> Result<Term, Error> {
+        if power == Rational::ZERO {
+            return Err(Error::ExponentIsZero);
+        }
+        let mut factors = Vec::with_capacity(self.factors.len());
+        for (symbol, exp) in self.factors {
+            factors.push((
+                symbol,
+                exp.checked_mul(power).ok_or(Error::ExponentTooLarge)?,
+            ));
+        }
+        Ok(Term {
+            unit: self.unit.pow(power)?,
+            factors,
+        })
+    }
+}
+
+/// Recursive descent parser over the tokens of a unit expression.
+struct Parser<'a> {
+    expr: &'a str,
+    /// Symbols to recognize in addition to those in `SYMBOLS`.
+    extra: &'a [(&'static str, Unit)],
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns the error for a malformed expression.
+    fn malformed(&self) -> Error {
+        Error::UnknownUnit(self.expr.to_string())
+    }
+
+    fn peek(&self, n: usize) -> Option<Token<'a>> {
+        self.tokens.get(self.pos + n).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek(0);
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), Error> {
+        if self.next() == Some(token) {
+            Ok(())
+        } else {
+            Err(self.malformed())
+        }
+    }
+
+    /// `expr := (product | '1') ('/' product)*`
+    ///
+    /// A numerator of 1 needs a division after it, so `1` on its own isn't a
+    /// unit.
+    fn expr(&mut self) -> Result<Term, Error> {
+        let mut term = if (self.peek(0), self.peek(1)) == (Some(Token::Digits(1)), Some(Token::Div))
+        {
+            self.next();
+            Term::one()?
+        } else {
+            self.product()?
+        };
+        while self.peek(0) == Some(Token::Div) {
+            self.next();
+            term = term.div(self.product()?)?;
+        }
+        Ok(term)
+    }
+
+    /// `product := factor ('*' factor)*`
+    fn product(&mut self) -> Result<Term, Error> {
+        let mut term = self.factor()?;
+        while self.peek(0) == Some(Token::Mul) {
+            self.next();
+            term = term.mul(self.factor()?)?;
+        }
+        Ok(term)
+    }
+
+    /// `factor := symbol digits? exponent? | '(' expr ')' exponent?`
+    fn factor(&mut self) -> Result<Term, Error> {
+        let term = match self.next() {
+            Some(Token::Symbol(s)) => {
+                let term = self.lookup(s)?;
+                match (self.peek(0), self.peek(1)) {
+                    (Some(Token::Digits(n)), _) => {
+                        self.next();
+                        term.pow(Rational::from(n))?
+                    }
+                    (Some(Token::Minus), Some(Token::Digits(n))) => {
+                        self.pos += 2;
+                        term.pow(Rational::from(-n))?
+                    }
+                    (Some(Token::Overflow), _) | (Some(Token::Minus), Some(Token::Overflow)) => {
+                        return Err(Error::ExponentTooLarge)
+                    }
+                    _ => term,
+                }
+            }
+            Some(Token::Open) => {
+                let term = self.expr()?;
+                self.expect(Token::Close)?;
+                term
+            }
+            _ => return Err(self.malformed()),
+        };
+
+        match self.peek(0) {
+            Some(Token::Superscript(exp)) => {
+                self.next();
+                term.pow(exp)
+            }
+            Some(Token::Caret) => {
+                self.next();
+                let exp = self.exponent()?;
+                term.pow(exp)
+            }
+            _ => Ok(term),
+        }
+    }
+
+    /// Looks up a single unit symbol.
+    fn lookup(&self, symbol: &str) -> Result<Term, Error> {
+        let symbol = ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == symbol)
+            .map_or(symbol, |(_, s)| *s);
+        SYMBOLS
+            .get_key_value(symbol)
+            .map(|(symbol, unit)| (*symbol, unit))
+            .or_else(|| {
+                self.extra
+                    .iter()
+                    .find(|(s, _)| *s == symbol)
+                    .map(|(symbol, unit)| (*symbol, unit))
+            })
+            .map(|(symbol, unit)| Term {
+                unit: unit.clone(),
+                factors: vec![(symbol, Rational::ONE)],
+            })
+            .ok_or_else(|| Error::UnknownUnit(symbol.to_string()))
+    }
+
+    /// `exponent := rational | '(' rational ')'`
+    fn exponent(&mut self) -> Result<Rational, Error> {
+        if self.peek(0) == Some(Token::Open) {
+            self.next();
+            let exp = self.rational()?;
+            self.expect(Token::Close)?;
+            Ok(exp)
+        } else {
+            self.rational()
+        }
+    }
+
+    /// `rational := '-'? digits ('/' digits)?`
+    ///
+    /// A slash is only part of the exponent if it is followed by digits, so
+    /// `m^2/s` is square meters per second but `s^-1/2` is `s⁻¹ᐟ²`.
+    fn rational(&mut self) -> Result<Rational, Error> {
+        let negative = self.peek(0) == Some(Token::Minus);
+        if negative {
+            self.next();
+        }
+        let numer = match self.next() {
+            Some(Token::Digits(numer)) => numer,
+            Some(Token::Overflow) => return Err(Error::ExponentTooLarge),
+            _ => return Err(self.malformed()),
+        };
+        let numer = if negative { -numer } else { numer };
+        match (self.peek(0), self.peek(1)) {
+            (Some(Token::Div), Some(Token::Digits(denom))) => {
+                self.pos += 2;
+                Rational::new(numer, denom).ok_or_else(|| self.malformed())
+            }
+            (Some(Token::Div), Some(Token::Overflow)) => Err(Error::ExponentTooLarge),
+            _ => Ok(Rational::from(numer)),
+        }
+    }
+}
+
+/// Splits a unit expression into tokens.
+fn tokenize(expr: &str) -> Result<Vec<Token<'_>>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        let token = match ch {
+            '*' | '.' | '·' | '⋅' => Token::Mul,
+            '/' => Token::Div,
+            '^' => Token::Caret,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '-' => Token::Minus,
+            '0'..='9' => {
+                // Whether too many digits are an error depends on where they
+                // are: `m99999999999` has too large an exponent, but
+                // `0x8000000000000000` isn't a unit at all.
+                let mut n = Some(ch.to_digit(10).unwrap_or(0));
+                while let Some(d) = chars.peek().and_then(|(_, c)| c.to_digit(10)) {
+                    n = n.and_then(|n| n.checked_mul(10)?.checked_add(d));
+                    chars.next();
+                }
+                n.and_then(|n| i32::try_from(n).ok())
+                    .map_or(Token::Overflow, Token::Digits)
+            }
+            _ if is_superscript(ch) => {
+                let mut s = String::from(ch);
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_superscript(*c)) {
+                    s.push(c);
+                }
+                Token::Superscript(
+                    superscript_to_rational(&s)
+                        .ok_or_else(|| Error::UnknownUnit(expr.to_string()))?,
+                )
+            }
+            _ => {
+                let mut end = start + ch.len_utf8();
+                while let Some((i, c)) = chars.next_if(|(_, c)| !is_special(*c)) {
+                    end = i + c.len_utf8();
+                }
+                Token::Symbol(&expr[start..end])
+            }
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Returns true if `ch` can't be part of a symbol.
+fn is_special(ch: char) -> bool {
+    "*.·⋅/^()-".contains(ch) || ch.is_ascii_digit() || is_superscript(ch)
+}
+
+fn is_superscript(ch: char) -> bool {
+    ch == '⁻' || ch == 'ᐟ' || SUPERSCRIPT_DIGITS.contains(&ch)
+}
+
+/// Converts a superscript exponent like "⁻¹ᐟ²" into a `Rational`.
+fn superscript_to_rational(s: &str) -> Option<Rational> {
+    let ascii: String = s
+        .chars()
+        .map(|c| match c {
+            '⁻' => '-',
+            'ᐟ' => '/',
+            _ => SUPERSCRIPT_DIGITS
+                .iter()
+                .position(|d| *d == c)
+                .and_then(|i| char::from_digit(u32::try_from(i).ok()?, 10))
+                .unwrap_or('?'),
+        })
+        .collect();
+    let (numer, denom) = ascii.split_once('/').unwrap_or((&ascii, "1"));
+    Rational::new(numer.parse().ok()?, denom.parse().ok()?)
+}
+
+impl std::str::FromStr for Unit {
+    type Err = Error;
+
+    /// Parses a unit expression such as `m/s^2`. See `parse_with`.
+    ///
+    /// # Errors
+    ///
+    /// See `parse_with`.
+    fn from_str(expr: &str) -> Result<Unit, Error> {
+        parse_with(expr, &[])
+    }
+}
+
+/// Parses a unit expression such as `m/s^2`, recognizing the symbols in
+/// `extra` as well as the built-in ones. A lone symbol produces the unit with
+/// that symbol. Compound expressions produce a unit whose symbol is the
+/// expression written the way `Display` would write it, so that e.g. `N.m` is
+/// displayed as `N⋅m` rather than in base units.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - the expression is malformed or contains an unknown symbol;
+/// - an exponent is zero;
+/// - the expression would result in a nonsensical temperature unit; or,
+/// - an exponent is too large to represent.
+pub fn parse_with(expr: &str, extra: &[(&'static str, Unit)]) -> Result<Unit, Error> {
+    let mut parser = Parser {
+        expr,
+        extra,
+        tokens: tokenize(expr)?,
+        pos: 0,
+    };
+    let term = parser.expr()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(parser.malformed());
+    }
+
+    match term.factors.as_slice() {
+        // Factors that cancel out leave the unit of the one that's left, but
+        // not its symbol, so look it up again.
+        [(symbol, exp)] if *exp == Rational::ONE => Ok(parser.lookup(symbol)?.unit),
+        factors => Ok(match exponents_to_string(factors) {
+            Some(symbol) => term.unit.with_symbol(&symbol),
+            None => term.unit,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::units::{Error, Unit, HOUR, KILOMETER, METER, NEWTON, SECOND, TEMP_CELSIUS};
+
+    fn parse(expr: &str) -> Result<Unit, Error> {
+        expr.parse()
+    }
+
+    #[test]
+    fn single_symbols() {
+        assert_eq!(parse("m").unwrap(), METER.as_unit());
+        assert_eq!(parse("N").unwrap(), *NEWTON);
+        assert_eq!(parse("(N)").unwrap(), *NEWTON);
+        assert_eq!(parse("tempC").unwrap(), TEMP_CELSIUS.as_unit());
+    }
+
+    #[test]
+    fn operators() {
+        let accel = Unit::new(&[METER], &[SECOND, SECOND]).unwrap();
+        for expr in [
+            "m/s^2",
+            "m/s2",
+            "m/s²",
+            "m⋅s⁻²",
+            "m.s-2",
+            "m*s^-2",
+            "m/s/s",
+            "m/(s*s)",
+        ] {
+            let u = parse(expr).unwrap();
+            assert!(u.is_commensurable_with(&accel), "{expr}");
+            assert_eq!(u.constant(), 1.0, "{expr}");
+            assert_eq!(u.to_string(), "m⋅s⁻²", "{expr}");
+        }
+
+        let u = parse("km/h").unwrap();
+        assert_eq!(
+            u,
+            Unit::new(&[KILOMETER], &[HOUR])
+                .unwrap()
+                .with_symbol("km⋅hr⁻¹")
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        // Multiplication binds more tightly than division.
+        let u = parse("W/m·K").unwrap();
+        assert_eq!(u.to_string(), "W⋅m⁻¹⋅K⁻¹");
+        let u = parse("kg·m²/s²").unwrap();
+        assert_eq!(u.to_string(), "kg⋅m²⋅s⁻²");
+    }
+
+    #[test]
+    fn derived_units_keep_their_symbols() {
+        let u = parse("N.m").unwrap();
+        assert_eq!(u.to_string(), "N⋅m");
+        assert!(u.is_commensurable_with(&crate::units::JOULE));
+
+        let u = parse("kN*m").unwrap();
+        assert_eq!(u.convert(1.0, &crate::units::JOULE).unwrap(), 1e3);
+    }
+
+    #[test]
+    fn fractional_exponents() {
+        for expr in ["V/Hz^(1/2)", "V/Hz^1/2", "V⋅Hz⁻¹ᐟ²", "V*Hz^-1/2"] {
+            assert_eq!(parse(expr).unwrap().to_string(), "V⋅Hz⁻¹ᐟ²", "{expr}");
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let u = parse("kg⋅m²⋅s⁻³⋅A⁻¹").unwrap();
+        assert!(u.is_commensurable_with(&crate::units::VOLT));
+        assert_eq!(parse(&u.to_string()).unwrap(), u);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("furlong"),
+            Err(Error::UnknownUnit("furlong".to_string()))
+        );
+        assert!(matches!(parse("m/"), Err(Error::UnknownUnit(_))));
+        assert!(matches!(parse("m^"), Err(Error::UnknownUnit(_))));
+        assert!(matches!(parse("(m"), Err(Error::UnknownUnit(_))));
+        assert!(matches!(parse("m)"), Err(Error::UnknownUnit(_))));
+        assert!(matches!(parse("2"), Err(Error::UnknownUnit(_))));
+        assert!(matches!(parse("tempC/s"), Err(Error::NonzeroZeroPoint(_))));
+        assert_eq!(parse("m^99999999999"), Err(Error::ExponentTooLarge));
+        assert_eq!(parse("m99999999999"), Err(Error::ExponentTooLarge));
+        assert_eq!(parse("s^-1/99999999999"), Err(Error::ExponentTooLarge));
+        for expr in ["0x8000000000000000", "0b1111111111111111111111111111111111"] {
+            assert!(matches!(parse(expr), Err(Error::UnknownUnit(_))), "{expr}");
+        }
+    }
+
+    #[test]
+    fn zero_exponents() {
+        for expr in ["m^0", "m0", "m-0", "(m/s)^0", "N⋅s⁰", "s^(0/2)"] {
+            assert_eq!(parse(expr), Err(Error::ExponentIsZero), "{expr}");
+        }
+    }
+
+    #[test]
+    fn reciprocals() {
+        let u = parse("1/s").unwrap();
+        assert_eq!(u, SECOND.as_unit().inverse().unwrap().with_symbol("s⁻¹"));
+        assert_eq!(parse("1/s").unwrap(), parse("s^-1").unwrap());
+        assert_eq!(parse("1/s²").unwrap().to_string(), "s⁻²");
+        assert_eq!(parse("(1/s)*m").unwrap().to_string(), "m⋅s⁻¹");
+        assert_eq!(parse("1/m/s").unwrap().to_string(), "m⁻¹⋅s⁻¹");
+        for expr in ["1", "1/", "2/s", "m*1/s"] {
+            assert!(matches!(parse(expr), Err(Error::UnknownUnit(_))), "{expr}");
+        }
+    }
+}
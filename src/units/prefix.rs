@@ -0,0 +1,163 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! SI prefixes.
+
+use super::{Unit, SYMBOLS, TONNE};
+
+/// Engineering prefixes and their scales, from largest to smallest. The empty
+/// prefix is included so that unprefixed units take part in scaling.
+const PREFIXES: [(&str, f64); 11] = [
+    ("P", 1e15),
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("", 1.0),
+    ("m", 1e-3),
+    ("u", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+    ("f", 1e-15),
+];
+
+/// Prefixes that aren't powers of a thousand. Units with these prefixes are
+/// recognized but never produced.
+const OTHER_PREFIXES: [(&str, f64); 1] = [("c", 1e-2)];
+
+/// Returns every engineering-prefixed variant of `unit`, from largest to
+/// smallest. Variants defined in `SYMBOLS` are used as they are; the rest are
+/// made by rescaling `unit`. For example, the family of `uF` is `PF` through
+/// `fF`, and the family of `ks` is `Ps` through `fs`, including `s`. In the
+/// family of grams, `t` takes the place of `Mg`.
+///
+/// Returns an empty vector if `unit` has no symbol and isn't a base unit, or
+/// if no other variant of it is defined in `SYMBOLS`. This keeps units like
+/// `mi` and `min` from being mistaken for prefixed units.
+#[must_use]
+pub(super) fn family(unit: &Unit) -> Vec<Unit> {
+    let Some(symbol) = symbol(unit) else {
+        return vec![];
+    };
+    let symbol = if symbol == TONNE.symbol { "Mg" } else { symbol };
+
+    PREFIXES
+        .iter()
+        .chain(OTHER_PREFIXES.iter())
+        .find_map(|(prefix, scale)| {
+            let root = symbol.strip_prefix(prefix)?;
+            if root.is_empty() {
+                None
+            } else {
+                family_with_root(unit, root, *scale)
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Helper for `family`. Returns the variants of `unit` formed by prefixing
+/// `root`, given that `unit` is `root` with a prefix of magnitude `scale`, or
+/// `None` if that isn't consistent with the units in `SYMBOLS`.
+fn family_with_root(unit: &Unit, root: &str, scale: f64) -> Option<Vec<Unit>> {
+    let symbol = symbol(unit);
+    let mut defined = false;
+    let mut family = Vec::with_capacity(PREFIXES.len());
+
+    for (prefix, s) in PREFIXES {
+        let name = format!("{prefix}{root}");
+        let ratio = s / scale;
+        if let Some(variant) = SYMBOLS.get(name.as_str()) {
+            let actual = variant.convert(1.0, unit).ok()?;
+            if ((actual - ratio) / ratio).abs() > 1e-9 {
+                return None;
+            }
+            defined |= Some(name.as_str()) != symbol;
+            family.push(variant.clone());
+        } else {
+            family.push(
+                unit.with_constant(unit.constant() * ratio)
+                    .with_symbol(&name),
+            );
+        }
+    }
+
+    // A megagram is usually called a tonne.
+    if root == "g" {
+        let mega = PREFIXES.iter().position(|(prefix, _)| *prefix == "M")?;
+        family[mega] = TONNE.as_unit();
+    }
+
+    defined.then_some(family)
+}
+
+/// Helper for `family`. Returns the symbol of `unit`, or of its base unit if
+/// it has no symbol of its own but is simply a base unit like `m`.
+#[allow(clippy::float_cmp)] // only a base unit that isn't scaled at all will do
+fn symbol(unit: &Unit) -> Option<&str> {
+    unit.symbol.as_deref().or_else(|| {
+        unit.sole_base()
+            .filter(|_| unit.constant() == 1.0)
+            .map(|b| b.symbol)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::family;
+    use crate::units::{
+        Unit, FARAD, FOOT, KILOGRAM, KILOSECOND, METER, MICROFARAD, MILE, MINUTE, TONNE, WATT,
+    };
+
+    fn symbols(unit: &Unit) -> Vec<String> {
+        family(unit).into_iter().map(|u| u.to_string()).collect()
+    }
+
+    #[test]
+    fn families() {
+        let farads = [
+            "PF", "TF", "GF", "MF", "kF", "F", "mF", "uF", "nF", "pF", "fF",
+        ];
+        assert_eq!(symbols(&FARAD), farads);
+        assert_eq!(symbols(&MICROFARAD), farads);
+        assert_eq!(
+            symbols(&KILOSECOND.as_unit()),
+            ["Ps", "Ts", "Gs", "Ms", "ks", "s", "ms", "us", "ns", "ps", "fs"]
+        );
+        assert_eq!(symbols(&METER.as_unit())[4], "km");
+        assert_eq!(
+            symbols(&KILOGRAM.as_unit()),
+            ["Pg", "Tg", "Gg", "t", "kg", "g", "mg", "ug", "ng", "pg", "fg"]
+        );
+        assert_eq!(symbols(&TONNE.as_unit())[3], "t");
+    }
+
+    #[test]
+    fn synthesized_variants() {
+        let watts = family(&WATT);
+        assert_eq!(watts[5], *WATT);
+        let exameters = METER.as_unit().with_constant(1e18).with_symbol("Em");
+        assert!(family(&exameters).is_empty());
+    }
+
+    #[test]
+    fn unprefixed_units() {
+        assert!(family(&FOOT.as_unit()).is_empty());
+        assert!(family(&MILE.as_unit()).is_empty());
+        assert!(family(&MINUTE.as_unit()).is_empty());
+        assert!(family(&(&*FARAD * &*FARAD).unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rational numbers for unit exponents.
+
+/// The largest denominator `Rational::from_f64` will search for.
+const MAX_DENOMINATOR: i32 = 100;
+
+/// A fraction in lowest terms with a positive denominator. Arithmetic is
+/// checked and returns `None` on overflow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: i32,
+    denom: i32,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational::from_integer(0);
+    pub const ONE: Rational = Rational::from_integer(1);
+
+    /// Returns the fraction `numer`/`denom` in lowest terms, or `None` if
+    /// `denom` is zero or the result can't be represented.
+    #[must_use]
+    pub fn new(numer: i32, denom: i32) -> Option<Rational> {
+        Self::reduced(i64::from(numer), i64::from(denom))
+    }
+
+    /// Returns the integer `i` as a fraction.
+    #[must_use]
+    pub const fn from_integer(i: i32) -> Rational {
+        Rational { numer: i, denom: 1 }
+    }
+
+    /// Returns the fraction with the smallest denominator that is exactly
+    /// equal to `x` in floating point, or `None` if there isn't one with a
+    /// reasonably small denominator.
+    #[must_use]
+    pub fn from_f64(x: f64) -> Option<Rational> {
+        if !x.is_finite() {
+            return None;
+        }
+        for denom in 1..=MAX_DENOMINATOR {
+            let numer = (x * f64::from(denom)).round();
+            if numer.abs() > f64::from(i32::MAX) {
+                return None;
+            }
+            // Only a fraction that gives back exactly `x` will do.
+            #[allow(clippy::float_cmp)]
+            let exact = numer / f64::from(denom) == x;
+            if exact {
+                // allowed because we tested the magnitude above
+                #[allow(clippy::cast_possible_truncation)]
+                return Rational::new(numer as i32, denom);
+            }
+        }
+        None
+    }
+
+    #[must_use]
+    pub fn numer(&self) -> i32 {
+        self.numer
+    }
+
+    #[must_use]
+    pub fn denom(&self) -> i32 {
+        self.denom
+    }
+
+    /// Returns true if this fraction has a denominator of 1.
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    #[must_use]
+    pub fn is_positive(&self) -> bool {
+        self.numer > 0
+    }
+
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.numer < 0
+    }
+
+    /// Returns the absolute value of this fraction, or `None` on overflow.
+    #[must_use]
+    pub fn checked_abs(&self) -> Option<Rational> {
+        self.numer.checked_abs().map(|numer| Rational {
+            numer,
+            denom: self.denom,
+        })
+    }
+
+    #[must_use]
+    pub fn checked_neg(&self) -> Option<Rational> {
+        self.numer.checked_neg().map(|numer| Rational {
+            numer,
+            denom: self.denom,
+        })
+    }
+
+    #[must_use]
+    pub fn checked_add(&self, other: Rational) -> Option<Rational> {
+        Self::reduced(
+            i64::from(self.numer) * i64::from(other.denom)
+                + i64::from(other.numer) * i64::from(self.denom),
+            i64::from(self.denom) * i64::from(other.denom),
+        )
+    }
+
+    #[must_use]
+    pub fn checked_mul(&self, other: Rational) -> Option<Rational> {
+        Self::reduced(
+            i64::from(self.numer) * i64::from(other.numer),
+            i64::from(self.denom) * i64::from(other.denom),
+        )
+    }
+
+    /// Returns `self`/`other`, or `None` if `other` is zero or on overflow.
+    #[must_use]
+    pub fn checked_div(&self, other: Rational) -> Option<Rational> {
+        Self::reduced(
+            i64::from(self.numer) * i64::from(other.denom),
+            i64::from(self.denom) * i64::from(other.numer),
+        )
+    }
+
+    #[must_use]
+    pub fn to_f64(&self) -> f64 {
+        f64::from(self.numer) / f64::from(self.denom)
+    }
+
+    /// Raises `x` to the power of this fraction. Integral powers use `powi`
+    /// so that conversions with integral exponents are as exact as possible.
+    #[must_use]
+    pub fn raise(&self, x: f64) -> f64 {
+        if self.is_integer() {
+            x.powi(self.numer)
+        } else {
+            x.powf(self.to_f64())
+        }
+    }
+
+    /// Helper for constructors and arithmetic. Reduces `numer`/`denom` to
+    /// lowest terms with a positive denominator.
+    fn reduced(numer: i64, denom: i64) -> Option<Rational> {
+        if denom == 0 {
+            return None;
+        }
+        let divisor = gcd(numer, denom) * denom.signum();
+        Some(Rational {
+            numer: i32::try_from(numer / divisor).ok()?,
+            denom: i32::try_from(denom / divisor).ok()?,
+        })
+    }
+}
+
+/// Greatest common divisor of the magnitudes of `a` and `b`.
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl From<i32> for Rational {
+    fn from(i: i32) -> Self {
+        Rational::from_integer(i)
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rational;
+
+    #[test]
+    fn reduction() {
+        assert_eq!(Rational::new(2, 4), Rational::new(1, 2));
+        assert_eq!(Rational::new(3, -6), Rational::new(-1, 2));
+        assert_eq!(Rational::new(0, -6), Some(Rational::ZERO));
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(Rational::new(i32::MIN, -1), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let half = Rational::new(1, 2).unwrap();
+        let third = Rational::new(1, 3).unwrap();
+        assert_eq!(half.checked_add(third), Rational::new(5, 6));
+        assert_eq!(half.checked_mul(third), Rational::new(1, 6));
+        assert_eq!(half.checked_div(third), Rational::new(3, 2));
+        assert_eq!(half.checked_div(Rational::ZERO), None);
+        assert_eq!(half.checked_add(half), Some(Rational::ONE));
+        assert_eq!(
+            Rational::from(i32::MAX).checked_mul(Rational::from(2)),
+            None
+        );
+    }
+
+    #[test]
+    fn from_f64() {
+        assert_eq!(Rational::from_f64(0.5), Rational::new(1, 2));
+        assert_eq!(Rational::from_f64(-1.5), Rational::new(-3, 2));
+        assert_eq!(Rational::from_f64(1.0 / 3.0), Rational::new(1, 3));
+        assert_eq!(Rational::from_f64(7.0), Some(Rational::from(7)));
+        assert_eq!(Rational::from_f64(std::f64::consts::PI), None);
+        assert_eq!(Rational::from_f64(1e100), None);
+        assert_eq!(Rational::from_f64(f64::NAN), None);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Rational::new(-1, 2).unwrap().to_string(), "-1/2");
+        assert_eq!(Rational::from(3).to_string(), "3");
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Systems of units.
+
+use once_cell::sync::Lazy;
+
+use super::{
+    Base, Number, PhysicalQuantity, Unit, AMPERE, BARYE, CANDELA, CENTIMETER, DEG_CELSIUS,
+    DEG_FAHRENHEIT, DYNE, ERG, FOOT, FOOT_POUND_FORCE, GAUSS, GRAM, KELVIN, KILOGRAM, MAXWELL,
+    METER, MOLE, NAMEABLE, POUND_FORCE, POUND_MASS, PSI, RADIAN, RANKINE, SECOND, TEMP_FAHRENHEIT,
+};
+
+/// A system of units: a base unit for each physical quantity, and named units
+/// that results may be expressed in. Currencies don't belong to any system.
+#[derive(Clone, Debug)]
+pub struct System {
+    /// The system's name, e.g. "SI".
+    pub name: &'static str,
+    /// One base unit per physical quantity, except currency.
+    bases: Vec<Base>,
+    /// The scale temperatures are expressed in, like K or tempF.
+    temperature: Base,
+    /// The unit differences between temperatures are expressed in, like degC.
+    interval: Base,
+    /// Named derived units belonging to this system. No two of these units
+    /// are commensurable with each other.
+    pub named: Vec<Unit>,
+}
+
+impl System {
+    /// Returns this system's base unit for `physq`, or `None` if the system
+    /// doesn't have one.
+    #[must_use]
+    pub fn base(&self, physq: PhysicalQuantity) -> Option<Base> {
+        self.bases.iter().find(|b| b.physq == physq).copied()
+    }
+
+    /// Returns true if `unit` is made up entirely of this system's base
+    /// units.
+    #[must_use]
+    pub fn contains(&self, unit: &Unit) -> bool {
+        unit.bases()
+            .iter()
+            .all(|(b, _)| Some(*b) == self.base(b.physq))
+    }
+
+    /// Returns a `Number` equal to `x` but with every base unit replaced by
+    /// this system's base unit for the same physical quantity. For example,
+    /// in SI, ft² becomes m² and tempC becomes K. Base units the system has no
+    /// counterpart for, like currencies, are kept. The result has no symbol.
+    ///
+    /// A temperature is expressed on the system's temperature scale, so in US
+    /// customary units tempC becomes tempF, and a temperature interval like
+    /// degC is expressed in the system's interval unit. Numbers without units,
+    /// and numbers in units made from temperature intervals, like ppm/degC,
+    /// are returned unchanged.
+    #[must_use]
+    pub fn normalize(&self, x: &Number) -> Number {
+        let Some(unit) = x.unit.as_ref() else {
+            return x.clone();
+        };
+        if let Some(base) = unit
+            .sole_base()
+            .filter(|b| b.physq == PhysicalQuantity::Temperature)
+        {
+            let target = if base.zero.is_none() {
+                self.interval
+            } else {
+                self.temperature
+            };
+            return unit.convert(x.value, &target.as_unit()).map_or_else(
+                |_| x.clone(),
+                |v| Number::new(v).with_unit(target.as_unit()),
+            );
+        }
+        if unit.bases().iter().any(|(b, _)| b.zero.is_none()) {
+            return x.clone();
+        }
+
+        let exponents = unit
+            .bases()
+            .iter()
+            .map(|(b, exp)| (self.base(b.physq).unwrap_or(*b), *exp));
+        Unit::from_exponents(exponents)
+            .and_then(|u| Ok(Number::new(unit.convert(x.value, &u)?).with_unit(u)))
+            .unwrap_or_else(|_| x.clone())
+    }
+}
+
+/// The International System of Units.
+pub static SI: Lazy<System> = Lazy::new(|| System {
+    name: "SI",
+    bases: vec![
+        SECOND, METER, KILOGRAM, AMPERE, KELVIN, MOLE, CANDELA, RADIAN,
+    ],
+    temperature: KELVIN,
+    interval: DEG_CELSIUS,
+    named: NAMEABLE.clone(),
+});
+
+/// United States customary units.
+pub static US_CUSTOMARY: Lazy<System> = Lazy::new(|| System {
+    name: "US customary",
+    bases: vec![
+        SECOND, FOOT, POUND_MASS, AMPERE, RANKINE, MOLE, CANDELA, RADIAN,
+    ],
+    temperature: TEMP_FAHRENHEIT,
+    interval: DEG_FAHRENHEIT,
+    named: vec![FOOT_POUND_FORCE.clone(), POUND_FORCE.clone(), PSI.clone()],
+});
+
+/// The centimeter-gram-second system.
+pub static CGS: Lazy<System> = Lazy::new(|| System {
+    name: "CGS",
+    bases: vec![
+        SECOND, CENTIMETER, GRAM, AMPERE, KELVIN, MOLE, CANDELA, RADIAN,
+    ],
+    temperature: KELVIN,
+    interval: DEG_CELSIUS,
+    named: vec![
+        DYNE.clone(),
+        ERG.clone(),
+        BARYE.clone(),
+        GAUSS.clone(),
+        MAXWELL.clone(),
+    ],
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{CGS, SI, US_CUSTOMARY};
+    use crate::units::{
+        Number, Unit, DEG_CELSIUS, DEG_FAHRENHEIT, FOOT, INCH, JOULE, KELVIN, METER, RANKINE,
+        SECOND, TEMP_CELSIUS, TEMP_FAHRENHEIT,
+    };
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn normalize() {
+        let x = Number::new(2.0).with_unit(Unit::new(&[FOOT, INCH], &[]).unwrap());
+        let y = SI.normalize(&x);
+        assert_relative_eq!(y.value, 2.0 * 0.3048 * 0.0254);
+        assert_eq!(y.unit.unwrap(), Unit::new(&[METER, METER], &[]).unwrap());
+
+        let y = US_CUSTOMARY.normalize(&Number::new(1.0).with_unit(METER.as_unit()));
+        assert_relative_eq!(y.value, 1.0 / 0.3048);
+
+        let y = CGS.normalize(&Number::new(1.0).with_unit(JOULE.clone()));
+        assert_relative_eq!(y.value, 1e7);
+        assert!(CGS.contains(y.unit.as_ref().unwrap()));
+
+        let y = SI.normalize(&Number::new(20.0).with_unit(TEMP_CELSIUS.as_unit()));
+        assert_relative_eq!(y.value, 293.15);
+    }
+
+    #[test]
+    fn normalize_temperatures() {
+        // Temperatures are converted to the system's temperature scale, zero
+        // point and all.
+        let y = US_CUSTOMARY.normalize(&Number::new(20.0).with_unit(TEMP_CELSIUS.as_unit()));
+        assert_relative_eq!(y.value, 68.0, epsilon = 1e-9);
+        assert_eq!(y.unit.unwrap(), TEMP_FAHRENHEIT.as_unit());
+
+        let y = US_CUSTOMARY.normalize(&Number::new(527.67).with_unit(RANKINE.as_unit()));
+        assert_relative_eq!(y.value, 68.0, epsilon = 1e-9);
+
+        let y = SI.normalize(&Number::new(100.0).with_unit(RANKINE.as_unit()));
+        assert_relative_eq!(y.value, 100.0 * 5.0 / 9.0);
+        assert_eq!(y.unit.unwrap(), KELVIN.as_unit());
+
+        // Intervals are converted to the system's interval unit.
+        let y = US_CUSTOMARY.normalize(&Number::new(10.0).with_unit(DEG_CELSIUS.as_unit()));
+        assert_relative_eq!(y.value, 18.0, epsilon = 1e-9);
+        assert_eq!(y.unit.unwrap(), DEG_FAHRENHEIT.as_unit());
+
+        let y = CGS.normalize(&Number::new(18.0).with_unit(DEG_FAHRENHEIT.as_unit()));
+        assert_relative_eq!(y.value, 10.0, epsilon = 1e-9);
+        assert_eq!(y.unit.unwrap(), DEG_CELSIUS.as_unit());
+    }
+
+    #[test]
+    fn normalize_unchanged() {
+        let x = Number::new(20.0).with_unit(DEG_CELSIUS.as_unit());
+        assert_eq!(SI.normalize(&x).to_string(), "[20 degC]");
+        assert_eq!(SI.normalize(&Number::new(3.0)).to_string(), "3");
+        let x = Number::new(3.0).with_unit(SECOND.as_unit());
+        assert_eq!(CGS.normalize(&x).to_string(), "[3 s]");
+    }
+}
@@ -15,17 +15,21 @@
 // You should have received a copy of the GNU General Public License along with
 // calc. If not, see <https://www.gnu.org/licenses/>.
 
-use super::{base::NUM_PHYSICAL_QUANTITIES, Base, Error};
+use super::{Base, Dimension, Error, Rational};
 #[allow(clippy::enum_glob_use)]
 use Error::*;
 
 /// A unit that may measure a base quantity or a derived quantity.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Unit {
     /// Symbolic representation of the unit
     pub symbol: Option<String>,
-    numer: Vec<Base>,
-    denom: Vec<Base>,
+    /// Base units and their exponents, in the order in which each base unit
+    /// was first encountered. No base unit appears more than once and no
+    /// exponent is zero.
+    bases: Vec<(Base, Rational)>,
+    /// The sum of the exponents of each physical quantity in `bases`.
+    dimension: Dimension,
     constant: f64,
 }
 
@@ -48,30 +52,60 @@ impl Unit {
     /// - there is more than one base unit; or,
     /// - the denominator is not empty.
     pub fn new(numer: &[Base], denom: &[Base]) -> Result<Self, Error> {
-        let u = Unit {
-            symbol: None,
-            numer: Vec::from(numer),
-            denom: Vec::from(denom),
-            constant: 1.0,
+        Self::from_exponents(
+            numer
+                .iter()
+                .map(|b| (*b, Rational::ONE))
+                .chain(denom.iter().map(|b| (*b, Rational::from(-1)))),
+        )
+    }
+
+    /// Derives a unit from base units and their exponents. Base units may
+    /// appear more than once, in which case their exponents are summed. Base
+    /// units whose exponents sum to zero are removed from the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - one of the base units has a non-zero zero point and it isn't the only
+    ///   base unit or its exponent isn't 1; or,
+    /// - an exponent is too large to represent.
+    pub fn from_exponents(
+        exponents: impl IntoIterator<Item = (Base, Rational)>,
+    ) -> Result<Self, Error> {
+        let mut bases: Vec<(Base, Rational)> = Vec::new();
+
+        for (base, exp) in exponents {
+            if let Some(entry) = bases.iter_mut().find(|(b, _)| *b == base) {
+                entry.1 = entry.1.checked_add(exp).ok_or(ExponentTooLarge)?;
+            } else {
+                bases.push((base, exp));
+            }
         }
-        .simplified();
+        bases.retain(|(_, exp)| *exp != Rational::ZERO);
 
-        for base in &u.numer {
+        for (base, exp) in &bases {
             if base.zero.is_some()
                 && base.zero != Some(0.0)
-                && (u.numer.len() > 1 || !u.denom.is_empty())
+                && (bases.len() > 1 || *exp != Rational::ONE)
             {
                 return Err(NonzeroZeroPoint(*base));
             }
         }
 
-        for base in &u.denom {
-            if base.zero.is_some() && base.zero != Some(0.0) {
-                return Err(NonzeroZeroPoint(*base));
-            }
+        let mut dimension = Dimension::none();
+        for (base, exp) in &bases {
+            dimension[base.physq] = dimension[base.physq]
+                .checked_add(*exp)
+                .ok_or(ExponentTooLarge)?;
         }
 
-        Ok(u)
+        Ok(Unit {
+            symbol: None,
+            bases,
+            dimension,
+            constant: 1.0,
+        })
     }
 
     /// Returns a new `Unit` identical to this one except that it has the given
@@ -80,8 +114,8 @@ impl Unit {
     pub fn with_symbol(&self, symbol: &str) -> Self {
         Unit {
             symbol: Some(String::from(symbol)),
-            numer: self.numer.clone(),
-            denom: self.denom.clone(),
+            bases: self.bases.clone(),
+            dimension: self.dimension,
             constant: self.constant,
         }
     }
@@ -92,26 +126,79 @@ impl Unit {
     pub fn with_constant(&self, constant: f64) -> Self {
         Unit {
             symbol: self.symbol.clone(),
-            numer: self.numer.clone(),
-            denom: self.denom.clone(),
+            bases: self.bases.clone(),
+            dimension: self.dimension,
             constant,
         }
     }
 
+    /// Returns this unit's base units and their exponents, in the order in
+    /// which each base unit was first encountered.
+    #[must_use]
+    pub fn bases(&self) -> &[(Base, Rational)] {
+        &self.bases
+    }
+
+    /// Returns the exponent of each physical quantity measured by this unit.
+    #[must_use]
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    /// Returns true if this unit has no base units, i.e. it is nothing more
+    /// than a constant.
+    #[must_use]
+    pub fn is_scalar(&self) -> bool {
+        self.bases.is_empty()
+    }
+
+    /// Returns true if this unit is a named ratio like `%`: a constant with a
+    /// symbol.
+    #[must_use]
+    pub fn is_ratio(&self) -> bool {
+        self.is_scalar() && self.symbol.is_some()
+    }
+
     /// Return a vector of this unit's base units that have positive exponents.
     /// The number of times a base unit appears in the vector indicates the
     /// magnitude of its exponent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the exponents is fractional, since a base unit can't
+    /// appear a fractional number of times.
     #[must_use]
-    pub fn numer(&self) -> &Vec<Base> {
-        &self.numer
+    pub fn numer(&self) -> Vec<Base> {
+        self.expand(Rational::is_positive)
     }
 
     /// Return a vector of this unit's base units that have negative exponents.
     /// The number of times a base unit appears in the vector indicates the
     /// magnitude of its exponent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the exponents is fractional, since a base unit can't
+    /// appear a fractional number of times.
     #[must_use]
-    pub fn denom(&self) -> &Vec<Base> {
-        &self.denom
+    pub fn denom(&self) -> Vec<Base> {
+        self.expand(Rational::is_negative)
+    }
+
+    /// Helper for `numer` and `denom`. Repeats each base whose exponent
+    /// satisfies `sign` according to the magnitude of its exponent.
+    fn expand(&self, sign: impl Fn(&Rational) -> bool) -> Vec<Base> {
+        assert!(
+            self.bases.iter().all(|(_, exp)| exp.is_integer()),
+            "{self} has fractional exponents"
+        );
+        let mut result = Vec::new();
+        for (base, exp) in self.bases.iter().filter(|(_, exp)| sign(exp)) {
+            for _ in 0..exp.numer().unsigned_abs() {
+                result.push(*base);
+            }
+        }
+        result
     }
 
     #[must_use]
@@ -119,6 +206,15 @@ impl Unit {
         self.constant
     }
 
+    /// Returns this unit's only base unit if it consists of exactly one base
+    /// unit with an exponent of 1.
+    pub(super) fn sole_base(&self) -> Option<Base> {
+        match self.bases.as_slice() {
+            [(base, exp)] if *exp == Rational::ONE => Some(*base),
+            _ => None,
+        }
+    }
+
     /// Converts a number in this unit to another unit.
     ///
     /// # Errors
@@ -135,26 +231,16 @@ impl Unit {
         }
 
         // Reduce to SI
-        for base in &self.numer {
-            if let Some(z) = base.zero {
-                num -= z;
-            }
-            num *= base.factor;
-        }
-        for base in &self.denom {
-            num /= base.factor;
+        if let Some(z) = self.sole_base().and_then(|b| b.zero) {
+            num -= z;
         }
+        num = scale(num, &self.bases, false);
         num *= self.constant;
 
         // Raise to new unit
-        for base in &other.numer {
-            num /= base.factor;
-            if let Some(z) = base.zero {
-                num += z;
-            }
-        }
-        for base in &other.denom {
-            num *= base.factor;
+        num = scale(num, &other.bases, true);
+        if let Some(z) = other.sole_base().and_then(|b| b.zero) {
+            num += z;
         }
         num /= other.constant;
 
@@ -166,61 +252,75 @@ impl Unit {
     ///
     /// # Errors
     ///
-    /// Returns an error if the unit has a zero point. Inversion of these units
-    /// is nonsensical.
+    /// Returns an error if:
+    /// - the unit has a zero point, since inversion of these units is
+    ///   nonsensical; or,
+    /// - an exponent is too large to represent.
     pub fn inverse(&self) -> Result<Self, Error> {
-        if !self.numer.is_empty() && self.numer[0].zero.is_some() && self.numer[0].zero != Some(0.0)
-        {
-            return Err(UninvertableUnits(Box::new(self.clone())));
+        if let Some(b) = self.sole_base() {
+            if b.zero.is_some() && b.zero != Some(0.0) {
+                return Err(UninvertableUnits(Box::new(self.clone())));
+            }
+        }
+        let mut exponents = Vec::with_capacity(self.bases.len());
+        for (base, exp) in &self.bases {
+            exponents.push((*base, exp.checked_neg().ok_or(ExponentTooLarge)?));
         }
-        Self::new(self.denom.as_slice(), self.numer.as_slice())
+        Self::from_exponents(exponents)
     }
 
-    /// Helper function for `is_commensurable_with`. Returns true if each
-    /// physical quantity occurs the same number of times in both sequences.
-    fn physq_counts_match(a: &Vec<Base>, b: &Vec<Base>) -> bool {
-        let mut counts = (
-            [0_usize; NUM_PHYSICAL_QUANTITIES],
-            [0_usize; NUM_PHYSICAL_QUANTITIES],
-        );
-
-        for base in a {
-            counts.0[base.physq as usize] += 1;
-        }
-        for base in b {
-            counts.1[base.physq as usize] += 1;
+    /// Returns this unit raised to the power of `power`. The exponents of the
+    /// base units are multiplied by `power`, which may produce fractional
+    /// exponents. Raising a unit to the power of 1 returns the unit unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - the unit has a zero point and `power` isn't 1; or,
+    /// - an exponent is too large to represent.
+    pub fn pow(&self, power: Rational) -> Result<Self, Error> {
+        if power == Rational::ONE {
+            return Ok(self.clone());
         }
-
-        for i in 0..NUM_PHYSICAL_QUANTITIES {
-            if counts.0[i] != counts.1[i] {
-                return false;
-            }
+        let mut exponents = Vec::with_capacity(self.bases.len());
+        for (base, exp) in &self.bases {
+            exponents.push((*base, exp.checked_mul(power).ok_or(ExponentTooLarge)?));
         }
+        Self::from_exponents(exponents).map(|u| u.with_constant(power.raise(self.constant)))
+    }
 
-        true
+    /// Returns the `degree`th root of this unit. The exponents of the base
+    /// units are divided by `degree`, which may produce fractional exponents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `degree` is zero;
+    /// - the unit has a zero point and `degree` isn't 1; or,
+    /// - an exponent is too large to represent.
+    pub fn root(&self, degree: Rational) -> Result<Self, Error> {
+        Rational::ONE
+            .checked_div(degree)
+            .ok_or(DegreeIsZero)
+            .and_then(|power| self.pow(power))
     }
 
     /// Determines whether a quantity in this unit can be converted to another unit.
     #[must_use]
     pub fn is_commensurable_with(&self, other: &Unit) -> bool {
-        // If number of occurrences of each physical quantity in the numerators
-        // differs, then the units are incommensurable; likewise for the
-        // denominators.
-        if !(Unit::physq_counts_match(&self.numer, &other.numer)
-            && Unit::physq_counts_match(&self.denom, &other.denom))
-        {
+        // If the exponent of any physical quantity differs, then the units are
+        // incommensurable.
+        if self.dimension != other.dimension {
             return false;
         }
 
         // By contract we assume that any unit with a temperature base (z != 0)
-        // consists solely of that base, in the numerator. If that isn't the
-        // case with these units, there's nothing left to check.
-        if !(self.numer.len() == 1 && self.denom.is_empty()) {
+        // consists solely of that base. If that isn't the case with these
+        // units, there's nothing left to check.
+        let (Some(a), Some(b)) = (self.sole_base(), other.sole_base()) else {
             return true;
-        }
-
-        let a = self.numer[0].zero;
-        let b = other.numer[0].zero;
+        };
+        let (a, b) = (a.zero, b.zero);
 
         // Conversion to or from Kelvin or Rankine is always allowed.
         if a == Some(0.0) || b == Some(0.0) {
@@ -232,44 +332,61 @@ impl Unit {
         (a.is_some() && b.is_some()) || (a.is_none() && b.is_none())
     }
 
-    /// Returns a new `Unit` mathematically identical to this one but without
-    /// any base units that cancel each other out.
-    fn simplified(&self) -> Self {
-        let mut s_numer = Vec::from(self.numer.as_slice());
-        let mut s_denom = Vec::from(self.denom.as_slice());
-        let mut numer_ix = 0;
-        let mut should_incr: bool;
-
-        while numer_ix < s_numer.len() {
-            should_incr = true;
-
-            for denom_ix in 0..s_denom.len() {
-                if s_numer[numer_ix] == s_denom[denom_ix] {
-                    s_numer.remove(numer_ix);
-                    s_denom.remove(denom_ix);
-                    should_incr = false;
-                    break;
-                }
-            }
-
-            if should_incr {
-                numer_ix += 1;
-            }
+    /// Returns the first of `candidates` that is made of the same base units
+    /// as this unit, so that naming a quantity never changes the units it is
+    /// measured in: kg⋅m²⋅s⁻² can be named J, but lb⋅ft²⋅s⁻² can't. Returns
+    /// `None` if this unit already has a symbol or if none of the candidates
+    /// match.
+    #[must_use]
+    pub fn named<'a>(&self, candidates: &'a [Unit]) -> Option<&'a Unit> {
+        if self.symbol.is_some() {
+            return None;
         }
+        candidates.iter().find(|c| {
+            c.bases.len() == self.bases.len() && self.bases.iter().all(|b| c.bases.contains(b))
+        })
+    }
+}
 
-        Unit {
-            symbol: self.symbol.clone(),
-            numer: s_numer,
-            denom: s_denom,
-            constant: self.constant,
+impl PartialEq for Unit {
+    /// Units are equal if they have the same symbol, constant, and base units
+    /// with the same exponents, regardless of the order of the base units.
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+            && self.constant == other.constant
+            && self.dimension == other.dimension
+            && self.bases.len() == other.bases.len()
+            && self.bases.iter().all(|b| other.bases.contains(b))
+    }
+}
+
+/// Helper for `convert`. Multiplies `num` by the factor of each base raised to
+/// its exponent, or divides if `inverse` is true. Bases with positive
+/// exponents are applied before bases with negative exponents.
+fn scale(num: f64, bases: &[(Base, Rational)], inverse: bool) -> f64 {
+    let mut num = num;
+    for (base, exp) in bases.iter().filter(|(_, exp)| exp.is_positive()) {
+        if inverse {
+            num /= exp.raise(base.factor);
+        } else {
+            num *= exp.raise(base.factor);
         }
     }
+    for (base, exp) in bases.iter().filter(|(_, exp)| exp.is_negative()) {
+        let exp = exp.checked_neg().unwrap_or(*exp);
+        if inverse {
+            num *= exp.raise(base.factor);
+        } else {
+            num /= exp.raise(base.factor);
+        }
+    }
+    num
 }
 
 const SUPERSCRIPTS: [&str; 10] = ["⁰", "¹", "²", "³", "⁴", "⁵", "⁶", "⁷", "⁸", "⁹"];
 
 /// Turns an integer `i` into a string using superscript digits.
-fn i_to_str_superscripts(i: usize) -> String {
+fn i_to_str_superscripts(i: u32) -> String {
     let mut result = String::new();
     for ch in i.to_string().chars() {
         result.push_str(SUPERSCRIPTS[(ch as usize) - ('0' as usize)]);
@@ -277,46 +394,61 @@ fn i_to_str_superscripts(i: usize) -> String {
     result
 }
 
-/// Given a sequence of bases, generates a string like "m²⋅A¹⋅s¹". Each exponent
-/// is prefixed with `sign`.
-fn bases_to_string(bases: &[Base], sign: Option<char>) -> Option<String> {
-    if bases.is_empty() {
-        return None;
+/// Turns an exponent into a string of superscript characters, like "⁻¹ᐟ²".
+pub(super) fn exponent_to_superscripts(exp: Rational) -> String {
+    let mut result = String::new();
+    if exp.is_negative() {
+        result.push('⁻');
     }
+    result.push_str(&i_to_str_superscripts(exp.numer().unsigned_abs()));
+    if !exp.is_integer() {
+        result.push('ᐟ');
+        result.push_str(&i_to_str_superscripts(exp.denom().unsigned_abs()));
+    }
+    result
+}
 
-    // Count the number of times each base occurs, preserving the order in
-    // in which each base is first encountered.
-    let mut uniq_bases: Vec<Base> = Vec::new();
-    let mut counts: Vec<usize> = Vec::new();
+/// Given a sequence of symbols and exponents, generates a string like
+/// "m²⋅A⋅s⁻¹ᐟ²". Symbols with positive exponents come first, followed by
+/// symbols with negative exponents, each in their original order. Returns
+/// `None` if `factors` is empty.
+pub(super) fn exponents_to_string(factors: &[(&str, Rational)]) -> Option<String> {
+    let pos = factors_to_string(factors, None);
+    let neg = factors_to_string(factors, Some('⁻'));
 
-    for base in bases {
-        let ix = uniq_bases
-            .iter()
-            .position(|b| b == base)
-            .unwrap_or_else(|| {
-                uniq_bases.push(*base);
-                counts.push(0);
-                uniq_bases.len() - 1
-            });
-        counts[ix] += 1;
+    match (pos, neg) {
+        (Some(pos), Some(neg)) => Some(format!("{pos}⋅{neg}")),
+        (Some(s), None) | (None, Some(s)) => Some(s),
+        (None, None) => None,
     }
+}
 
-    // Generate a string containing each base and its exponent.
+/// Helper for `exponents_to_string`. Only symbols whose exponents have the
+/// same sign as `sign` are included, and each exponent is prefixed with
+/// `sign`.
+fn factors_to_string(factors: &[(&str, Rational)], sign: Option<char>) -> Option<String> {
     let mut result = String::new();
 
-    for ix in 0..uniq_bases.len() {
-        result.push_str(uniq_bases[ix].symbol);
+    for (symbol, exp) in factors {
+        if exp.is_negative() != sign.is_some() {
+            continue;
+        }
+        result.push_str(symbol);
         if let Some(sign) = sign {
             result.push(sign);
         }
-        if counts[ix] > 1 || sign.is_some() {
-            result.push_str(&i_to_str_superscripts(counts[ix]));
+        let (numer, denom) = (exp.numer().unsigned_abs(), exp.denom().unsigned_abs());
+        if numer > 1 || denom > 1 || sign.is_some() {
+            result.push_str(&i_to_str_superscripts(numer));
+        }
+        if denom > 1 {
+            result.push('ᐟ');
+            result.push_str(&i_to_str_superscripts(denom));
         }
         result.push('⋅');
     }
 
-    result.pop();
-    Some(result)
+    result.pop().map(|_| result)
 }
 
 impl std::fmt::Display for Unit {
@@ -327,14 +459,12 @@ impl std::fmt::Display for Unit {
         }
 
         // Otherwise, generate a string with the symbol's bases and exponents.
-        let pos = bases_to_string(&self.numer, None);
-        let neg = bases_to_string(&self.denom, Some('⁻'));
-
-        match (pos, neg) {
-            (Some(pos), Some(neg)) => write!(fmt, "{pos}⋅{neg}"),
-            (Some(pos), None) => write!(fmt, "{pos}"),
-            (None, Some(neg)) => write!(fmt, "{neg}"),
-            (None, None) => panic!("Unit with empty `numer` and `denom`"),
+        let factors: Vec<(&str, Rational)> =
+            self.bases.iter().map(|(b, exp)| (b.symbol, *exp)).collect();
+
+        match exponents_to_string(&factors) {
+            Some(s) => write!(fmt, "{s}"),
+            None => panic!("Unit with empty `numer` and `denom`"),
         }
     }
 }
@@ -345,11 +475,7 @@ impl std::ops::Mul<Self> for &Unit {
     /// Produces the unit that would result from multiplying a quantity in this
     /// unit with a quantity in another unit.
     fn mul(self, other: &Unit) -> Result<Unit, Error> {
-        let mut numer = self.numer.clone();
-        let mut denom = self.denom.clone();
-        numer.extend(&other.numer);
-        denom.extend(&other.denom);
-        Unit::new(numer.as_slice(), denom.as_slice())
+        Unit::from_exponents(self.bases.iter().chain(&other.bases).copied())
             .map(|u| u.with_constant(self.constant * other.constant))
     }
 }
@@ -370,22 +496,27 @@ impl std::ops::Mul<Base> for &Unit {
     /// Produces the unit that would result from multiplying a quantity in this
     /// unit with a quantity in a base unit.
     fn mul(self, other: Base) -> Result<Unit, Error> {
-        // Do cancellation of units that `simplified()` can't do, e.g. m/in.
-        for i in 0..self.denom.len() {
-            if self.denom[i].physq == other.physq {
-                let mut denom = self.denom.clone();
-                let canceled = denom.remove(i);
-
-                #[allow(clippy::suspicious_arithmetic_impl)]
-                let constant = self.constant * other.factor / canceled.factor;
-
-                return Unit::new(&self.numer, &denom).map(|u| u.with_constant(constant));
-            }
+        // Do cancellation of units that `from_exponents()` can't do, e.g. m/in.
+        if let Some(canceled) = self
+            .bases
+            .iter()
+            .find(|(b, exp)| exp.is_negative() && b.physq == other.physq)
+            .map(|(b, _)| *b)
+        {
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            let constant = self.constant * other.factor / canceled.factor;
+
+            return Unit::from_exponents(
+                self.bases
+                    .iter()
+                    .copied()
+                    .chain([(canceled, Rational::ONE)]),
+            )
+            .map(|u| u.with_constant(constant));
         }
 
-        let mut numer = self.numer.clone();
-        numer.extend([other]);
-        Unit::new(numer.as_slice(), self.denom.as_slice()).map(|u| u.with_constant(self.constant))
+        Unit::from_exponents(self.bases.iter().copied().chain([(other, Rational::ONE)]))
+            .map(|u| u.with_constant(self.constant))
     }
 }
 
@@ -395,12 +526,11 @@ impl std::ops::Div<Self> for &Unit {
     /// Produces the unit that would result from dividing a quantity in this
     /// unit by a quantity in another unit.
     fn div(self, other: &Unit) -> Result<Unit, Error> {
-        let mut numer = self.numer.clone();
-        let mut denom = self.denom.clone();
-        numer.extend(&other.denom);
-        denom.extend(&other.numer);
-        Unit::new(numer.as_slice(), denom.as_slice())
-            .map(|u| u.with_constant(self.constant / other.constant))
+        let mut exponents = self.bases.clone();
+        for (base, exp) in &other.bases {
+            exponents.push((*base, exp.checked_neg().ok_or(ExponentTooLarge)?));
+        }
+        Unit::from_exponents(exponents).map(|u| u.with_constant(self.constant / other.constant))
     }
 }
 
@@ -420,22 +550,32 @@ impl std::ops::Div<Base> for &Unit {
     /// Produces the unit that would result from dividing a quantity in this
     /// unit by a quantity in a base unit.
     fn div(self, other: Base) -> Result<Unit, Error> {
-        // Do cancellation of units that `simplified()` can't do, e.g. m/in.
-        for i in 0..self.numer.len() {
-            if self.numer[i].physq == other.physq {
-                let mut numer = self.numer.clone();
-                let canceled = numer.remove(i);
-
-                #[allow(clippy::suspicious_arithmetic_impl)]
-                let constant = self.constant / other.factor * canceled.factor;
-
-                return Unit::new(&numer, &self.denom).map(|u| u.with_constant(constant));
-            }
+        // Do cancellation of units that `from_exponents()` can't do, e.g. m/in.
+        if let Some(canceled) = self
+            .bases
+            .iter()
+            .find(|(b, exp)| exp.is_positive() && b.physq == other.physq)
+            .map(|(b, _)| *b)
+        {
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            let constant = self.constant / other.factor * canceled.factor;
+
+            return Unit::from_exponents(
+                self.bases
+                    .iter()
+                    .copied()
+                    .chain([(canceled, Rational::from(-1))]),
+            )
+            .map(|u| u.with_constant(constant));
         }
 
-        let mut denom = self.denom.clone();
-        denom.extend([other]);
-        Unit::new(self.numer.as_slice(), denom.as_slice()).map(|u| u.with_constant(self.constant))
+        Unit::from_exponents(
+            self.bases
+                .iter()
+                .copied()
+                .chain([(other, Rational::from(-1))]),
+        )
+        .map(|u| u.with_constant(self.constant))
     }
 }
 
@@ -443,7 +583,7 @@ impl std::ops::Div<Base> for &Unit {
 mod tests {
     use approx::assert_relative_eq;
 
-    use crate::units::{Unit, INCH, POUND_MASS};
+    use crate::units::{Error, Rational, Unit, HERTZ, INCH, POUND_MASS};
     use crate::units::{
         AMPERE, DEG_CELSIUS, DEG_FAHRENHEIT, FOOT, HOUR, KELVIN, KILOGRAM, KILOPASCAL, METER, MILE,
         NAUTICAL_MILE, NEWTON, POUND_FORCE, PSI, RANKINE, SECOND, TEMP_CELSIUS, TEMP_FAHRENHEIT,
@@ -455,20 +595,12 @@ mod tests {
         let m_kg_per_ampere_s = (((METER * KILOGRAM).unwrap() / AMPERE).unwrap() / SECOND).unwrap();
         assert_eq!(m_kg_per_ampere_s.to_string(), "m⋅kg⋅A⁻¹⋅s⁻¹");
 
-        let joule = Unit {
-            symbol: Some(String::from("J")),
-            numer: vec![KILOGRAM, METER, METER],
-            denom: vec![SECOND, SECOND],
-            constant: 1.0,
-        };
+        let joule = Unit::new(&[KILOGRAM, METER, METER], &[SECOND, SECOND])
+            .unwrap()
+            .with_symbol("J");
         assert_eq!(joule.to_string(), "J");
 
-        let joule = Unit {
-            symbol: None,
-            numer: vec![KILOGRAM, METER, METER],
-            denom: vec![SECOND, SECOND],
-            constant: 1.0,
-        };
+        let joule = Unit::new(&[KILOGRAM, METER, METER], &[SECOND, SECOND]).unwrap();
         assert_ne!(joule.to_string(), "J");
         assert_eq!(joule.with_symbol("J").to_string(), "J");
     }
@@ -485,21 +617,75 @@ mod tests {
         assert_eq!(u.to_string(), "s⁻²⋅A⁻¹");
     }
 
+    #[test]
+    fn unit_display_fractional_exponents() {
+        let u = HERTZ.root(Rational::from(2)).unwrap();
+        assert_eq!(u.to_string(), "s⁻¹ᐟ²");
+        let u = (&*VOLT / &u).unwrap();
+        assert_eq!(u.to_string(), "kg⋅m²⋅s⁻⁵ᐟ²⋅A⁻¹");
+        let u = METER.as_unit().pow(Rational::new(3, 2).unwrap()).unwrap();
+        assert_eq!(u.to_string(), "m³ᐟ²");
+    }
+
+    #[test]
+    fn fractional_exponent_commensurability() {
+        let per_root_hz = HERTZ.root(Rational::from(2)).unwrap();
+        let a = (&*VOLT / &per_root_hz).unwrap();
+        let b = (&VOLT.with_constant(1e-9) / &per_root_hz).unwrap();
+        assert!(a.is_commensurable_with(&b));
+        assert_relative_eq!(a.convert(1.0, &b).unwrap(), 1e9);
+
+        let ft = FOOT.as_unit().root(Rational::from(2)).unwrap();
+        let m = METER.as_unit().root(Rational::from(2)).unwrap();
+        assert!(ft.is_commensurable_with(&m));
+        assert_relative_eq!(ft.convert(1.0, &m).unwrap(), 0.3048_f64.sqrt());
+        assert!(!m.is_commensurable_with(&METER.as_unit()));
+        assert!(!a.is_commensurable_with(&VOLT));
+    }
+
+    #[test]
+    #[should_panic(expected = "fractional exponents")]
+    fn fractional_exponents_have_no_numerator() {
+        let u = SECOND.as_unit().root(Rational::from(2)).unwrap();
+        let _ = u.numer();
+    }
+
+    #[test]
+    fn pow_and_root() {
+        let u = Unit::new(&[METER], &[SECOND]).unwrap();
+        let squared = u.pow(Rational::from(2)).unwrap();
+        assert_eq!(
+            squared,
+            Unit::new(&[METER, METER], &[SECOND, SECOND]).unwrap()
+        );
+        assert_eq!(squared.root(Rational::from(2)).unwrap(), u);
+        assert_eq!(u.root(Rational::ZERO), Err(Error::DegreeIsZero));
+        assert!(TEMP_CELSIUS.as_unit().pow(Rational::from(2)).is_err());
+        assert!(TEMP_CELSIUS.as_unit().pow(Rational::ONE).is_ok());
+        assert_eq!(
+            VOLT.with_constant(1e3)
+                .pow(Rational::from(2))
+                .unwrap()
+                .constant(),
+            1e6
+        );
+    }
+
     #[test]
     fn unit_multiplied_by_unit() {
         let m_per_s = (METER / SECOND).unwrap();
         let kg_per_amp = (KILOGRAM / AMPERE).unwrap();
         let result = (&m_per_s * &kg_per_amp).unwrap();
-        assert_eq!(result.numer, vec![METER, KILOGRAM]);
-        assert_eq!(result.denom, vec![SECOND, AMPERE]);
+        assert_eq!(result.numer(), vec![METER, KILOGRAM]);
+        assert_eq!(result.denom(), vec![SECOND, AMPERE]);
     }
 
     #[test]
     fn unit_multiplied_by_base() {
         let m_per_s = (METER / SECOND).unwrap();
         let result = (m_per_s * KILOGRAM).unwrap();
-        assert_eq!(result.numer, vec![METER, KILOGRAM]);
-        assert_eq!(result.denom, vec![SECOND]);
+        assert_eq!(result.numer(), vec![METER, KILOGRAM]);
+        assert_eq!(result.denom(), vec![SECOND]);
     }
 
     #[test]
@@ -507,16 +693,16 @@ mod tests {
         let m_per_s = (METER / SECOND).unwrap();
         let kg_per_amp = (KILOGRAM / AMPERE).unwrap();
         let result = (&m_per_s / &kg_per_amp).unwrap();
-        assert_eq!(result.numer, vec![METER, AMPERE]);
-        assert_eq!(result.denom, vec![SECOND, KILOGRAM]);
+        assert_eq!(result.numer(), vec![METER, AMPERE]);
+        assert_eq!(result.denom(), vec![SECOND, KILOGRAM]);
     }
 
     #[test]
     fn unit_divided_by_base() {
         let m_per_s = (METER / SECOND).unwrap();
         let result = (m_per_s / KILOGRAM).unwrap();
-        assert_eq!(result.numer, vec![METER]);
-        assert_eq!(result.denom, vec![SECOND, KILOGRAM]);
+        assert_eq!(result.numer(), vec![METER]);
+        assert_eq!(result.denom(), vec![SECOND, KILOGRAM]);
     }
 
     #[test]
@@ -526,8 +712,8 @@ mod tests {
             .unwrap()
             / SECOND)
             .unwrap();
-        assert_eq!(kg_per_s.numer, vec![KILOGRAM]);
-        assert_eq!(kg_per_s.denom, vec![SECOND]);
+        assert_eq!(kg_per_s.numer(), vec![KILOGRAM]);
+        assert_eq!(kg_per_s.denom(), vec![SECOND]);
     }
 
     #[test]
@@ -764,12 +950,12 @@ mod tests {
 
     #[test]
     fn conversion_between_systems() {
-        assert_eq!(POUND_FORCE.convert(1.0, &NEWTON).unwrap(), 4.4482216152605);
-        assert_eq!(
+        assert_relative_eq!(POUND_FORCE.convert(1.0, &NEWTON).unwrap(), 4.4482216152605);
+        assert_relative_eq!(
             NEWTON.convert(1.0, &POUND_FORCE).unwrap(),
             0.22480894309971047
         );
-        assert_eq!(PSI.convert(1.0, &KILOPASCAL).unwrap(), 6.89475729316836);
-        assert_eq!(KILOPASCAL.convert(1.0, &PSI).unwrap(), 0.14503773773020925);
+        assert_relative_eq!(PSI.convert(1.0, &KILOPASCAL).unwrap(), 6.89475729316836);
+        assert_relative_eq!(KILOPASCAL.convert(1.0, &PSI).unwrap(), 0.14503773773020925);
     }
 }
@@ -0,0 +1,276 @@
+// Copyright 2023 electronfraud
+//
+// This file is part of calc.
+//
+// calc is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// calc is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// calc. If not, see <https://www.gnu.org/licenses/>.
+
+//! Vectors of numbers that share a unit.
+//!
+//! Element-wise operations are carried out with the `units::Number`
+//! operations, so vectors follow the same rules for units as numbers do.
+//! Operations on two vectors assume that the vectors have the same length;
+//! callers are responsible for checking.
+
+use crate::units::{Error, Number, Unit};
+
+/// A vector of numbers in a single unit.
+#[derive(Clone, Debug)]
+pub struct Vector {
+    pub values: Vec<f64>,
+    pub unit: Option<Unit>,
+}
+
+impl Vector {
+    /// Returns a dimensionless `Vector`.
+    #[must_use]
+    pub fn new(values: Vec<f64>) -> Vector {
+        Vector { values, unit: None }
+    }
+
+    /// Makes a vector out of numbers, converting them to the units of the
+    /// first number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the numbers have incommensurable units.
+    pub fn from_numbers(numbers: &[Number]) -> Result<Vector, Error> {
+        let unit = numbers.first().and_then(|n| n.unit.clone());
+        let values = numbers
+            .iter()
+            .map(|n| n.value_in(unit.as_ref()))
+            .collect::<Result<_, _>>()?;
+        Ok(Vector { values, unit })
+    }
+
+    /// Returns the number of elements in the vector.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the vector has no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the elements of the vector as numbers.
+    pub fn numbers(&self) -> impl Iterator<Item = Number> + '_ {
+        self.values.iter().map(|&value| Number {
+            value,
+            unit: self.unit.clone(),
+        })
+    }
+
+    /// Returns a vector with the same values as this one but different units.
+    /// No unit conversion is performed.
+    #[must_use]
+    pub fn with_unit(&self, unit: Unit) -> Vector {
+        if unit.is_scalar() {
+            let values = self.values.iter().map(|x| x * unit.constant()).collect();
+            Vector::new(values)
+        } else {
+            Vector {
+                values: self.values.clone(),
+                unit: Some(unit),
+            }
+        }
+    }
+
+    /// Applies `f` to every element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `f` does.
+    pub fn map(&self, f: impl Fn(&Number) -> Result<Number, Error>) -> Result<Vector, Error> {
+        let numbers = self
+            .numbers()
+            .map(|x| f(&x))
+            .collect::<Result<Vec<_>, _>>()?;
+        Vector::collect(&numbers, || {
+            f(&Number {
+                value: 1.0,
+                unit: self.unit.clone(),
+            })
+        })
+    }
+
+    /// Applies `f` to every pair of corresponding elements of this vector and
+    /// `other`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `f` does.
+    pub fn zip_with(
+        &self,
+        other: &Vector,
+        f: impl Fn(&Number, &Number) -> Result<Number, Error>,
+    ) -> Result<Vector, Error> {
+        let numbers = self
+            .numbers()
+            .zip(other.numbers())
+            .map(|(x, y)| f(&x, &y))
+            .collect::<Result<Vec<_>, _>>()?;
+        Vector::collect(&numbers, || {
+            f(
+                &Number {
+                    value: 1.0,
+                    unit: self.unit.clone(),
+                },
+                &Number {
+                    value: 1.0,
+                    unit: other.unit.clone(),
+                },
+            )
+        })
+    }
+
+    /// Helper for `map` and `zip_with`. Makes a vector out of the results of
+    /// an element-wise operation. If there are none, `probe` performs the
+    /// operation on sample numbers to find the units of the result.
+    fn collect(
+        numbers: &[Number],
+        probe: impl FnOnce() -> Result<Number, Error>,
+    ) -> Result<Vector, Error> {
+        if numbers.is_empty() {
+            Ok(Vector {
+                values: Vec::new(),
+                unit: probe()?.unit,
+            })
+        } else {
+            Vector::from_numbers(numbers)
+        }
+    }
+
+    /// Finds the dot product of this vector and `other`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the units can't be multiplied.
+    pub fn dot(&self, other: &Vector) -> Result<Number, Error> {
+        let products = self.zip_with(other, |x, y| x * y)?;
+        Ok(Number {
+            value: products.values.iter().sum(),
+            unit: products.unit,
+        })
+    }
+
+    /// Finds the cross product of this vector and `other`, which must both
+    /// have three elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the units can't be multiplied.
+    pub fn cross(&self, other: &Vector) -> Result<Vector, Error> {
+        let a: Vec<Number> = self.numbers().collect();
+        let b: Vec<Number> = other.numbers().collect();
+        let component = |i: usize, j: usize| &(&a[i] * &b[j])? - &(&a[j] * &b[i])?;
+        Vector::from_numbers(&[component(1, 2)?, component(2, 0)?, component(0, 1)?])
+    }
+
+    /// Finds the Euclidean norm (length) of this vector, in its units.
+    #[must_use]
+    pub fn norm(&self) -> Number {
+        Number {
+            value: self.values.iter().map(|x| x * x).sum::<f64>().sqrt(),
+            unit: self.unit.clone(),
+        }
+    }
+
+    /// Returns the dimensionless unit vector in the direction of this vector,
+    /// or `None` if this vector is zero and has no direction.
+    #[must_use]
+    pub fn normalized(&self) -> Option<Vector> {
+        let norm = self.norm().value;
+        if norm == 0.0 {
+            return None;
+        }
+        Some(Vector::new(self.values.iter().map(|x| x / norm).collect()))
+    }
+}
+
+impl std::fmt::Display for Vector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let values: Vec<String> = self
+            .values
+            .iter()
+            .map(|&x| Number::new(x).to_string())
+            .collect();
+        write!(f, "[{}]", values.join(" "))?;
+        if let Some(u) = &self.unit {
+            write!(f, " {u}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{METER, MILLIMETER, NEWTON};
+
+    fn meters(values: &[f64]) -> Vector {
+        Vector {
+            values: values.to_vec(),
+            unit: Some(METER.as_unit()),
+        }
+    }
+
+    #[test]
+    fn from_numbers() {
+        let v = Vector::from_numbers(&[
+            Number::new(1.0).with_unit(METER.as_unit()),
+            Number::new(500.0).with_unit(MILLIMETER.as_unit()),
+        ])
+        .unwrap();
+        assert_eq!(v.values, [1.0, 0.5]);
+        assert_eq!(v.unit, Some(METER.as_unit()));
+        assert!(Vector::from_numbers(&[
+            Number::new(1.0).with_unit(METER.as_unit()),
+            Number::new(1.0)
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn products() {
+        let r = meters(&[1.0, 2.0, 3.0]);
+        let f = Vector {
+            values: vec![4.0, 5.0, 6.0],
+            unit: Some(NEWTON.clone()),
+        };
+        let dot = r.dot(&f).unwrap();
+        assert_eq!(dot.value, 32.0);
+        let cross = r.cross(&f).unwrap();
+        assert_eq!(cross.values, [-3.0, 6.0, -3.0]);
+        assert_eq!(cross.unit, dot.unit);
+    }
+
+    #[test]
+    fn norms() {
+        let v = meters(&[3.0, 4.0]);
+        assert_eq!(v.norm().value, 5.0);
+        let u = v.normalized().unwrap();
+        assert_eq!(u.values, [0.6, 0.8]);
+        assert!(u.unit.is_none());
+        assert!(Vector::new(vec![0.0, 0.0]).normalized().is_none());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Vector::new(vec![1.0, 2.5]).to_string(), "[1 2.5]");
+        assert_eq!(meters(&[1.0, 2.0, 3.0]).to_string(), "[1 2 3] m");
+        assert_eq!(Vector::new(Vec::new()).to_string(), "[]");
+    }
+}
@@ -1,3 +1,6 @@
+// Expected values are copied verbatim from the output of `units`.
+#![allow(clippy::excessive_precision)]
+
 use approx::assert_relative_eq;
 
 use calc::eval;
@@ -1,6 +1,7 @@
+use approx::assert_relative_eq;
 use calc::units::{
-    AMPERE, DAY, DEGREE, DEG_FAHRENHEIT, FOOT, INCH, KILOAMPERE, KILOGRAM, MEGAMETER, METER, MOLE,
-    NANOCANDELA, NANOSECOND, SECOND,
+    AMPERE, DAY, DEGREE, DEG_FAHRENHEIT, FOOT, INCH, KILOAMPERE, KILOGRAM, MEGAMETER, METER,
+    MILLIMETER, MOLE, NANOCANDELA, NANOSECOND, SECOND, TEMP_CELSIUS, WATT,
 };
 use calc::{eval, popf};
 
@@ -160,3 +161,1244 @@ fn psi_to_pascals() {
     assert_eq!(u.numer(), &[KILOGRAM]);
     assert_eq!(u.denom(), &[SECOND, SECOND, METER]);
 }
+
+/// Multiplying newtons by meters should produce joules rather than base units,
+/// unless automatic naming is turned off.
+#[test]
+fn automatic_naming() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("100 N 2 m *"), eval::Status::Ok);
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[200 J]");
+
+    assert_eq!(ctx.eval("noautoname 100 N 2 m *"), eval::Status::Ok);
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[200 kg⋅m²⋅s⁻²]");
+}
+
+/// Units made eligible for automatic naming replace commensurable units, and
+/// units can be made ineligible.
+#[test]
+fn nameable_units() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("kJ nameable 100 N 2 m *"), eval::Status::Ok);
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[0.2 kJ]");
+
+    assert_eq!(ctx.eval("W unnameable 2 V 3 A *"), eval::Status::Ok);
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 6.0);
+    let u = f.unit.unwrap();
+    assert_eq!(u.symbol, None);
+    assert_eq!(u.numer(), WATT.numer());
+    assert_eq!(u.denom(), WATT.denom());
+
+    // Naming doesn't switch the units of a result to SI.
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("2 psi 3 *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[6 psi]");
+
+    assert_eq!(ctx.eval("2 kJ 3 *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[6 kJ]");
+
+    assert_eq!(ctx.eval("1 ft 1 lbf *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.unit.unwrap().to_string(), "ft²⋅lb⋅s⁻²");
+}
+
+/// Noise spectral density in nanovolts per root hertz. Taking the square root
+/// of a unit produces fractional exponents, and quantities with fractional
+/// exponents convert like any other.
+#[test]
+fn fractional_exponents() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(
+        ctx.eval("4 nV 1 Hz sqrt / uV kHz sqrt / into"),
+        eval::Status::Ok
+    );
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 4e-3 * 1e3_f64.sqrt());
+    assert_eq!(f.unit.unwrap().to_string(), "kg⋅m²⋅s⁻⁵ᐟ²⋅A⁻¹");
+
+    assert_eq!(ctx.eval("1 s -0.5 **"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[1 s⁻¹ᐟ²]");
+}
+
+/// Compound units can be entered as a single word in infix notation, including
+/// the notation units are displayed in, and used as the target of `into`.
+#[test]
+fn infix_unit_expressions() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("100 km/h m/s into"), eval::Status::Ok);
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 27.777_777_777_777_78);
+    assert_eq!(f.unit.unwrap().to_string(), "m⋅s⁻¹");
+
+    assert_eq!(ctx.eval("3 N.m kg·m²/s² into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[3 kg⋅m²⋅s⁻²]");
+
+    assert_eq!(ctx.eval("9.80665 m⋅s⁻² ft/s2 into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 32.17404855643044);
+
+    assert_eq!(ctx.eval("60 1/min 1/s into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[1 s⁻¹]");
+
+    assert!(matches!(
+        ctx.eval("3 m^0"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::ExponentIsZero
+            )),
+            ..
+        }
+    ));
+    ctx.stack.clear();
+
+    assert!(matches!(
+        ctx.eval("m/furlong"),
+        eval::Status::Err {
+            error: eval::Error::UnknownWord,
+            ..
+        }
+    ));
+
+    // Integer literals too large to parse aren't unit expressions with huge
+    // exponents either.
+    assert_eq!(
+        ctx.eval("0x8000000000000000"),
+        eval::Status::Err {
+            error: eval::Error::UnknownWord,
+            word: String::from("0x8000000000000000")
+        }
+    );
+}
+
+/// Quantities can be split across several units and joined back together, and
+/// times and angles can be entered in sexagesimal notation.
+#[test]
+fn mixed_units() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("1.8 m ft in split"), eval::Status::Ok);
+
+    let inches = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    let feet = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(feet.to_string(), "[5 ft]");
+    assert_eq!(inches.to_string(), "[10.866142 in]");
+
+    assert_eq!(ctx.eval("5 ft 10 in join in into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 70.0);
+
+    // Only the parts of one quantity are joined.
+    assert_eq!(ctx.eval("1 m 5 ft 10 in join"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[5.833333 ft]");
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[1 m]");
+
+    assert_eq!(ctx.eval("6 in 2 ft join"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[2 ft]");
+    ctx.stack.clear();
+
+    assert_eq!(ctx.eval("12:34:56 s into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 45296.0);
+
+    assert_eq!(ctx.eval("41°24'12\" rad into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 41.403_333_333_333_33_f64.to_radians());
+}
+
+/// Automatic scaling and preferred units change how numbers are displayed but
+/// not the numbers themselves.
+#[test]
+fn display_units() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("autoscale 0.000047 F"), eval::Status::Ok);
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(ctx.settings.displayed(&f).to_string(), "[47 uF]");
+    assert_eq!(f.to_string(), "[4.7e-5 F]");
+
+    assert_eq!(ctx.eval("mm prefer 1.5 m"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(ctx.settings.displayed(&f).to_string(), "[1500 mm]");
+
+    assert_eq!(ctx.eval("mm unprefer 1500 kg"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(ctx.settings.displayed(&f).to_string(), "[1.5 t]");
+
+    assert_eq!(ctx.eval("mm unprefer noautoscale 1500 m"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(ctx.settings.displayed(&f).to_string(), "[1500 m]");
+}
+
+/// Gram-prefixed units are scaled relative to the gram, not the kilogram.
+#[test]
+fn gram_prefixes() {
+    let mut ctx = eval::Context::new();
+    for (expr, expected) in [
+        ("1 Mg kg into", 1e3),
+        ("1 t kg into", 1e3),
+        ("1 Pg kg into", 1e12),
+        ("1 mg g into", 1e-3),
+        ("1 ug kg into", 1e-9),
+        ("1 fg g into", 1e-15),
+    ] {
+        assert_eq!(ctx.eval(expr), eval::Status::Ok, "{expr}");
+        let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+        assert_relative_eq!(f.value, expected, max_relative = 1e-12);
+    }
+}
+
+/// Selecting a unit system expresses the results of arithmetic in that
+/// system's units, including its named units.
+#[test]
+fn unit_systems() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("sisystem 2 ft 3 ft *"), eval::Status::Ok);
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 6.0 * 0.3048 * 0.3048);
+    assert_eq!(f.unit.unwrap().to_string(), "m²");
+
+    assert_eq!(ctx.eval("cgssystem 1 N 1 m *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[10000000 erg]");
+
+    assert_eq!(ctx.eval("2 T 3 *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[60000 G]");
+
+    assert_eq!(ctx.eval("ussystem 1 kg 9.80665 m/s2 *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 1.0 / 0.453_592_37);
+    assert_eq!(f.unit.unwrap().to_string(), "lbf");
+
+    assert_eq!(ctx.eval("10 N 1 m *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 7.375_621_492_772_654, max_relative = 1e-12);
+    assert_eq!(f.unit.unwrap().to_string(), "ft⋅lbf");
+
+    // Temperatures stay on the system's customary scale, and intervals are
+    // converted to its interval unit.
+    assert_eq!(ctx.eval("70 tempF 1 degF +"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 71.0, epsilon = 1e-9);
+    assert_eq!(f.unit.unwrap().to_string(), "tempF");
+
+    assert_eq!(ctx.eval("20 tempC 10 degC +"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 86.0, epsilon = 1e-9);
+    assert_eq!(f.unit.unwrap().to_string(), "tempF");
+
+    assert_eq!(ctx.eval("10 degC 2 *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 36.0, epsilon = 1e-9);
+    assert_eq!(f.unit.unwrap().to_string(), "degF");
+
+    assert_eq!(ctx.eval("nosystem 2 ft 3 in *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[0.5 ft²]");
+}
+
+/// Inspection builtins describe quantities without changing the stack, and
+/// `si` converts quantities into SI base units.
+#[test]
+fn unit_inspection() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("5 N dim base m into?"), eval::Status::Ok);
+    assert_eq!(
+        ctx.output,
+        [
+            "L¹ M¹ T⁻²",
+            "[5 N] = [5 kg⋅m⋅s⁻²]",
+            "[5 N]: L¹ M¹ T⁻²",
+            "m: L¹",
+            "[5 N] and m differ by M¹ T⁻²",
+        ]
+    );
+    assert_eq!(ctx.stack.height(), 2);
+
+    assert_eq!(ctx.eval("clear 2 kJ si"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[2000 kg⋅m²⋅s⁻²]");
+}
+
+/// Dimensional analysis finds the product of powers of the quantities on the
+/// stack that has the target unit.
+#[test]
+fn dimensional_analysis() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(
+        ctx.eval("12 m/s 1.2 kg/m3 3 m Pa dimsolve"),
+        eval::Status::Ok
+    );
+
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 172.8);
+    assert_eq!(f.unit.unwrap().to_string(), "Pa");
+    assert_eq!(ctx.output, ["[12 m⋅s⁻¹]² ⋅ [1.2 kg⋅m⁻³]"]);
+    assert!(ctx.stack.is_empty());
+
+    assert!(matches!(
+        ctx.eval("3 m W dimsolve"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NoCombination),
+            ..
+        }
+    ));
+}
+
+/// Currency codes become units once exchange rates are loaded, and convert
+/// through the rates' base currency.
+#[test]
+fn currencies() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(
+        ctx.eval("EUR"),
+        eval::Status::Err {
+            error: eval::Error::UnknownWord,
+            word: String::from("EUR")
+        }
+    );
+
+    let rates = "date = 2024-03-01\nbase = \"USD\"\n[rates]\nEUR = 0.8\nJPY = 150";
+    ctx.rates = Some(rates.parse().unwrap());
+    assert_eq!(ctx.eval("100 EUR USD into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 125.0);
+    assert_eq!(f.unit.unwrap().to_string(), "USD");
+
+    assert_eq!(ctx.eval("0.12 $/kWh 10 MJ * ¥ into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 50.0, max_relative = 1e-12);
+    assert_eq!(f.unit.unwrap().to_string(), "JPY");
+}
+
+/// Ratios like `%` and `ppm` scale the number they're applied to, and can be
+/// used with `into` to express a number as a ratio, even right after it.
+#[test]
+fn ratios() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("100 ohm 5 % *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 5.0);
+    assert_eq!(f.unit.unwrap().to_string(), "ohm");
+
+    assert_eq!(ctx.eval("50 ppm/degC 20 degC *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 0.001);
+    assert!(f.is_dimensionless());
+
+    assert_eq!(ctx.eval("% 0.05 swap into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[5 %]");
+
+    assert_eq!(ctx.eval("% 0.05 swap into ppm into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[50000 ppm]");
+
+    assert_eq!(ctx.eval("0.05 % into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[5 %]");
+
+    assert_eq!(ctx.eval("3 permille ppm into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[3000 ppm]");
+
+    assert_eq!(ctx.eval("5 % m"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[0.05 m]");
+
+    assert_eq!(ctx.eval("50 % sqrt"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 0.5_f64.sqrt());
+}
+
+/// Subtracting temperatures gives a difference in degrees, and adding a
+/// difference to a temperature gives a temperature.
+#[test]
+fn affine_temperatures() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("80 tempF 70 tempF -"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 10.0);
+    assert_eq!(f.unit.unwrap().to_string(), "degF");
+
+    assert_eq!(ctx.eval("20 tempC 5 degC + 9 degF +"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 30.0);
+    assert_eq!(f.unit.unwrap().to_string(), "tempC");
+
+    assert_eq!(ctx.eval("300 tempK tempC into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 26.85, max_relative = 1e-12);
+
+    // K is a temperature, not a difference, in either order.
+    assert_eq!(ctx.eval("20 tempC 300 K -"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, -6.85, max_relative = 1e-12);
+    assert_eq!(f.unit.unwrap().to_string(), "degC");
+
+    assert_eq!(ctx.eval("300 K 20 tempC -"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 6.85, max_relative = 1e-12);
+    assert_eq!(f.unit.unwrap().to_string(), "K");
+
+    assert!(matches!(
+        ctx.eval("20 tempC 300 K +"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::TemperaturesAdded(_, _)
+            )),
+            ..
+        }
+    ));
+    ctx.stack.clear();
+
+    assert!(matches!(
+        ctx.eval("20 tempC 30 tempC +"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::TemperaturesAdded(_, _)
+            )),
+            ..
+        }
+    ));
+}
+
+/// Physical constants are namespaced with `@` and carry metadata that
+/// `const?` shows.
+#[test]
+fn physical_constants() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("@k_B 300 K * @e /"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 0.025_851_999_786_435_535, max_relative = 1e-12);
+    assert_eq!(f.unit.unwrap().to_string(), "V");
+
+    assert_eq!(ctx.eval("e"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, std::f64::consts::E);
+
+    assert_eq!(ctx.eval("@mu0 @c *"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.unit.unwrap().to_string(), "ohm");
+    assert_eq!(ctx.eval("@eps0"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.unit.unwrap().to_string(), "F⋅m⁻¹");
+
+    assert_eq!(ctx.eval("@m_e g into const?"), eval::Status::Ok);
+    assert_eq!(
+        ctx.output,
+        ["@m_e = 9.1093837015e-31 kg (electron mass; relative uncertainty 3e-10)"]
+    );
+    popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert!(ctx.stack.is_empty());
+
+    assert_eq!(
+        ctx.eval("2 const?"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotAConstant),
+            word: String::from("const?")
+        }
+    );
+}
+
+/// With an angle mode, trigonometric functions accept dimensionless numbers,
+/// and `arcmode` makes inverse functions answer in the mode's unit.
+#[test]
+fn angle_modes() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(
+        ctx.eval("30 sin"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::MissingUnit),
+            word: String::from("sin")
+        }
+    );
+    ctx.stack.clear();
+
+    assert_eq!(ctx.eval("deg anglemode 30 sin"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 0.5, max_relative = 1e-12);
+
+    assert_eq!(ctx.eval("1 asin"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, std::f64::consts::FRAC_PI_2);
+    assert_eq!(f.unit.unwrap().to_string(), "rad");
+
+    assert_eq!(ctx.eval("arcmode 1 asin arcmin into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 5400.0, max_relative = 1e-12);
+
+    assert_eq!(ctx.eval("0.25 turn grad into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 100.0, max_relative = 1e-12);
+}
+
+#[test]
+fn more_trigonometry() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("3 m 300 cm atan2 deg into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 45.0, max_relative = 1e-12);
+
+    assert_eq!(ctx.eval("3 ft 48 in hypot"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 5.0, max_relative = 1e-12);
+    assert_eq!(f.unit.unwrap().to_string(), "ft");
+
+    assert_eq!(ctx.eval("60 deg sec 1 cosh acosh"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 1.0, max_relative = 1e-12);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 2.0, max_relative = 1e-12);
+
+    assert_eq!(
+        ctx.eval("1 s 1 m atan2"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::IncommensurableUnits(
+                    Some(Box::new(SECOND.as_unit())),
+                    Some(Box::new(METER.as_unit()))
+                )
+            )),
+            word: String::from("atan2")
+        }
+    );
+    ctx.stack.clear();
+
+    assert_eq!(
+        ctx.eval("1 rad sinh"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotDimensionless),
+            word: String::from("sinh")
+        }
+    );
+}
+
+#[test]
+fn rounding_and_sign() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("2.54 cm round"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 3.0);
+    assert_eq!(f.unit.unwrap().to_string(), "cm");
+
+    assert_eq!(
+        ctx.eval("autoscale 0.0254 m round noautoscale"),
+        eval::Status::Ok
+    );
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 25.0);
+    assert_eq!(f.unit.unwrap().to_string(), "mm");
+
+    assert_eq!(ctx.eval("2.54 cm 5 mm roundto"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 25.0);
+    assert_eq!(f.unit.unwrap().to_string(), "mm");
+
+    assert_eq!(ctx.eval("9.80665 m 3 roundsig"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 9.81);
+
+    assert_eq!(ctx.eval("0x10 neg abs"), eval::Status::Ok);
+    match ctx.stack.pop() {
+        Ok(calc::stack::Item::Integer(i)) => assert_eq!(i, calc::integer::Integer::hex(16)),
+        item => panic!("expected an integer, got {item:?}"),
+    }
+
+    for word in ["abs", "neg"] {
+        assert_eq!(
+            ctx.eval(&format!("-9223372036854775808 {word}")),
+            eval::Status::Err {
+                error: eval::Error::Builtins(calc::builtins::Error::Overflow),
+                word: String::from(word)
+            }
+        );
+        ctx.stack.clear();
+        assert_eq!(
+            ctx.eval(&format!("-20 tempC {word}")),
+            eval::Status::Err {
+                error: eval::Error::Builtins(calc::builtins::Error::Units(
+                    calc::units::Error::NonzeroZeroPoint(TEMP_CELSIUS)
+                )),
+                word: String::from(word)
+            }
+        );
+        ctx.stack.clear();
+    }
+    assert_eq!(ctx.eval("-20 degC abs"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.to_string(), "[20 degC]");
+
+    assert_eq!(
+        ctx.eval("1 m 90 cm greater 2 m 3 m clamp"),
+        eval::Status::Ok
+    );
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 2.0);
+
+    assert_eq!(
+        ctx.eval("5 10 0 clamp"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::BoundsReversed),
+            word: String::from("clamp")
+        }
+    );
+}
+
+/// Builtins are looked up before units, so a builtin named like a unit would
+/// make the unit unusable.
+#[test]
+fn builtins_do_not_shadow_units() {
+    for name in calc::builtins::table().keys() {
+        assert!(
+            !calc::units::SYMBOLS.contains_key(name),
+            "builtin {name} shadows a unit"
+        );
+    }
+
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("5 min s into"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 300.0);
+
+    assert_eq!(ctx.eval("12:34:56 hr min s split"), eval::Status::Ok);
+    let s = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    let m = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(m.to_string(), "[34 min]");
+    assert_relative_eq!(s.value, 56.0, epsilon = 1e-9);
+}
+
+#[test]
+fn statistics() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("1 m 2 m 300 cm mean"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 2.0);
+    assert_eq!(f.unit.unwrap().to_string(), "m");
+    assert!(ctx.stack.is_empty());
+
+    assert_eq!(ctx.eval("42 2 4 4 4 5 5 7 9 8 stdevn"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, (32.0f64 / 7.0).sqrt(), max_relative = 1e-12);
+    assert_eq!(ctx.stack.height(), 1);
+    ctx.stack.clear();
+
+    assert_eq!(ctx.eval("3 1 2 10 median"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 2.5);
+
+    assert_eq!(ctx.eval("1 m 3 m var"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 2.0);
+    assert_eq!(f.unit.unwrap().to_string(), "m²");
+
+    assert_eq!(ctx.eval("1 m 90 cm 2 m minimum"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 90.0);
+    assert_eq!(f.unit.unwrap().to_string(), "cm");
+
+    assert_eq!(ctx.eval("1 4 16 geomean"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 4.0, max_relative = 1e-12);
+
+    assert_eq!(
+        ctx.eval("1 m 1 s 2 sumn"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::IncommensurableUnits(
+                    Some(Box::new(SECOND.as_unit())),
+                    Some(Box::new(METER.as_unit()))
+                )
+            )),
+            word: String::from("sumn")
+        }
+    );
+    ctx.stack.clear();
+
+    assert_eq!(
+        ctx.eval("10 tempC 20 tempC sum"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::TemperaturesAdded(TEMP_CELSIUS, TEMP_CELSIUS)
+            )),
+            word: String::from("sum")
+        }
+    );
+    ctx.stack.clear();
+
+    assert_eq!(
+        ctx.eval("10 tempC 20 tempC 60 tempC mean"),
+        eval::Status::Ok
+    );
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 30.0);
+    assert_eq!(f.unit.unwrap().to_string(), "tempC");
+
+    assert_eq!(
+        ctx.eval("10 tempC 20 tempC 60 tempC range"),
+        eval::Status::Ok
+    );
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 50.0);
+    assert_eq!(f.unit.unwrap().to_string(), "degC");
+}
+
+#[test]
+fn number_theory() {
+    let mut ctx = eval::Context::new();
+    assert_eq!(ctx.eval("0x3c factor"), eval::Status::Ok);
+    for p in [5, 3, 2, 2] {
+        match ctx.stack.pop() {
+            Ok(calc::stack::Item::Integer(i)) => assert_eq!(i, calc::integer::Integer::hex(p)),
+            item => panic!("expected an integer, got {item:?}"),
+        }
+    }
+    assert!(ctx.stack.is_empty());
+
+    assert_eq!(
+        ctx.eval("52 5 nCr 10 ! 4 13 497 modpow 3 11 modinv"),
+        eval::Status::Ok
+    );
+    for expected in [4, 445, 3_628_800, 2_598_960] {
+        match ctx.stack.pop() {
+            Ok(calc::stack::Item::Integer(i)) => assert_eq!(i.value, expected),
+            item => panic!("expected an integer, got {item:?}"),
+        }
+    }
+
+    assert_eq!(
+        ctx.eval("21 !"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Overflow),
+            word: String::from("!")
+        }
+    );
+    ctx.stack.clear();
+
+    assert_eq!(
+        ctx.eval("6 9 modinv"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotInvertible),
+            word: String::from("modinv")
+        }
+    );
+}
+
+/// Checks special functions against tabulated values, mostly from Abramowitz
+/// and Stegun.
+#[test]
+fn special_functions() {
+    let cases = [
+        ("0.5 gamma", 1.772_453_850_905_516),
+        ("1.9 gamma", 0.961_765_831_907_387),
+        ("-1.5 gamma", 2.363_271_801_207_355),
+        ("10 gamma", 362_880.0),
+        ("100 lgamma", 359.134_205_369_575_4),
+        ("-0.5 lgamma", 1.265_512_123_484_645),
+        ("2 3 beta", 1.0 / 12.0),
+        ("0.5 0.5 beta", std::f64::consts::PI),
+        ("0.5 erf", 0.520_499_877_813_046_5),
+        ("-1.5 erf", -0.966_105_146_475_310_7),
+        ("3 erfc", 2.209_049_699_858_544e-5),
+        ("5 erfc", 1.537_459_794_428_035e-12),
+        ("0.5 erfinv", 0.476_936_276_204_469_9),
+        ("0.999 erfinv", 2.326_753_765_513_525),
+        ("1 J0", 0.765_197_686_557_966_6),
+        ("10 J0", -0.245_935_764_451_348_3),
+        ("50 J0", 0.055_812_327_669_251_82),
+        ("1 J1", 0.440_050_585_744_933_5),
+        ("10 J1", 0.043_472_746_168_861_44),
+        ("5 2 Jn", 0.046_565_116_277_752_21),
+        ("10 5 Jn", -0.234_061_528_186_793_6),
+        ("1 30 Jn", 3.482_869_794_251_483e-42),
+        ("1 Y0", 0.088_256_964_215_676_96),
+        ("10 Y0", 0.055_671_167_283_599_39),
+        ("50 Y0", -0.098_064_995_470_077_08),
+    ];
+    let mut ctx = eval::Context::new();
+    for (expr, expected) in cases {
+        assert_eq!(ctx.eval(expr), eval::Status::Ok, "{expr}");
+        let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+        assert!(f.unit.is_none(), "{expr}");
+        assert_relative_eq!(f.value, expected, max_relative = 1e-13);
+    }
+
+    assert_eq!(
+        ctx.eval("1 m erf"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotDimensionless),
+            word: String::from("erf")
+        }
+    );
+}
+
+/// Checks distribution functions against reference values and converts the
+/// units of their parameters.
+#[test]
+fn distributions() {
+    let cases = [
+        ("1 0 1 normpdf", 0.241_970_724_519_143_37),
+        ("-3 0 1 normcdf", 0.001_349_898_031_630_094_6),
+        ("0.975 0 1 norminv", 1.959_963_984_540_054),
+        ("2 5 tcdf", 0.949_030_260_585_070_8),
+        ("0.975 10 tinv", 2.228_138_851_986_275),
+        ("0.5 0.5 tinv", 0.0),
+        ("3 4 chi2pdf", 0.167_347_620_111_322_37),
+        ("0.95 1 chi2inv", 3.841_458_820_694_126),
+        ("1 2 exppdf", 0.303_265_329_856_316_7),
+        ("50 % 2 expinv", 1.386_294_361_119_890_6),
+        ("3 2 6 unifcdf", 0.25),
+        ("3 10 0.5 binompdf", 0.117_187_5),
+        ("3 10 0.5 binomcdf", 0.171_875),
+        ("2 3 poisspdf", 0.224_041_807_655_387_74),
+        ("2 3 poisscdf", 0.423_190_081_126_843_5),
+    ];
+    let mut ctx = eval::Context::new();
+    for (expr, expected) in cases {
+        assert_eq!(ctx.eval(expr), eval::Status::Ok, "{expr}");
+        let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+        assert!(f.unit.is_none(), "{expr}");
+        assert_relative_eq!(f.value, expected, max_relative = 1e-13);
+    }
+
+    assert_eq!(ctx.eval("1 in 25 mm 0.1 mm normcdf"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert!(f.unit.is_none());
+    assert_relative_eq!(f.value, 0.999_968_328_758_166_9, max_relative = 1e-13);
+
+    assert_eq!(ctx.eval("1 in 25 mm 0.1 mm normpdf"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 0.001_338_302_257_648_930_7, max_relative = 1e-12);
+    let u = f.unit.unwrap();
+    assert_eq!(u.numer(), &[]);
+    assert_eq!(u.denom(), &[MILLIMETER]);
+
+    assert_eq!(ctx.eval("99.865 % 25 mm 0.1 mm norminv"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, 25.299_997_699_270_34, max_relative = 1e-13);
+    let u = f.unit.unwrap();
+    assert_eq!(u.numer(), &[MILLIMETER]);
+    assert_eq!(u.denom(), &[]);
+
+    assert_eq!(
+        ctx.eval("1.5 0 1 norminv"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotAProbability),
+            word: String::from("norminv")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("1 0 0 normcdf"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotPositive),
+            word: String::from("normcdf")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("1 2 m tcdf"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotDimensionless),
+            word: String::from("tcdf")
+        }
+    );
+}
+
+/// Checks that seeding makes random numbers reproducible and that random
+/// numbers have the requested ranges and units.
+#[test]
+fn random_numbers() {
+    let mut ctx = eval::Context::new();
+    let mut draw = || {
+        assert_eq!(
+            ctx.eval("42 seed 10 mm 0.1 mm randn rand"),
+            eval::Status::Ok
+        );
+        let x = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+        let y = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+        (x, y)
+    };
+    let (x1, y1) = draw();
+    let (x2, y2) = draw();
+    assert_eq!(x1.value, x2.value);
+    assert_eq!(y1.value, y2.value);
+
+    assert!((0.0..1.0).contains(&x1.value));
+    assert!((9.0..11.0).contains(&y1.value));
+    let u = y1.unit.unwrap();
+    assert_eq!(u.numer(), &[MILLIMETER]);
+    assert_eq!(u.denom(), &[]);
+
+    assert_eq!(ctx.eval("1 m 10 mm randn"), eval::Status::Ok);
+    let x = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert!((0.9..1.1).contains(&x.value));
+    assert_eq!(x.unit.unwrap().numer(), &[METER]);
+
+    assert_eq!(ctx.eval("20 tempC 1 degC randn"), eval::Status::Ok);
+    let x = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert!((10.0..30.0).contains(&x.value));
+    assert_eq!(x.unit.unwrap().numer(), &[TEMP_CELSIUS]);
+
+    for _ in 0..100 {
+        assert_eq!(ctx.eval("-2 2 randint"), eval::Status::Ok);
+        match ctx.stack.pop() {
+            Ok(calc::stack::Item::Integer(n)) => assert!((-2..=2).contains(&n.value)),
+            item => panic!("expected an integer: {item:?}"),
+        }
+    }
+
+    assert_eq!(
+        ctx.eval("6 1 randint"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::BoundsReversed),
+            word: String::from("randint")
+        }
+    );
+}
+
+/// Builds vectors, does element-wise arithmetic with them, and converts them.
+#[test]
+fn vectors() {
+    let vector = |ctx: &mut eval::Context| match ctx.stack.pop() {
+        Ok(calc::stack::Item::Vector(v)) => v,
+        item => panic!("expected a vector: {item:?}"),
+    };
+    let mut ctx = eval::Context::new();
+
+    assert_eq!(ctx.eval("[ 1 m 50 cm 3 ft ] mm into"), eval::Status::Ok);
+    let v = vector(&mut ctx);
+    assert_relative_eq!(v.values[..], [1000.0, 500.0, 914.4][..]);
+    assert_eq!(v.unit.unwrap().numer(), &[MILLIMETER]);
+
+    assert_eq!(ctx.eval("[ 1 2 3 ] m 2 * [ 1 1 1 ] m +"), eval::Status::Ok);
+    let v = vector(&mut ctx);
+    assert_eq!(v.values, [3.0, 5.0, 7.0]);
+    assert_eq!(v.unit.unwrap().numer(), &[METER]);
+
+    assert_eq!(ctx.eval("1 2 3 3 vector [ 4 5 6 ] cross"), eval::Status::Ok);
+    assert_eq!(vector(&mut ctx).values, [-3.0, 6.0, -3.0]);
+
+    assert_eq!(ctx.eval("[ 1 2 3 ] m [ 4 5 6 ] s dot"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 32.0);
+    let u = f.unit.unwrap();
+    assert_eq!(u.numer(), &[METER, SECOND]);
+
+    assert_eq!(ctx.eval("[ 3 4 ] m norm"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 5.0);
+    assert_eq!(f.unit.unwrap().numer(), &[METER]);
+
+    assert_eq!(ctx.eval("[ 3 4 ] m unit"), eval::Status::Ok);
+    let v = vector(&mut ctx);
+    assert_eq!(v.values, [0.6, 0.8]);
+    assert!(v.unit.is_none());
+
+    assert_eq!(
+        ctx.eval("[ 1 2 ] [ 1 2 3 ] +"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::LengthMismatch),
+            word: String::from("+")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("[ 1 m 2 s ]"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::IncommensurableUnits(
+                    Some(Box::new(SECOND.as_unit())),
+                    Some(Box::new(METER.as_unit()))
+                )
+            )),
+            word: String::from("]")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("1 ]"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::UnmatchedBracket),
+            word: String::from("]")
+        }
+    );
+    ctx.stack.clear();
+    for expr in ["[ 1 [ 2 3 ] ]", "[ [ 1 2 ] 3 ]"] {
+        assert_eq!(
+            ctx.eval(expr),
+            eval::Status::Err {
+                error: eval::Error::Builtins(calc::builtins::Error::MixedBracket),
+                word: String::from("]")
+            },
+            "{expr}"
+        );
+        ctx.stack.clear();
+    }
+
+    // Items from below the [ can't be used up, even if the stack grows back.
+    for expr in ["1 [ 2 pop pop 3 ]", "1 [ 2 + ]"] {
+        assert_eq!(
+            ctx.eval(expr),
+            eval::Status::Err {
+                error: eval::Error::Builtins(calc::builtins::Error::Stack(
+                    calc::stack::Error::Underflow
+                )),
+                word: String::from("]")
+            },
+            "{expr}"
+        );
+        ctx.stack.clear();
+    }
+
+    assert_eq!(
+        ctx.eval("[ 0 0 ] m unit"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::ZeroVector),
+            word: String::from("unit")
+        }
+    );
+}
+
+/// Builds matrices and does linear algebra with them.
+#[test]
+fn matrices() {
+    let matrix = |ctx: &mut eval::Context| match ctx.stack.pop() {
+        Ok(calc::stack::Item::Matrix(m)) => m,
+        item => panic!("expected a matrix: {item:?}"),
+    };
+    let mut ctx = eval::Context::new();
+
+    assert_eq!(ctx.eval("[ [ 1 2 ] [ 3 4 ] ] m"), eval::Status::Ok);
+    let m = matrix(&mut ctx);
+    assert_eq!((m.rows, m.cols), (2, 2));
+    assert_eq!(m.values, [1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(m.unit.unwrap().numer(), &[METER]);
+
+    assert_eq!(
+        ctx.eval("[ [ 1 2 3 ] [ 4 5 6 ] ] dup transpose *"),
+        eval::Status::Ok
+    );
+    assert_eq!(matrix(&mut ctx).values, [14.0, 32.0, 32.0, 77.0]);
+
+    assert_eq!(ctx.eval("[ [ 1 2 ] [ 3 4 ] ] m det"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(f.value, -2.0);
+    assert_eq!(f.unit.unwrap().to_string(), "m²");
+
+    assert_eq!(ctx.eval("[ [ 4 7 ] [ 2 6 ] ] inv"), eval::Status::Ok);
+    let m = matrix(&mut ctx);
+    assert_relative_eq!(m.values[..], [0.6, -0.7, -0.2, 0.4][..], epsilon = 1e-15);
+
+    assert_eq!(
+        ctx.eval("[ [ 2 1 ] [ 1 3 ] ] s [ 3 5 ] m solve"),
+        eval::Status::Ok
+    );
+    let v = match ctx.stack.pop() {
+        Ok(calc::stack::Item::Vector(v)) => v,
+        item => panic!("expected a vector: {item:?}"),
+    };
+    assert_relative_eq!(v.values[..], [0.8, 1.4][..], epsilon = 1e-15);
+    assert_eq!(v.unit.unwrap().to_string(), "m⋅s⁻¹");
+
+    assert_eq!(ctx.eval("3 identity [ 1 2 3 ] *"), eval::Status::Ok);
+    match ctx.stack.pop() {
+        Ok(calc::stack::Item::Vector(v)) => assert_eq!(v.values, [1.0, 2.0, 3.0]),
+        item => panic!("expected a vector: {item:?}"),
+    }
+
+    assert_eq!(ctx.eval("[ [ 1 2 ] [ 3 4 ] ] m 1 0 elem"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 3.0);
+    assert_eq!(f.unit.unwrap().numer(), &[METER]);
+
+    assert_eq!(
+        ctx.eval("[ [ 1 2 ] [ 2 4 ] ] inv"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Singular),
+            word: String::from("inv")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("[ [ 1 2 3 ] [ 4 5 6 ] ] det"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NotSquare),
+            word: String::from("det")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("[ [ 1 2 ] [ 3 4 ] ] [ [ 1 2 3 ] ] *"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::ShapeMismatch),
+            word: String::from("*")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("[ [ 1 2 ] [ 3 4 ] ] 2 0 elem"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::IndexOutOfRange),
+            word: String::from("elem")
+        }
+    );
+}
+
+/// Evaluates polynomials, finds their roots, and multiplies and divides them.
+#[test]
+fn polynomials() {
+    let vector = |ctx: &mut eval::Context| match ctx.stack.pop() {
+        Ok(calc::stack::Item::Vector(v)) => v,
+        item => panic!("expected a vector: {item:?}"),
+    };
+    let mut ctx = eval::Context::new();
+
+    assert_eq!(ctx.eval("1 -3 2 3 roots"), eval::Status::Ok);
+    assert_eq!(ctx.stack.height(), 2);
+    let b = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    let a = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(a.value, 1.0);
+    assert_relative_eq!(b.value, 2.0);
+
+    assert_eq!(ctx.eval("[ 1 -3 2 ] 3 polyval"), eval::Status::Ok);
+    let f = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_eq!(f.value, 2.0);
+
+    // Free fall: when does something thrown up at 10 m/s from 20 m land?
+    assert_eq!(
+        ctx.eval("-4.903325 m s -2 ** * 10 m s / 20 m 3 roots"),
+        eval::Status::Ok
+    );
+    let t = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(t.value, 3.282_168, epsilon = 1e-6);
+    assert_eq!(t.unit.unwrap().numer(), &[SECOND]);
+    ctx.stack.clear();
+
+    assert_eq!(ctx.eval("1 0 1 3 croots"), eval::Status::Ok);
+    assert_eq!(vector(&mut ctx).values, [0.0, 1.0]);
+    assert_eq!(vector(&mut ctx).values, [0.0, -1.0]);
+
+    assert_eq!(ctx.eval("[ 1 -1 ] [ 1 -2 ] polymul"), eval::Status::Ok);
+    assert_eq!(vector(&mut ctx).values, [1.0, -3.0, 2.0]);
+
+    assert_eq!(
+        ctx.eval("[ 1 -3 2 5 ] m [ 1 -1 ] s polydiv"),
+        eval::Status::Ok
+    );
+    let r = vector(&mut ctx);
+    assert_eq!(r.values, [5.0]);
+    assert_eq!(r.unit.unwrap().numer(), &[METER]);
+    let q = vector(&mut ctx);
+    assert_eq!(q.values, [1.0, -2.0, 0.0]);
+    assert_eq!(q.unit.unwrap().to_string(), "m⋅s⁻¹");
+
+    assert_eq!(ctx.eval("[ 1 -3 2 ] [ 0 1 ] polydiv"), eval::Status::Ok);
+    assert_eq!(vector(&mut ctx).values, [0.0]);
+    assert_eq!(vector(&mut ctx).values, [1.0, -3.0, 2.0]);
+
+    assert_eq!(ctx.eval("1 m 2 s 2 roots"), eval::Status::Ok);
+    let r = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(r.value, -2.0);
+    assert_eq!(r.unit.unwrap().to_string(), "s⋅m⁻¹");
+
+    assert!(matches!(
+        ctx.eval("1 m 2 s 3 kg 3 roots"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Units(
+                calc::units::Error::IncommensurableUnits(_, _)
+            )),
+            ..
+        }
+    ));
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("0 0 2 roots"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::ZeroPolynomial),
+            word: String::from("roots")
+        }
+    );
+}
+
+/// Finds roots of blocks of words.
+#[test]
+fn root_solver() {
+    let mut ctx = eval::Context::new();
+
+    assert_eq!(ctx.eval("{ dup * 2 - } 1 solve"), eval::Status::Ok);
+    let x = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(x.value, std::f64::consts::SQRT_2);
+
+    // What diameter of copper wire has a resistance of 0.1 Ω over 5 m?
+    assert_eq!(
+        ctx.eval("{ dup * pi * 4 / 1.68e-8 ohm m * 5 m * swap / 0.1 ohm - } 1 mm solve"),
+        eval::Status::Ok
+    );
+    let d = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(d.value, 1.034_18, epsilon = 1e-5);
+    assert_eq!(d.unit.unwrap().numer(), &[MILLIMETER]);
+    assert!(ctx.stack.is_empty());
+
+    assert_eq!(
+        ctx.eval("{ dup * 1 + } 1 solve"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NoConvergence),
+            word: String::from("solve")
+        }
+    );
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("{ pop pop } 1 solve"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::Block {
+                word: String::from("pop"),
+                error: Box::new(eval::Error::Builtins(calc::builtins::Error::Stack(
+                    calc::stack::Error::Underflow
+                )))
+            }),
+            word: String::from("solve")
+        }
+    );
+    assert_eq!(ctx.stack.height(), 2);
+    ctx.stack.clear();
+    assert_eq!(
+        ctx.eval("{ pop } 1 solve"),
+        eval::Status::Err {
+            error: eval::Error::Builtins(calc::builtins::Error::NoResult),
+            word: String::from("solve")
+        }
+    );
+    ctx.stack.clear();
+
+    // A root that the block touches without crossing.
+    assert_eq!(ctx.eval("{ dup * } 5 solve"), eval::Status::Ok);
+    let x = popf!(ctx.stack).unwrap_or_else(|e| panic!("popf: {e:?}"));
+    assert_relative_eq!(x.value, 0.0, epsilon = 1e-6);
+
+    assert_eq!(
+        ctx.eval("{ dup * 2 -"),
+        eval::Status::Err {
+            error: eval::Error::UnmatchedBrace,
+            word: String::from("{")
+        }
+    );
+}